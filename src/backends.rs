@@ -5,6 +5,11 @@
 
 #[cfg(feature = "cpal")]
 mod cpal_backend;
+mod manual_backend;
+mod offline_backend;
 
 #[cfg(feature = "cpal")]
 pub use cpal_backend::*;
+pub use manual_backend::*;
+pub use offline_backend::*;
+pub(crate) use offline_backend::{write_wav, DEFAULT_FRAMES_PER_BATCH};
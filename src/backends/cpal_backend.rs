@@ -25,6 +25,7 @@ pub struct CpalBackend {
     buffer_size: CpalBufferSize,
     device: cpal::Device,
     stream: Option<cpal::Stream>,
+    use_f32_samples: bool,
 }
 
 impl CpalBackend {
@@ -47,6 +48,7 @@ impl CpalBackend {
             buffer_size: CpalBufferSize::Default,
             device,
             stream: None,
+            use_f32_samples: false,
             sample_format,
         })
     }
@@ -70,6 +72,7 @@ impl CpalBackend {
             buffer_size,
             device,
             stream: None,
+            use_f32_samples: false,
             sample_format,
         })
     }
@@ -88,12 +91,24 @@ impl CpalBackend {
             buffer_size,
             device,
             stream: None,
+            use_f32_samples: false,
             sample_format,
         }
     }
 }
 
 impl CpalBackend {
+    /// Mix and output f32 samples instead of i16 samples.
+    ///
+    /// Sounds that provide f32 samples (see
+    /// [Sound::next_samples_f32][crate::Sound::next_samples_f32]) then keep
+    /// their precision and mixed sounds are only clipped when converted to the
+    /// output sample format. Defaults to false. Only affects streams started
+    /// after this is called.
+    pub fn set_use_f32_samples(&mut self, use_f32_samples: bool) {
+        self.use_f32_samples = use_f32_samples;
+    }
+
     /// Start a cpal output stream and connect it to the returned Manager.
     ///
     /// Only a single stream is supported at a time per CpalBackend object.
@@ -119,13 +134,13 @@ impl CpalBackend {
         let stream = match self.sample_format {
             cpal::SampleFormat::I16 => self.device.build_output_stream(
                 &config,
-                make_data_callback::<i16>(renderer, self.channel_count),
+                make_data_callback::<i16>(renderer, self.channel_count, self.use_f32_samples),
                 error_callback,
                 timeout,
             )?,
             cpal::SampleFormat::F32 => self.device.build_output_stream(
                 &config,
-                make_data_callback::<f32>(renderer, self.channel_count),
+                make_data_callback::<f32>(renderer, self.channel_count, self.use_f32_samples),
                 error_callback,
                 timeout,
             )?,
@@ -149,32 +164,68 @@ impl CpalBackend {
     }
 }
 
-/// Converts Awedio's internal i16 samples to the format required by the audio device (type T).
+/// Converts Awedio's internal i16 (or f32 if `use_f32_samples`) samples to the
+/// format required by the audio device (type T).
 fn make_data_callback<T>(
     mut renderer: Renderer,
     channel_count: u16,
+    use_f32_samples: bool,
 ) -> impl FnMut(&mut [T], &cpal::OutputCallbackInfo)
 where
-    T: Sample + FromSample<i16>,
+    T: Sample + FromSample<i16> + FromSample<f32>,
 {
+    // Allocated here so the callback does not allocate. Device buffers larger
+    // than this are filled in parts.
+    let mut samples: Vec<i16> = vec![0; CALLBACK_CHUNK_LEN];
+    let mut samples_f32: Vec<f32> = vec![0.0; CALLBACK_CHUNK_LEN];
     move |buffer: &mut [T], _info: &cpal::OutputCallbackInfo| {
-        assert!(buffer.len() % channel_count as usize == 0);
+        assert!(buffer.len().is_multiple_of(channel_count as usize));
 
         renderer.on_start_of_batch();
 
-        buffer.fill_with(|| {
-            let sample = renderer
-                .next_sample()
-                .expect("renderer should never return an Error");
-            match sample {
-                crate::NextSample::Sample(s) => T::from_sample(s),
-                crate::NextSample::MetadataChanged => {
-                    unreachable!("we never change metadata mid-batch")
+        for buffer in buffer.chunks_mut(CALLBACK_CHUNK_LEN) {
+            if use_f32_samples {
+                let samples = &mut samples_f32[..buffer.len()];
+                fill_from_renderer(&mut renderer, samples, |r, b| r.next_samples_f32(b));
+                for (out, sample) in buffer.iter_mut().zip(samples.iter()) {
+                    *out = T::from_sample(*sample);
                 }
-                crate::NextSample::Paused => T::from_sample(0), // TODO: implement pausing
-                crate::NextSample::Finished => T::from_sample(0), // TODO: implement finishing
+            } else {
+                let samples = &mut samples[..buffer.len()];
+                fill_from_renderer(&mut renderer, samples, |r, b| r.next_samples(b));
+                for (out, sample) in buffer.iter_mut().zip(samples.iter()) {
+                    *out = T::from_sample(*sample);
+                }
+            }
+        }
+    }
+}
+
+/// The most samples converted at once in the data callback.
+const CALLBACK_CHUNK_LEN: usize = 4096;
+
+/// Fill `samples` from `renderer` using `read`.
+fn fill_from_renderer<S: Copy + Default>(
+    renderer: &mut Renderer,
+    samples: &mut [S],
+    read: impl Fn(&mut Renderer, &mut [S]) -> Result<(usize, crate::NextSample), crate::Error>,
+) {
+    let mut num_filled = 0;
+    while num_filled < samples.len() {
+        let (num_read, next) = read(renderer, &mut samples[num_filled..])
+            .expect("renderer should never return an Error");
+        num_filled += num_read;
+        match next {
+            crate::NextSample::Sample(_) => {}
+            crate::NextSample::MetadataChanged => {
+                unreachable!("we never change metadata mid-batch")
+            }
+            crate::NextSample::Paused | crate::NextSample::Finished => {
+                // TODO: implement pausing and finishing
+                samples[num_filled..].fill(S::default());
+                break;
             }
-        });
+        }
     }
 }
 
@@ -0,0 +1,178 @@
+//! [`ManualBackend`] lets the caller pull samples from a [`Manager`]
+//! deterministically (e.g. in tests).
+
+use crate::{
+    manager::{BackendSource, Manager, Renderer},
+    NextSample, Sound,
+};
+
+/// A backend where samples are only rendered when explicitly requested.
+///
+/// Each pull acts like a single callback from an audio device: the Renderer
+/// is informed of the start of a batch and then exactly the requested number
+/// of frames are rendered. Like other backends, silence is output when no
+/// sounds are playing. This makes it possible to test code that uses a
+/// [Manager] without audio hardware or waiting on real time.
+///
+/// ```
+/// use awedio::{backends::ManualBackend, sounds::MemorySound};
+/// use std::sync::Arc;
+///
+/// let mut backend = ManualBackend::new(1, 1000);
+/// let mut manager = backend.start();
+/// manager.play(Box::new(MemorySound::from_samples(Arc::new(vec![1, 2]), 1, 1000)));
+/// assert_eq!(backend.pull_frames(3), vec![1, 2, 0]);
+/// ```
+pub struct ManualBackend {
+    channel_count: u16,
+    sample_rate: u32,
+    frames_per_batch: usize,
+    renderer: Option<Renderer>,
+}
+
+/// The default number of frames returned by [ManualBackend::pull_batch].
+const DEFAULT_PULL_BATCH_FRAMES: usize = 512;
+
+impl ManualBackend {
+    /// Create a new backend with the given output channel count and sample
+    /// rate.
+    pub fn new(channel_count: u16, sample_rate: u32) -> ManualBackend {
+        ManualBackend {
+            channel_count,
+            sample_rate,
+            frames_per_batch: DEFAULT_PULL_BATCH_FRAMES,
+            renderer: None,
+        }
+    }
+
+    /// Set the number of frames rendered by [pull_batch][Self::pull_batch].
+    /// Defaults to 512.
+    pub fn set_frames_per_batch(&mut self, frames_per_batch: usize) {
+        self.frames_per_batch = frames_per_batch;
+    }
+
+    /// The number of frames rendered by [pull_batch][Self::pull_batch].
+    pub fn frames_per_batch(&self) -> usize {
+        self.frames_per_batch
+    }
+
+    /// The output channel count.
+    pub fn channel_count(&self) -> u16 {
+        self.channel_count
+    }
+
+    /// The output sample rate.
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Create the Renderer that samples will be pulled from and connect it to
+    /// the returned Manager.
+    ///
+    /// Calling start again replaces the previous Renderer.
+    pub fn start(&mut self) -> Manager {
+        let (manager, renderer) = Manager::new();
+        self.renderer = Some(renderer);
+        self.set_output_format(self.channel_count, self.sample_rate);
+        manager
+    }
+
+    /// Change the output channel count and sample rate. Playing sounds are
+    /// converted to the new format from the next pull.
+    pub fn set_output_format(&mut self, channel_count: u16, sample_rate: u32) {
+        self.channel_count = channel_count;
+        self.sample_rate = sample_rate;
+        if let Some(renderer) = &mut self.renderer {
+            renderer.set_output_channel_count_and_sample_rate(channel_count, sample_rate);
+            let Ok(NextSample::MetadataChanged) = renderer.next_sample() else {
+                panic!("expected MetadataChanged event")
+            };
+        }
+    }
+
+    /// Pull [frames_per_batch][Self::frames_per_batch] frames as a single
+    /// batch.
+    ///
+    /// # Panics
+    ///
+    /// Panics if [start][ManualBackend::start] has not been called.
+    pub fn pull_batch(&mut self) -> Vec<i16> {
+        self.pull_frames(self.frames_per_batch)
+    }
+
+    /// Pull `num_frames` frames as a single batch.
+    ///
+    /// # Panics
+    ///
+    /// Panics if [start][ManualBackend::start] has not been called.
+    pub fn pull_frames(&mut self, num_frames: usize) -> Vec<i16> {
+        let mut samples = vec![0; num_frames * self.channel_count as usize];
+        self.pull_into(&mut samples);
+        samples
+    }
+
+    /// Fill `buffer` as a single batch.
+    ///
+    /// Returns `NextSample::Sample` if the Renderer filled all of `buffer` or
+    /// the `Paused` or `Finished` returned by the Renderer in which case the
+    /// rest of `buffer` is filled with silence.
+    ///
+    /// # Panics
+    ///
+    /// Panics if [start][ManualBackend::start] has not been called or if the
+    /// length of `buffer` is not a multiple of the channel count.
+    pub fn pull_into(&mut self, buffer: &mut [i16]) -> NextSample {
+        self.pull_with(buffer, Renderer::next_samples, crate::sound::filled)
+    }
+
+    /// Same as [pull_into][Self::pull_into] but pulls f32 samples (see
+    /// [Sound::next_samples_f32]).
+    ///
+    /// # Panics
+    ///
+    /// Panics if [start][ManualBackend::start] has not been called or if the
+    /// length of `buffer` is not a multiple of the channel count.
+    pub fn pull_into_f32(&mut self, buffer: &mut [f32]) -> NextSample {
+        self.pull_with(buffer, Renderer::next_samples_f32, crate::sound::filled_f32)
+    }
+
+    /// Shared implementation of `pull_into` and `pull_into_f32` which reads
+    /// samples with `read` and returns the result of `filled` if all of
+    /// `buffer` was filled.
+    fn pull_with<S: Copy + Default>(
+        &mut self,
+        buffer: &mut [S],
+        read: impl Fn(&mut Renderer, &mut [S]) -> Result<(usize, NextSample), crate::Error>,
+        filled: impl Fn(&[S]) -> (usize, NextSample),
+    ) -> NextSample {
+        assert!(buffer.len().is_multiple_of(self.channel_count as usize));
+        let renderer = self
+            .renderer
+            .as_mut()
+            .expect("start must be called before pulling samples");
+
+        renderer.on_start_of_batch();
+
+        let mut num_filled = 0;
+        while num_filled < buffer.len() {
+            let (num_read, next) = read(renderer, &mut buffer[num_filled..])
+                .expect("renderer should never return an Error");
+            num_filled += num_read;
+            match next {
+                NextSample::Sample(_) => {}
+                NextSample::MetadataChanged => {
+                    unreachable!("we never change metadata mid-batch")
+                }
+                NextSample::Paused | NextSample::Finished => {
+                    buffer[num_filled..].fill(S::default());
+                    return next;
+                }
+            }
+        }
+        filled(buffer).1
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/manual_backend.rs"]
+mod tests;
@@ -0,0 +1,273 @@
+//! [`OfflineBackend`] renders the output of a [`Manager`] to a WAV or raw PCM
+//! writer as fast as possible instead of playing it on a device.
+
+use crate::{
+    manager::{BackendSource, Manager, Renderer},
+    NextSample, Sound,
+};
+use std::{
+    io::{Seek, SeekFrom, Write},
+    path::Path,
+    time::Duration,
+};
+
+/// The default number of frames rendered per batch.
+pub(crate) const DEFAULT_FRAMES_PER_BATCH: usize = 1024;
+
+/// A backend that renders to a writer instead of an output device.
+///
+/// Useful to bake a mix into a file or to compare rendered output in tests
+/// without a sound card. Samples are written as 16 bit little endian
+/// interleaved PCM, either raw or with a WAV header.
+///
+/// Rendering is driven by calling one of the `render_*` functions after
+/// [start][OfflineBackend::start]. Sounds played on the returned Manager
+/// before rendering are included from the first sample. Rendering stops once
+/// the Renderer returns `Finished` (all sounds finished and the Manager was
+/// dropped) or after the given duration. If no duration is given rendering
+/// also stops when the Renderer returns `Paused` (no sounds are playing) since
+/// otherwise it would never end.
+pub struct OfflineBackend {
+    channel_count: u16,
+    sample_rate: u32,
+    frames_per_batch: usize,
+    renderer: Option<Renderer>,
+}
+
+impl OfflineBackend {
+    /// Create a new backend that renders with the given channel count and
+    /// sample rate.
+    pub fn new(channel_count: u16, sample_rate: u32) -> OfflineBackend {
+        OfflineBackend {
+            channel_count,
+            sample_rate,
+            frames_per_batch: DEFAULT_FRAMES_PER_BATCH,
+            renderer: None,
+        }
+    }
+
+    /// Set the number of frames rendered between each call to
+    /// `on_start_of_batch`. Commands sent with a
+    /// [Controller][crate::sounds::wrappers::Controller] are applied at the
+    /// start of each batch. Defaults to 1024.
+    pub fn set_frames_per_batch(&mut self, frames_per_batch: usize) {
+        assert!(frames_per_batch > 0);
+        self.frames_per_batch = frames_per_batch;
+    }
+
+    /// Create the Renderer that will be rendered and connect it to the
+    /// returned Manager.
+    ///
+    /// Calling start again replaces the previous Renderer.
+    pub fn start(&mut self) -> Manager {
+        let (manager, mut renderer) = Manager::new();
+        renderer.set_output_channel_count_and_sample_rate(self.channel_count, self.sample_rate);
+        let Ok(NextSample::MetadataChanged) = renderer.next_sample() else {
+            panic!("expected MetadataChanged event")
+        };
+        self.renderer = Some(renderer);
+        manager
+    }
+
+    /// Render raw 16 bit little endian interleaved samples to `writer`.
+    ///
+    /// Returns the number of frames written.
+    ///
+    /// # Panics
+    ///
+    /// Panics if [start][OfflineBackend::start] has not been called.
+    pub fn render_raw<W: Write>(
+        &mut self,
+        writer: &mut W,
+        max_duration: Option<Duration>,
+    ) -> Result<u64, crate::Error> {
+        let renderer = self
+            .renderer
+            .as_mut()
+            .expect("start must be called before rendering");
+        render(renderer, writer, self.frames_per_batch, max_duration)
+    }
+
+    /// Render a WAV file to `writer`.
+    ///
+    /// Returns the number of frames written.
+    ///
+    /// # Panics
+    ///
+    /// Panics if [start][OfflineBackend::start] has not been called.
+    pub fn render_wav<W: Write + Seek>(
+        &mut self,
+        writer: &mut W,
+        max_duration: Option<Duration>,
+    ) -> Result<u64, crate::Error> {
+        let renderer = self
+            .renderer
+            .as_mut()
+            .expect("start must be called before rendering");
+        write_wav(renderer, writer, self.frames_per_batch, max_duration)
+    }
+
+    /// Render a WAV file to `path`, replacing it if it exists.
+    ///
+    /// Returns the number of frames written.
+    ///
+    /// # Panics
+    ///
+    /// Panics if [start][OfflineBackend::start] has not been called.
+    pub fn render_wav_file<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        max_duration: Option<Duration>,
+    ) -> Result<u64, crate::Error> {
+        let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+        let num_frames = self.render_wav(&mut writer, max_duration)?;
+        writer.flush()?;
+        Ok(num_frames)
+    }
+}
+
+const WAV_HEADER_LEN: u32 = 44;
+
+/// Write `sound` as a WAV file with a header matching the sound's current
+/// channel count and sample rate.
+///
+/// An error is returned if `sound` changes its channel count or sample rate.
+pub(crate) fn write_wav<S: Sound + ?Sized, W: Write + Seek>(
+    sound: &mut S,
+    writer: &mut W,
+    frames_per_batch: usize,
+    max_duration: Option<Duration>,
+) -> Result<u64, crate::Error> {
+    let channel_count = sound.channel_count();
+    let sample_rate = sound.sample_rate();
+    let start = writer.stream_position()?;
+    write_wav_header(writer, channel_count, sample_rate, 0)?;
+    let num_frames = render(sound, writer, frames_per_batch, max_duration)?;
+    let end = writer.stream_position()?;
+    let data_len = wav_data_len(num_frames, channel_count)?;
+    writer.seek(SeekFrom::Start(start))?;
+    write_wav_header(writer, channel_count, sample_rate, data_len)?;
+    writer.seek(SeekFrom::Start(end))?;
+    Ok(num_frames)
+}
+
+/// The length in bytes of the samples of `num_frames` frames if it fits in a
+/// WAV file.
+fn wav_data_len(num_frames: u64, channel_count: u16) -> std::io::Result<u32> {
+    num_frames
+        .checked_mul(channel_count as u64 * 2)
+        .and_then(|data_len| u32::try_from(data_len).ok())
+        // The RIFF chunk size includes the rest of the header so must fit too.
+        .filter(|data_len| *data_len <= u32::MAX - (WAV_HEADER_LEN - 8))
+        .ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "rendered audio is too long for a WAV file",
+            )
+        })
+}
+
+fn write_wav_header<W: Write>(
+    writer: &mut W,
+    channel_count: u16,
+    sample_rate: u32,
+    data_len: u32,
+) -> std::io::Result<()> {
+    const BITS_PER_SAMPLE: u16 = 16;
+    const PCM_FORMAT: u16 = 1;
+    let block_align = channel_count * BITS_PER_SAMPLE / 8;
+    let byte_rate = sample_rate * block_align as u32;
+
+    writer.write_all(b"RIFF")?;
+    writer.write_all(&(WAV_HEADER_LEN - 8 + data_len).to_le_bytes())?;
+    writer.write_all(b"WAVE")?;
+    writer.write_all(b"fmt ")?;
+    writer.write_all(&16_u32.to_le_bytes())?;
+    writer.write_all(&PCM_FORMAT.to_le_bytes())?;
+    writer.write_all(&channel_count.to_le_bytes())?;
+    writer.write_all(&sample_rate.to_le_bytes())?;
+    writer.write_all(&byte_rate.to_le_bytes())?;
+    writer.write_all(&block_align.to_le_bytes())?;
+    writer.write_all(&BITS_PER_SAMPLE.to_le_bytes())?;
+    writer.write_all(b"data")?;
+    writer.write_all(&data_len.to_le_bytes())?;
+    Ok(())
+}
+
+/// Pull batches of samples from `sound` and write them to
+/// `writer` until the sound stops or `max_duration` has been rendered.
+///
+/// Returns the number of frames written.
+fn render<S: Sound + ?Sized, W: Write>(
+    sound: &mut S,
+    writer: &mut W,
+    frames_per_batch: usize,
+    max_duration: Option<Duration>,
+) -> Result<u64, crate::Error> {
+    let channel_count = sound.channel_count();
+    let sample_rate = sound.sample_rate();
+    let max_frames = max_duration
+        .map(|duration| crate::utils::duration_to_num_samples(duration, 1, sample_rate));
+    let mut samples = vec![0_i16; frames_per_batch * channel_count as usize];
+    let mut bytes = Vec::with_capacity(samples.len() * 2);
+    let mut num_frames: u64 = 0;
+
+    loop {
+        let batch_frames = match max_frames {
+            Some(max_frames) => (max_frames - num_frames).min(frames_per_batch as u64) as usize,
+            None => frames_per_batch,
+        };
+        if batch_frames == 0 {
+            break;
+        }
+        let batch = &mut samples[..batch_frames * channel_count as usize];
+
+        sound.on_start_of_batch();
+        let mut num_filled = 0;
+        let mut stop = false;
+        while num_filled < batch.len() {
+            let (num_read, next) = sound.next_samples(&mut batch[num_filled..])?;
+            num_filled += num_read;
+            match next {
+                NextSample::Sample(_) => {}
+                NextSample::MetadataChanged => {
+                    if sound.channel_count() != channel_count || sound.sample_rate() != sample_rate
+                    {
+                        return Err(std::io::Error::new(
+                            std::io::ErrorKind::InvalidData,
+                            "channel count or sample rate changed while rendering",
+                        )
+                        .into());
+                    }
+                }
+                NextSample::Paused if max_frames.is_some() => {
+                    // Render silence until the duration is reached.
+                    batch[num_filled..].fill(0);
+                    num_filled = batch.len();
+                }
+                NextSample::Paused | NextSample::Finished => {
+                    stop = true;
+                    break;
+                }
+            }
+        }
+        // Only whole frames are written.
+        let num_filled = num_filled - num_filled % channel_count as usize;
+
+        bytes.clear();
+        for sample in &batch[..num_filled] {
+            bytes.extend_from_slice(&sample.to_le_bytes());
+        }
+        writer.write_all(&bytes)?;
+        num_frames += (num_filled / channel_count as usize) as u64;
+
+        if stop {
+            break;
+        }
+    }
+    Ok(num_frames)
+}
+
+#[cfg(test)]
+#[path = "./tests/offline_backend.rs"]
+mod tests;
@@ -0,0 +1,68 @@
+use std::sync::Arc;
+
+use super::*;
+use crate::{sounds::MemorySound, tests::ConstantValueSound};
+
+#[test]
+fn silence_without_sounds() {
+    let mut backend = ManualBackend::new(2, 1000);
+    let _manager = backend.start();
+    assert_eq!(backend.pull_frames(2), vec![0; 4]);
+}
+
+#[test]
+fn play_and_clear_between_batches() {
+    let mut backend = ManualBackend::new(2, 1000);
+    backend.set_frames_per_batch(2);
+    let mut manager = backend.start();
+    manager.play(Box::new(ConstantValueSound::new(3)));
+    assert_eq!(backend.pull_batch(), vec![3; 4]);
+    manager.play(Box::new(ConstantValueSound::new(4)));
+    assert_eq!(backend.pull_batch(), vec![7; 4]);
+    manager.clear();
+    assert_eq!(backend.pull_batch(), vec![0; 4]);
+}
+
+#[test]
+fn pull_into_reports_paused_and_finished() {
+    let mut backend = ManualBackend::new(1, 1000);
+    let mut manager = backend.start();
+    manager.play(Box::new(MemorySound::from_samples(
+        Arc::new(vec![1, 2, 3]),
+        1,
+        1000,
+    )));
+    let mut buffer = [9; 2];
+    assert_eq!(backend.pull_into(&mut buffer), NextSample::Sample(2));
+    assert_eq!(buffer, [1, 2]);
+    assert_eq!(backend.pull_into(&mut buffer), NextSample::Paused);
+    assert_eq!(buffer, [3, 0]);
+    drop(manager);
+    assert_eq!(backend.pull_into(&mut buffer), NextSample::Finished);
+    assert_eq!(buffer, [0, 0]);
+}
+
+#[test]
+fn output_format_can_change() {
+    let mut backend = ManualBackend::new(1, 1000);
+    let mut manager = backend.start();
+    manager.play(Box::new(MemorySound::from_samples(
+        Arc::new(vec![1, 2, 3, 4]),
+        1,
+        1000,
+    )));
+    assert_eq!(backend.pull_frames(2), vec![1, 2]);
+    backend.set_output_format(2, 1000);
+    assert_eq!(backend.pull_frames(2), vec![3, 3, 4, 4]);
+}
+
+#[test]
+fn pull_f32() {
+    let mut backend = ManualBackend::new(1, 1000);
+    let mut manager = backend.start();
+    manager.play(Box::new(ConstantValueSound::new(i16::MAX)));
+    manager.play(Box::new(ConstantValueSound::new(i16::MAX)));
+    let mut buffer = [0.0; 2];
+    backend.pull_into_f32(&mut buffer);
+    assert_eq!(buffer, [2.0, 2.0]);
+}
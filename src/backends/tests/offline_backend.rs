@@ -0,0 +1,88 @@
+use std::{io::Cursor, sync::Arc, time::Duration};
+
+use super::*;
+use crate::{sounds::MemorySound, tests::ConstantValueSound};
+
+fn samples_from_bytes(bytes: &[u8]) -> Vec<i16> {
+    bytes
+        .chunks_exact(2)
+        .map(|b| i16::from_le_bytes([b[0], b[1]]))
+        .collect()
+}
+
+#[test]
+fn render_raw_for_duration() {
+    let mut backend = OfflineBackend::new(2, 1000);
+    backend.set_frames_per_batch(3);
+    let mut manager = backend.start();
+    manager.play(Box::new(ConstantValueSound::new(7)));
+    let mut output = Vec::new();
+    let num_frames = backend
+        .render_raw(&mut output, Some(Duration::from_millis(10)))
+        .unwrap();
+    assert_eq!(num_frames, 10);
+    assert_eq!(samples_from_bytes(&output), vec![7; 20]);
+}
+
+#[test]
+fn render_raw_pads_paused_with_silence() {
+    let mut backend = OfflineBackend::new(1, 1000);
+    let mut manager = backend.start();
+    manager.play(Box::new(MemorySound::from_samples(
+        Arc::new(vec![1, 2, 3]),
+        1,
+        1000,
+    )));
+    let mut output = Vec::new();
+    let num_frames = backend
+        .render_raw(&mut output, Some(Duration::from_millis(5)))
+        .unwrap();
+    assert_eq!(num_frames, 5);
+    assert_eq!(samples_from_bytes(&output), vec![1, 2, 3, 0, 0]);
+}
+
+#[test]
+fn render_wav_until_finished() {
+    let mut backend = OfflineBackend::new(1, 8000);
+    let mut manager = backend.start();
+    manager.play(Box::new(MemorySound::from_samples(
+        Arc::new(vec![1, -2, 3, -4]),
+        1,
+        8000,
+    )));
+    drop(manager);
+    let mut output = Cursor::new(Vec::new());
+    let num_frames = backend.render_wav(&mut output, None).unwrap();
+    assert_eq!(num_frames, 4);
+    let output = output.into_inner();
+    assert_eq!(output.len(), 44 + 8);
+    assert_eq!(&output[0..4], b"RIFF");
+    assert_eq!(u32::from_le_bytes(output[4..8].try_into().unwrap()), 36 + 8);
+    assert_eq!(&output[8..16], b"WAVEfmt ");
+    // channel count, sample rate
+    assert_eq!(u16::from_le_bytes(output[22..24].try_into().unwrap()), 1);
+    assert_eq!(u32::from_le_bytes(output[24..28].try_into().unwrap()), 8000);
+    assert_eq!(&output[36..40], b"data");
+    assert_eq!(u32::from_le_bytes(output[40..44].try_into().unwrap()), 8);
+    assert_eq!(samples_from_bytes(&output[44..]), vec![1, -2, 3, -4]);
+}
+
+#[test]
+fn write_wav_of_sound() {
+    let path = std::env::temp_dir().join(format!("awedio_write_wav_{}.wav", std::process::id()));
+    let sound = MemorySound::from_samples(Arc::new(vec![5, 6, 7, 8]), 2, 1000);
+    sound.write_wav(&path).unwrap();
+    let output = std::fs::read(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(u16::from_le_bytes(output[22..24].try_into().unwrap()), 2);
+    assert_eq!(u32::from_le_bytes(output[24..28].try_into().unwrap()), 1000);
+    assert_eq!(samples_from_bytes(&output[44..]), vec![5, 6, 7, 8]);
+}
+
+#[test]
+fn wav_data_len_leaves_room_for_header() {
+    let max_frames = (u32::MAX as u64 - 36) / 4;
+    assert_eq!(wav_data_len(max_frames, 2).unwrap(), max_frames as u32 * 4);
+    assert!(wav_data_len(max_frames + 1, 2).is_err());
+    assert!(wav_data_len(u64::MAX, 2).is_err());
+}
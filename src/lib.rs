@@ -12,6 +12,11 @@ mod sound;
 #[cfg(test)]
 mod tests;
 
+// Make the assertions of the assert-no-alloc feature take effect in tests.
+#[cfg(all(test, feature = "assert-no-alloc"))]
+#[global_allocator]
+static ALLOCATOR: assert_no_alloc::AllocDisabler = assert_no_alloc::AllocDisabler;
+
 pub use error::Error;
 pub use sound::NextSample;
 pub use sound::Sound;
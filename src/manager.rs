@@ -1,19 +1,55 @@
 //! Manager is how sounds are played on a backend.
 mod backend_source;
+mod events;
+mod group;
 mod renderer;
+mod sound_handle;
 
+use crate::sounds::wrappers::CommandQueueFull;
 use crate::sounds::wrappers::Controllable;
 use crate::sounds::wrappers::Controller;
+use crate::sounds::wrappers::ResampleQuality;
+use crate::sounds::MasterStage;
+use crate::sounds::MasterStageProcessor;
+use crate::sounds::MixedSound;
+use crate::sounds::MixerCommand;
+use crate::sounds::SharedOutputFormat;
+use crate::sounds::SoundCollector;
 use crate::sounds::SoundMixer;
 use crate::Sound;
 pub use backend_source::BackendSource;
+use events::Events;
+pub use events::{SoundEvent, SoundId};
+pub use group::Group;
 pub use renderer::Renderer;
+pub use sound_handle::{SoundHandle, SoundStatus};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::Duration;
 
 /// A Manager can play sounds by rendering sounds on a [`Renderer`] for a
 /// backend.
 #[derive(Clone)]
 pub struct Manager {
-    mixer_controller: Controller<SoundMixer>,
+    mixer_controller: Controller<SoundMixer, MixerCommand>,
+    output_format: Arc<SharedOutputFormat>,
+    frame_clock: Arc<AtomicU64>,
+    events: Arc<Events>,
+    collector: Arc<SoundCollector>,
+    /// The quality sounds are converted with by [Manager::play].
+    resample_quality: Arc<Mutex<ResampleQuality>>,
+}
+
+/// When a sound played with [Manager::play_at] starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StartTime {
+    /// Start when the output reaches this frame as counted by
+    /// [Manager::current_frame].
+    Frame(u64),
+    /// Start this long after the start of the batch in which the audio thread
+    /// receives the sound. This is never in the past but is only accurate
+    /// relative to other sounds received in the same batch.
+    After(Duration),
 }
 
 // These are undocumented, should not be relied on and subject to change.
@@ -21,16 +57,41 @@ pub struct Manager {
 const DEFAULT_CHANNEL_COUNT: u16 = 1;
 const DEFAULT_SAMPLE_RATE: u32 = 1000; // Purposely low value to discourage use
 
+/// The number of commands (e.g. sounds to play) that can be sent to the audio
+/// thread of a Manager or a [Group] between batches.
+const COMMAND_CAPACITY: usize = 1024;
+
 impl Manager {
     /// Create a new Manager and the renderer its samples will render to.
     ///
     /// Normally you do not need to call this function directly but you instead
     /// call `.start(...)` on a backend which will call this function.
     pub fn new() -> (Self, Renderer) {
+        let output_format = Arc::new(SharedOutputFormat::new(
+            DEFAULT_CHANNEL_COUNT,
+            DEFAULT_SAMPLE_RATE,
+        ));
+        let mut mixer = SoundMixer::new(DEFAULT_CHANNEL_COUNT, DEFAULT_SAMPLE_RATE);
+        mixer.publish_output_format(output_format.clone());
+        let frame_clock = Arc::new(AtomicU64::new(0));
+        mixer.publish_frame_clock(frame_clock.clone());
+        mixer.reserve(COMMAND_CAPACITY);
+        // Sounds only reach the mixers through commands so this many can be
+        // removed in a batch.
+        let collector = SoundCollector::spawn(COMMAND_CAPACITY);
+        mixer.set_collector(collector.clone());
         let (mixer, mixer_controller) =
-            Controllable::new(SoundMixer::new(DEFAULT_CHANNEL_COUNT, DEFAULT_SAMPLE_RATE));
+            Controllable::with_commands(mixer, COMMAND_CAPACITY, SoundMixer::run_command);
         let renderer = Renderer::new(mixer);
-        let manager = Manager { mixer_controller };
+        let manager = Manager {
+            mixer_controller,
+            output_format,
+            frame_clock,
+            // Each sound sent in a batch can start and finish in the next.
+            events: Events::new(2 * COMMAND_CAPACITY, collector.clone()),
+            collector,
+            resample_quality: Arc::new(Mutex::new(ResampleQuality::default())),
+        };
         (manager, renderer)
     }
 
@@ -41,13 +102,168 @@ impl Manager {
     ///
     /// See the modifier functions on [Sound] to control sounds before and/or
     /// after playing.
-    pub fn play(&mut self, sound: Box<dyn Sound>) {
-        self.mixer_controller.add(sound);
+    ///
+    /// Returns the id of the sound in [SoundEvent]s.
+    ///
+    /// Sounds are passed to the audio thread through a bounded queue that is
+    /// emptied at the start of each batch. If more than 1024 sounds and other
+    /// commands are sent between two batches, the sound is dropped and a
+    /// warning is logged. Use [try_play][Manager::try_play] to handle a full
+    /// queue.
+    pub fn play(&mut self, sound: Box<dyn Sound>) -> SoundId {
+        let id = self.events.next_id();
+        let sound = self.prepare(id, sound, self.resample_quality());
+        self.mixer_controller.send_typed(MixerCommand::Add(sound));
+        id
+    }
+
+    /// Same as [play][Manager::play] but returns an error instead of dropping
+    /// the sound if the queue to the audio thread is full.
+    pub fn try_play(&mut self, sound: Box<dyn Sound>) -> Result<SoundId, CommandQueueFull> {
+        let id = self.events.next_id();
+        let sound = self.prepare(id, sound, self.resample_quality());
+        self.mixer_controller
+            .try_send_typed(MixerCommand::Add(sound))?;
+        Ok(id)
+    }
+
+    /// Same as [play][Manager::play] but returns a [SoundHandle] to stop,
+    /// pause or change the volume of this sound and to check whether it is
+    /// still playing.
+    pub fn play_with_handle(&mut self, sound: Box<dyn Sound>) -> SoundHandle {
+        let id = self.events.next_id();
+        let (handle, sound) = SoundHandle::new(id, sound);
+        let sound = self.prepare(id, Box::new(sound), self.resample_quality());
+        self.mixer_controller.send_typed(MixerCommand::Add(sound));
+        handle
+    }
+
+    /// Add a new Sound that starts playing at exactly `start` instead of at
+    /// the start of the next batch.
+    ///
+    /// The start is accurate to the frame even within a batch. For rhythmic
+    /// sounds use [StartTime::Frame] with frames counted from the same
+    /// [current_frame][Manager::current_frame] so they stay in time with each
+    /// other. If `start` has already passed when the sound is received by the
+    /// audio thread, the sound starts immediately.
+    pub fn play_at(&mut self, sound: Box<dyn Sound>, start: StartTime) -> SoundId {
+        let id = self.events.next_id();
+        let sound = self.prepare(id, sound, self.resample_quality());
+        self.mixer_controller
+            .send_typed(MixerCommand::AddAt(sound, start));
+        id
+    }
+
+    /// The number of frames output at the start of the most recent batch.
+    ///
+    /// The count only advances while sounds are playing or scheduled to
+    /// play. Use with [StartTime::Frame] to schedule sounds relative to each
+    /// other.
+    pub fn current_frame(&self) -> u64 {
+        self.frame_clock.load(Ordering::Relaxed)
+    }
+
+    /// Add a new Sound to be played in `group`. Same as [Group::play].
+    pub fn play_in(&mut self, group: &mut Group, sound: Box<dyn Sound>) -> SoundId {
+        group.play(sound)
+    }
+
+    /// Create a new [Group] of sounds that can have its volume adjusted, be
+    /// paused or be cleared together. Groups can be nested with
+    /// [Group::create_group].
+    pub fn create_group(&mut self) -> Group {
+        let (group, sound) = Group::new(
+            self.output_format.clone(),
+            self.events.clone(),
+            self.collector.clone(),
+        );
+        let sound = MixedSound::new(
+            Box::new(sound),
+            &self.output_format,
+            ResampleQuality::default(),
+            true,
+        );
+        self.mixer_controller.send_typed(MixerCommand::Add(sound));
+        group
+    }
+
+    /// Same as [play][Manager::play] but if the sample rate of `sound` differs
+    /// from the output it is converted using `quality` instead of the default
+    /// quality.
+    pub fn play_with_resample_quality(
+        &mut self,
+        sound: Box<dyn Sound>,
+        quality: ResampleQuality,
+    ) -> SoundId {
+        let id = self.events.next_id();
+        let sound = self.prepare(id, sound, quality);
+        self.mixer_controller.send_typed(MixerCommand::Add(sound));
+        id
+    }
+
+    /// Set the [ResampleQuality] used for sounds played with
+    /// [play][Manager::play] from now on. Defaults to
+    /// [ResampleQuality::Linear].
+    pub fn set_resample_quality(&mut self, quality: ResampleQuality) {
+        *self
+            .resample_quality
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) = quality;
+    }
+
+    /// Set the processing applied to the sum of all playing sounds such as
+    /// headroom, soft clipping or a look-ahead limiter. See [MasterStage].
+    pub fn set_master_stage(&mut self, stage: MasterStage) {
+        let (channel_count, sample_rate) = self.output_format.load();
+        let master = Box::new(MasterStageProcessor::new(stage, channel_count, sample_rate));
+        self.mixer_controller
+            .send_typed(MixerCommand::ReplaceMasterStage(master));
+    }
+
+    /// Receive a [SoundEvent] when a sound played from now on (including in
+    /// groups) starts, finishes or errors.
+    ///
+    /// Events are sent from a separate thread shortly after they happen so
+    /// the audio thread does not need to allocate to send them.
+    ///
+    /// Only the receiver from the most recent call to `events` or
+    /// [events_async][Manager::events_async] receives events. Sounds played
+    /// before the first call do not have events.
+    pub fn events(&mut self) -> mpsc::Receiver<SoundEvent> {
+        self.events.subscribe()
+    }
+
+    /// Same as [events][Manager::events] but the events are received from a
+    /// tokio channel.
+    #[cfg(feature = "async")]
+    pub fn events_async(&mut self) -> tokio::sync::mpsc::UnboundedReceiver<SoundEvent> {
+        self.events.subscribe_async()
     }
 
     /// Stop playing and remove all audio sounds. New sounds can still be added.
+    ///
+    /// Groups and the sounds playing in them are kept. Use [Group::clear] to
+    /// clear a group.
     pub fn clear(&mut self) {
-        self.mixer_controller.clear();
+        self.mixer_controller.send_typed(MixerCommand::Clear);
+    }
+
+    fn resample_quality(&self) -> ResampleQuality {
+        *self
+            .resample_quality
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Track `sound` as `id` and convert it to the output format here so the
+    /// audio thread does not need to allocate to add it.
+    fn prepare(&self, id: SoundId, sound: Box<dyn Sound>, quality: ResampleQuality) -> MixedSound {
+        MixedSound::new(
+            self.events.track(id, sound),
+            &self.output_format,
+            quality,
+            false,
+        )
     }
 }
 
@@ -56,3 +272,7 @@ impl std::fmt::Debug for Manager {
         f.debug_struct("Manager").finish()
     }
 }
+
+#[cfg(test)]
+#[path = "./tests/manager.rs"]
+mod tests;
@@ -0,0 +1,256 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::Duration;
+
+use crate::sounds::wrappers::Seekable;
+use crate::sounds::SoundCollector;
+use crate::{NextSample, Sound};
+use crossbeam_queue::ArrayQueue;
+
+/// Identifies a sound played on a [Manager][super::Manager] in
+/// [SoundEvent]s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SoundId(u64);
+
+/// A change in the lifecycle of a sound played on a
+/// [Manager][super::Manager]. See [Manager::events][super::Manager::events].
+#[derive(Debug)]
+pub enum SoundEvent {
+    /// The first sample of the sound was played.
+    Started(SoundId),
+    /// The sound finished or was removed such as by clearing the
+    /// [Manager][super::Manager]. Sounds removed before they started are
+    /// finished without having started. Removed sounds are reported once they
+    /// are dropped on another thread.
+    Finished(SoundId),
+    /// The sound returned an error and was removed.
+    Errored(SoundId, crate::Error),
+}
+
+/// Where events are sent to.
+enum EventSender {
+    Std(mpsc::Sender<SoundEvent>),
+    #[cfg(feature = "async")]
+    Tokio(tokio::sync::mpsc::UnboundedSender<SoundEvent>),
+}
+
+impl EventSender {
+    fn send(&self, event: SoundEvent) {
+        // The receiver being dropped just means nobody is interested anymore.
+        let _ = match self {
+            EventSender::Std(sender) => sender.send(event).map_err(|_| ()),
+            #[cfg(feature = "async")]
+            EventSender::Tokio(sender) => sender.send(event).map_err(|_| ()),
+        };
+    }
+}
+
+/// Assigns ids to sounds played on a [Manager][super::Manager] and its groups
+/// and wraps them to report events if there is a subscriber.
+///
+/// Sending to a channel can allocate so events are queued by the audio thread
+/// and sent from the thread of a [SoundCollector].
+pub(crate) struct Events {
+    next_id: AtomicU64,
+    /// Events waiting to be sent.
+    pending: ArrayQueue<SoundEvent>,
+    sender: Mutex<Option<EventSender>>,
+    collector: Arc<SoundCollector>,
+}
+
+impl Events {
+    /// Create events whose queue holds up to `capacity` events between
+    /// wake ups of the thread of `collector`.
+    pub(crate) fn new(capacity: usize, collector: Arc<SoundCollector>) -> Arc<Self> {
+        let events = Arc::new(Events {
+            next_id: AtomicU64::new(0),
+            pending: ArrayQueue::new(capacity),
+            sender: Mutex::new(None),
+            collector: collector.clone(),
+        });
+        // Weak since the events own the collector.
+        let weak = Arc::downgrade(&events);
+        collector.set_task(Box::new(move || {
+            if let Some(events) = weak.upgrade() {
+                events.send_pending();
+            }
+        }));
+        events
+    }
+
+    pub(crate) fn next_id(&self) -> SoundId {
+        SoundId(self.next_id.fetch_add(1, Ordering::Relaxed))
+    }
+
+    /// Send events from now on to a new std channel.
+    pub(crate) fn subscribe(&self) -> mpsc::Receiver<SoundEvent> {
+        let (sender, receiver) = mpsc::channel();
+        self.set_sender(EventSender::Std(sender));
+        receiver
+    }
+
+    /// Send events from now on to a new tokio channel.
+    #[cfg(feature = "async")]
+    pub(crate) fn subscribe_async(&self) -> tokio::sync::mpsc::UnboundedReceiver<SoundEvent> {
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+        self.set_sender(EventSender::Tokio(sender));
+        receiver
+    }
+
+    /// Wrap `sound` to report its events as `id` if there is a subscriber.
+    pub(crate) fn track(self: &Arc<Self>, id: SoundId, sound: Box<dyn Sound>) -> Box<dyn Sound> {
+        if self.lock_sender().is_none() {
+            return sound;
+        }
+        Box::new(Reported {
+            inner: sound,
+            id,
+            events: self.clone(),
+            started: false,
+            done: false,
+        })
+    }
+
+    fn set_sender(&self, sender: EventSender) {
+        *self.lock_sender() = Some(sender);
+    }
+
+    fn lock_sender(&self) -> std::sync::MutexGuard<'_, Option<EventSender>> {
+        self.sender.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Queue `event` to be sent. Does not allocate.
+    fn report(&self, event: SoundEvent) {
+        // If the collector thread has fallen far behind the event is lost.
+        let _ = self.pending.push(event);
+        self.collector.wake();
+    }
+
+    /// Send the queued events to the subscriber.
+    fn send_pending(&self) {
+        let sender = self.lock_sender();
+        while let Some(event) = self.pending.pop() {
+            if let SoundEvent::Errored(_, error) = &event {
+                log::error!("dropping sound which returned error: {}", error);
+            }
+            if let Some(sender) = sender.as_ref() {
+                sender.send(event);
+            }
+        }
+    }
+}
+
+/// Reports the events of the inner sound.
+///
+/// Errors are reported as an event and then reported to the mixer as Finished
+/// since the error can only be consumed once.
+struct Reported {
+    inner: Box<dyn Sound>,
+    id: SoundId,
+    events: Arc<Events>,
+    started: bool,
+    done: bool,
+}
+
+impl Reported {
+    fn on_next(&mut self, next: NextSample) {
+        match next {
+            NextSample::Sample(_) if !self.started => {
+                self.started = true;
+                self.events.report(SoundEvent::Started(self.id));
+            }
+            NextSample::Finished if !self.done => {
+                self.done = true;
+                self.events.report(SoundEvent::Finished(self.id));
+            }
+            _ => {}
+        }
+    }
+
+    fn on_error(&mut self, error: crate::Error) -> NextSample {
+        if !self.done {
+            self.done = true;
+            self.events.report(SoundEvent::Errored(self.id, error));
+        }
+        NextSample::Finished
+    }
+
+    /// Report the result of reading into a buffer where the first sample
+    /// might have been read.
+    fn on_next_samples(
+        &mut self,
+        result: Result<(usize, NextSample), crate::Error>,
+    ) -> (usize, NextSample) {
+        match result {
+            Ok((num_read, next)) => {
+                if num_read > 0 {
+                    self.on_next(NextSample::Sample(0));
+                }
+                self.on_next(next);
+                (num_read, next)
+            }
+            Err(e) => (0, self.on_error(e)),
+        }
+    }
+}
+
+impl Sound for Reported {
+    fn channel_count(&self) -> u16 {
+        self.inner.channel_count()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        match self.inner.next_sample() {
+            Ok(next) => {
+                self.on_next(next);
+                Ok(next)
+            }
+            Err(e) => Ok(self.on_error(e)),
+        }
+    }
+
+    fn next_samples(&mut self, buffer: &mut [i16]) -> Result<(usize, NextSample), crate::Error> {
+        let result = self.inner.next_samples(buffer);
+        Ok(self.on_next_samples(result))
+    }
+
+    fn next_samples_f32(
+        &mut self,
+        buffer: &mut [f32],
+    ) -> Result<(usize, NextSample), crate::Error> {
+        let result = self.inner.next_samples_f32(buffer);
+        Ok(self.on_next_samples(result))
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.inner.on_start_of_batch()
+    }
+
+    fn as_seekable(&mut self) -> Option<&mut dyn Seekable> {
+        self.inner.as_seekable()
+    }
+
+    fn position(&self) -> Option<Duration> {
+        self.inner.position()
+    }
+
+    fn duration(&self) -> Option<Duration> {
+        self.inner.duration()
+    }
+}
+
+impl Drop for Reported {
+    fn drop(&mut self) {
+        if !self.done {
+            self.events.report(SoundEvent::Finished(self.id));
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/events.rs"]
+mod tests;
@@ -0,0 +1,144 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::sounds::wrappers::{
+    AdjustableVolume, CommandQueueFull, Controllable, Controller, Pausable, ResampleQuality,
+};
+use crate::sounds::{MixedSound, MixerCommand, SharedOutputFormat, SoundCollector, SoundMixer};
+use crate::Sound;
+
+use super::events::Events;
+use super::{SoundHandle, SoundId};
+
+type GroupSound = AdjustableVolume<Pausable<SoundMixer>>;
+
+/// A group of sounds (a bus or submix) that can have its volume adjusted,
+/// be paused or be cleared together.
+///
+/// Created with [Manager::create_group][super::Manager::create_group] or
+/// nested inside another group with [Group::create_group]. Sounds are added
+/// with [Group::play] or [Manager::play_in][super::Manager::play_in].
+///
+/// The group keeps playing while any clone of it exists. Once all clones are
+/// dropped the group finishes after its sounds have finished. Clearing the
+/// group or the [Manager][super::Manager] it is in does not remove groups
+/// nested in it.
+#[derive(Clone)]
+pub struct Group {
+    controller: Controller<GroupSound, MixerCommand>,
+    format: Arc<SharedOutputFormat>,
+    events: Arc<Events>,
+    collector: Arc<SoundCollector>,
+}
+
+impl Group {
+    /// Create a group whose sound is to be played in a mixer with the output
+    /// format `format`. Sounds removed from the group are dropped by
+    /// `collector`.
+    pub(crate) fn new(
+        format: Arc<SharedOutputFormat>,
+        events: Arc<Events>,
+        collector: Arc<SoundCollector>,
+    ) -> (Self, Controllable<GroupSound, MixerCommand>) {
+        let mut mixer = SoundMixer::new_following(format.clone());
+        mixer.set_collector(collector.clone());
+        mixer.reserve(super::COMMAND_CAPACITY);
+        let (sound, controller) = Controllable::with_commands(
+            mixer.pausable().with_adjustable_volume(),
+            super::COMMAND_CAPACITY,
+            |group, command| group.inner_mut().inner_mut().run_command(command),
+        );
+        let group = Group {
+            controller,
+            format,
+            events,
+            collector,
+        };
+        (group, sound)
+    }
+
+    /// Add a new Sound to be played in this group in parallel to any existing
+    /// sounds in the group.
+    ///
+    /// Returns the id of the sound in [SoundEvent][super::SoundEvent]s.
+    ///
+    /// Like [Manager::play][super::Manager::play] the sound is dropped if the
+    /// queue to the audio thread is full.
+    pub fn play(&mut self, sound: Box<dyn Sound>) -> SoundId {
+        let id = self.events.next_id();
+        let sound = self.prepare(self.events.track(id, sound), false);
+        self.controller.send_typed(MixerCommand::Add(sound));
+        id
+    }
+
+    /// Same as [play][Group::play] but returns an error instead of dropping
+    /// the sound if the queue to the audio thread is full.
+    pub fn try_play(&mut self, sound: Box<dyn Sound>) -> Result<SoundId, CommandQueueFull> {
+        let id = self.events.next_id();
+        let sound = self.prepare(self.events.track(id, sound), false);
+        self.controller.try_send_typed(MixerCommand::Add(sound))?;
+        Ok(id)
+    }
+
+    /// Same as [play][Group::play] but returns a [SoundHandle] to control
+    /// this sound.
+    pub fn play_with_handle(&mut self, sound: Box<dyn Sound>) -> SoundHandle {
+        let id = self.events.next_id();
+        let (handle, sound) = SoundHandle::new(id, sound);
+        let sound = self.prepare(self.events.track(id, Box::new(sound)), false);
+        self.controller.send_typed(MixerCommand::Add(sound));
+        handle
+    }
+
+    /// Create a new group that plays inside of this group.
+    ///
+    /// Volume and pausing of this group also apply to the new group.
+    pub fn create_group(&mut self) -> Group {
+        let (group, sound) = Group::new(
+            self.format.clone(),
+            self.events.clone(),
+            self.collector.clone(),
+        );
+        let sound = self.prepare(Box::new(sound), true);
+        self.controller.send_typed(MixerCommand::Add(sound));
+        group
+    }
+
+    /// Convert `sound` to the output format here so the audio thread does not
+    /// need to allocate to add it.
+    fn prepare(&self, sound: Box<dyn Sound>, is_group: bool) -> MixedSound {
+        MixedSound::new(sound, &self.format, ResampleQuality::default(), is_group)
+    }
+
+    /// Set the volume of all sounds in the group.
+    pub fn set_volume(&mut self, volume: f32) {
+        self.controller.set_volume(volume);
+    }
+
+    /// Change the volume of all sounds in the group gradually over
+    /// `duration`.
+    pub fn set_volume_over(&mut self, volume: f32, duration: Duration) {
+        self.controller.set_volume_over(volume, duration);
+    }
+
+    /// Pause or unpause all sounds in the group.
+    pub fn set_paused(&mut self, paused: bool) {
+        self.controller.set_paused(paused);
+    }
+
+    /// Stop playing and remove all sounds in the group. Nested groups are
+    /// kept and new sounds can still be added.
+    pub fn clear(&mut self) {
+        self.controller.send_typed(MixerCommand::Clear);
+    }
+}
+
+impl std::fmt::Debug for Group {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Group").finish()
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/group.rs"]
+mod tests;
@@ -1,5 +1,7 @@
 use crate::sounds::wrappers::Controllable;
 use crate::sounds::wrappers::Wrapper;
+use crate::sounds::MasterStage;
+use crate::sounds::MixerCommand;
 use crate::sounds::SoundMixer;
 use crate::NextSample;
 use crate::Sound;
@@ -8,13 +10,29 @@ use super::backend_source::BackendSource;
 
 /// The default [BackendSource]. Renderer is essentially half of [Manager].
 pub struct Renderer {
-    mixer: Controllable<SoundMixer>,
+    mixer: Controllable<SoundMixer, MixerCommand>,
 }
 
 impl Renderer {
-    pub(crate) fn new(mixer: Controllable<SoundMixer>) -> Self {
+    pub(crate) fn new(mixer: Controllable<SoundMixer, MixerCommand>) -> Self {
         Renderer { mixer }
     }
+
+    /// Set the processing applied to the sum of all playing sounds. Must only
+    /// be called when the next sample is for the first channel.
+    ///
+    /// Usually [Manager::set_master_stage][crate::manager::Manager::set_master_stage] is
+    /// used instead.
+    pub fn set_master_stage(&mut self, stage: MasterStage) {
+        self.mixer.inner_mut().set_master_stage(stage);
+    }
+
+    /// The number of frames output so far which sounds played with
+    /// [Manager::play_at][crate::manager::Manager::play_at] are scheduled
+    /// against.
+    pub fn current_frame(&self) -> u64 {
+        self.mixer.inner().current_frame()
+    }
 }
 
 impl BackendSource for Renderer {
@@ -46,16 +64,53 @@ impl Sound for Renderer {
     /// the Renderer has been dropped.
     ///
     /// Guaranteed to not return an Error.
+    ///
+    /// With the `assert-no-alloc` feature, debug builds abort if anything
+    /// allocates or deallocates while mixing the sample.
     fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        #[cfg(feature = "assert-no-alloc")]
+        return assert_no_alloc::assert_no_alloc(|| self.mixer.next_sample());
+        #[cfg(not(feature = "assert-no-alloc"))]
         self.mixer.next_sample()
     }
 
+    /// Same as `next_sample` but fills a buffer of samples at once.
+    ///
+    /// Guaranteed to not return an Error. Checked by the `assert-no-alloc`
+    /// feature like `next_sample`.
+    fn next_samples(&mut self, buffer: &mut [i16]) -> Result<(usize, NextSample), crate::Error> {
+        #[cfg(feature = "assert-no-alloc")]
+        return assert_no_alloc::assert_no_alloc(|| self.mixer.next_samples(buffer));
+        #[cfg(not(feature = "assert-no-alloc"))]
+        self.mixer.next_samples(buffer)
+    }
+
+    /// Same as `next_samples` but mixes and fills f32 samples.
+    ///
+    /// Guaranteed to not return an Error. Checked by the `assert-no-alloc`
+    /// feature like `next_sample`.
+    fn next_samples_f32(
+        &mut self,
+        buffer: &mut [f32],
+    ) -> Result<(usize, NextSample), crate::Error> {
+        #[cfg(feature = "assert-no-alloc")]
+        return assert_no_alloc::assert_no_alloc(|| self.mixer.next_samples_f32(buffer));
+        #[cfg(not(feature = "assert-no-alloc"))]
+        self.mixer.next_samples_f32(buffer)
+    }
+
     /// Inform the playing or queued sounds that a new batch of samples will be
     /// requested. This must only be called when the next sample to be delivered
     /// from `next_sample` is for the first channel.
     ///
     /// See [Sound::on_start_of_batch]
+    ///
+    /// With the `assert-no-alloc` feature, debug builds abort if anything
+    /// allocates or deallocates while starting the batch.
     fn on_start_of_batch(&mut self) {
+        #[cfg(feature = "assert-no-alloc")]
+        return assert_no_alloc::assert_no_alloc(|| self.mixer.on_start_of_batch());
+        #[cfg(not(feature = "assert-no-alloc"))]
         self.mixer.on_start_of_batch()
     }
 }
@@ -0,0 +1,216 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::SoundId;
+use crate::sounds::wrappers::{AdjustableVolume, Controllable, Controller, Pausable, Seekable};
+use crate::{NextSample, Sound};
+
+type HandledInner = AdjustableVolume<Pausable<Box<dyn Sound>>>;
+
+/// Whether a sound played with a [SoundHandle] is still playing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoundStatus {
+    /// The sound has not finished yet. Paused sounds and sounds that have not
+    /// started yet are also playing.
+    Playing,
+    /// The sound finished, was stopped or was removed (e.g. by clearing the
+    /// [Manager][super::Manager]).
+    Finished,
+    /// The sound returned an error and was removed.
+    Errored,
+}
+
+impl SoundStatus {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            0 => SoundStatus::Playing,
+            1 => SoundStatus::Finished,
+            _ => SoundStatus::Errored,
+        }
+    }
+}
+
+/// Set `status` unless a status after playing has already been set.
+fn set_status(status: &AtomicU8, new_status: SoundStatus) {
+    let _ = status.compare_exchange(
+        SoundStatus::Playing as u8,
+        new_status as u8,
+        Ordering::Relaxed,
+        Ordering::Relaxed,
+    );
+}
+
+/// A handle to a single sound played with
+/// [Manager::play_with_handle][super::Manager::play_with_handle].
+///
+/// Unlike a [Controller] the sound does not need to be wrapped before playing
+/// and it still finishes while the handle exists. Dropping the handle does not
+/// stop the sound.
+#[derive(Clone)]
+pub struct SoundHandle {
+    id: SoundId,
+    controller: Controller<HandledInner>,
+    status: Arc<AtomicU8>,
+}
+
+impl SoundHandle {
+    /// Wrap `sound` so it can be controlled by the returned handle.
+    pub(crate) fn new(id: SoundId, sound: Box<dyn Sound>) -> (Self, HandledSound) {
+        let (inner, controller) = Controllable::new(sound.pausable().with_adjustable_volume());
+        let status = Arc::new(AtomicU8::new(SoundStatus::Playing as u8));
+        let handled = HandledSound {
+            inner: inner.finish_with_inner(),
+            status: status.clone(),
+            stopped: false,
+        };
+        let handle = SoundHandle {
+            id,
+            controller,
+            status,
+        };
+        (handle, handled)
+    }
+
+    /// The id of the sound in [SoundEvent][super::SoundEvent]s.
+    pub fn id(&self) -> SoundId {
+        self.id
+    }
+
+    /// Whether the sound is still playing, finished or errored.
+    ///
+    /// Updated from the audio thread as soon as the sound finishes. Sounds
+    /// removed without finishing are updated shortly after when they are
+    /// dropped on another thread.
+    pub fn status(&self) -> SoundStatus {
+        SoundStatus::from_u8(self.status.load(Ordering::Relaxed))
+    }
+
+    /// True until the sound has finished, been stopped or errored.
+    pub fn is_playing(&self) -> bool {
+        self.status() == SoundStatus::Playing
+    }
+
+    /// Stop the sound and remove it from the mixer at the start of the next
+    /// batch. The status is [Finished][SoundStatus::Finished] from now on.
+    pub fn stop(&mut self) {
+        set_status(&self.status, SoundStatus::Finished);
+    }
+
+    /// Pause or unpause the sound.
+    pub fn set_paused(&mut self, paused: bool) {
+        self.controller.set_paused(paused);
+    }
+
+    /// Set the volume of the sound.
+    pub fn set_volume(&mut self, volume: f32) {
+        self.controller.set_volume(volume);
+    }
+
+    /// Change the volume of the sound gradually over `duration` to avoid
+    /// clicks.
+    pub fn set_volume_over(&mut self, volume: f32, duration: Duration) {
+        self.controller.set_volume_over(volume, duration);
+    }
+}
+
+impl std::fmt::Debug for SoundHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SoundHandle")
+            .field("id", &self.id)
+            .field("status", &self.status())
+            .finish()
+    }
+}
+
+/// The sound played in the mixer for a [SoundHandle].
+pub(crate) struct HandledSound {
+    inner: Controllable<HandledInner>,
+    status: Arc<AtomicU8>,
+    /// Set at the start of a batch once the status is no longer playing
+    /// such as after [SoundHandle::stop].
+    stopped: bool,
+}
+
+impl HandledSound {
+    /// Record the status of the result of reading from the inner sound.
+    fn track<T>(
+        &mut self,
+        result: Result<(T, NextSample), crate::Error>,
+    ) -> Result<(T, NextSample), crate::Error> {
+        match &result {
+            Ok((_, NextSample::Finished)) => set_status(&self.status, SoundStatus::Finished),
+            Ok(_) => {}
+            Err(_) => set_status(&self.status, SoundStatus::Errored),
+        }
+        result
+    }
+}
+
+impl Sound for HandledSound {
+    fn channel_count(&self) -> u16 {
+        self.inner.channel_count()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        if self.stopped {
+            return Ok(NextSample::Finished);
+        }
+        let result = self.inner.next_sample().map(|next| ((), next));
+        self.track(result).map(|((), next)| next)
+    }
+
+    fn next_samples(&mut self, buffer: &mut [i16]) -> Result<(usize, NextSample), crate::Error> {
+        if self.stopped {
+            return Ok((0, NextSample::Finished));
+        }
+        let result = self.inner.next_samples(buffer);
+        self.track(result)
+    }
+
+    fn next_samples_f32(
+        &mut self,
+        buffer: &mut [f32],
+    ) -> Result<(usize, NextSample), crate::Error> {
+        if self.stopped {
+            return Ok((0, NextSample::Finished));
+        }
+        let result = self.inner.next_samples_f32(buffer);
+        self.track(result)
+    }
+
+    fn on_start_of_batch(&mut self) {
+        if self.status.load(Ordering::Relaxed) != SoundStatus::Playing as u8 {
+            self.stopped = true;
+        }
+        self.inner.on_start_of_batch();
+    }
+
+    fn as_seekable(&mut self) -> Option<&mut dyn Seekable> {
+        self.inner.as_seekable()
+    }
+
+    fn position(&self) -> Option<Duration> {
+        self.inner.position()
+    }
+
+    fn duration(&self) -> Option<Duration> {
+        self.inner.duration()
+    }
+}
+
+impl Drop for HandledSound {
+    fn drop(&mut self) {
+        // The sound was removed without finishing such as when the mixer was
+        // cleared.
+        set_status(&self.status, SoundStatus::Finished);
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/sound_handle.rs"]
+mod tests;
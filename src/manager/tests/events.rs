@@ -0,0 +1,177 @@
+use std::time::Duration;
+
+use crate::manager::{BackendSource, Manager, Renderer, SoundEvent};
+use crate::tests::{ConstantValueSound, DEFAULT_CHANNEL_COUNT, DEFAULT_SAMPLE_RATE};
+use crate::{NextSample, Sound};
+
+fn start() -> (Manager, Renderer) {
+    let (manager, mut renderer) = Manager::new();
+    renderer.set_output_channel_count_and_sample_rate(DEFAULT_CHANNEL_COUNT, DEFAULT_SAMPLE_RATE);
+    (manager, renderer)
+}
+
+/// Render a batch of `len` samples.
+fn render(renderer: &mut Renderer, len: usize) {
+    renderer.on_start_of_batch();
+    let mut buffer = vec![0; len];
+    let mut num_filled = 0;
+    while num_filled < len {
+        match renderer.next_samples(&mut buffer[num_filled..]).unwrap() {
+            (num_read, NextSample::Sample(_) | NextSample::MetadataChanged) => {
+                num_filled += num_read
+            }
+            (_, NextSample::Paused | NextSample::Finished) => break,
+        }
+    }
+}
+
+/// Events are sent from another thread so wait for them.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Receive the next `count` events.
+fn receive(events: &std::sync::mpsc::Receiver<SoundEvent>, count: usize) -> Vec<SoundEvent> {
+    (0..count)
+        .map(|_| events.recv_timeout(TIMEOUT).unwrap())
+        .collect()
+}
+
+fn memory_sound(len: usize) -> Box<crate::sounds::MemorySound> {
+    Box::new(crate::sounds::MemorySound::from_samples(
+        std::sync::Arc::new(vec![1; len]),
+        DEFAULT_CHANNEL_COUNT,
+        DEFAULT_SAMPLE_RATE,
+    ))
+}
+
+/// A sound that returns an error after `samples_left` samples.
+struct FailingSound {
+    samples_left: u16,
+}
+
+impl Sound for FailingSound {
+    fn channel_count(&self) -> u16 {
+        DEFAULT_CHANNEL_COUNT
+    }
+
+    fn sample_rate(&self) -> u32 {
+        DEFAULT_SAMPLE_RATE
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        if self.samples_left == 0 {
+            // An error that can be created without allocating.
+            return Err(std::io::Error::from(std::io::ErrorKind::Other).into());
+        }
+        self.samples_left -= 1;
+        Ok(NextSample::Sample(1))
+    }
+
+    fn on_start_of_batch(&mut self) {}
+}
+
+#[test]
+fn started_and_finished_are_reported() {
+    let (mut manager, mut renderer) = start();
+    let events = manager.events();
+    let id = manager.play(memory_sound(4));
+    assert!(events.try_recv().is_err());
+    render(&mut renderer, 2);
+    assert!(matches!(events.recv_timeout(TIMEOUT), Ok(SoundEvent::Started(i)) if i == id));
+    assert!(events.try_recv().is_err());
+    render(&mut renderer, 4);
+    assert!(matches!(events.recv_timeout(TIMEOUT), Ok(SoundEvent::Finished(i)) if i == id));
+}
+
+#[test]
+fn errors_are_reported_with_the_error() {
+    let (mut manager, mut renderer) = start();
+    let events = manager.events();
+    let mut group = manager.create_group();
+    let other = manager.play(Box::new(ConstantValueSound::new(1)));
+    let failing = group.play(Box::new(FailingSound { samples_left: 2 }));
+    assert_ne!(other, failing);
+    render(&mut renderer, 4);
+    let error = loop {
+        match events.recv_timeout(TIMEOUT).unwrap() {
+            SoundEvent::Started(_) => {}
+            other => break other,
+        }
+    };
+    assert!(matches!(error, SoundEvent::Errored(i, crate::Error::IoError(_)) if i == failing));
+}
+
+#[test]
+fn cleared_sounds_are_finished() {
+    let (mut manager, mut renderer) = start();
+    let events = manager.events();
+    let id = manager.play(Box::new(ConstantValueSound::new(1)));
+    render(&mut renderer, 2);
+    manager.clear();
+    render(&mut renderer, 2);
+    let received = receive(&events, 2);
+    assert!(
+        matches!(received[..], [SoundEvent::Started(a), SoundEvent::Finished(b)] if a == id && b == id)
+    );
+}
+
+#[test]
+fn handle_ids_match_events() {
+    let (mut manager, mut renderer) = start();
+    let events = manager.events();
+    let handle = manager.play_with_handle(memory_sound(2));
+    render(&mut renderer, 4);
+    let received = receive(&events, 2);
+    assert!(
+        matches!(received[..], [SoundEvent::Started(a), SoundEvent::Finished(b)] if a == handle.id() && b == handle.id())
+    );
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn async_events_are_received() {
+    let (mut manager, mut renderer) = start();
+    let mut events = manager.events_async();
+    let id = manager.play(memory_sound(2));
+    render(&mut renderer, 4);
+    let mut received = Vec::new();
+    let start = std::time::Instant::now();
+    while received.len() < 2 && start.elapsed() < TIMEOUT {
+        match events.try_recv() {
+            Ok(event) => received.push(event),
+            Err(_) => std::thread::sleep(Duration::from_millis(1)),
+        }
+    }
+    assert!(
+        matches!(received[..], [SoundEvent::Started(a), SoundEvent::Finished(b)] if a == id && b == id)
+    );
+}
+
+/// With the assert-no-alloc feature this aborts if reporting events while
+/// mixing allocates.
+#[test]
+fn reporting_events_does_not_allocate() {
+    let (mut manager, mut renderer) = start();
+    let events = manager.events();
+    let finishing = manager.play(memory_sound(1));
+    let mut group = manager.create_group();
+    let failing = group.play(Box::new(FailingSound { samples_left: 1 }));
+    renderer.on_start_of_batch();
+    assert_eq!(renderer.next_sample().unwrap(), NextSample::MetadataChanged);
+    assert_eq!(renderer.next_sample().unwrap(), NextSample::Sample(2));
+    assert_eq!(renderer.next_sample().unwrap(), NextSample::Paused);
+    let mut received = receive(&events, 4);
+    received.sort_by_key(|event| match event {
+        SoundEvent::Started(_) => 0,
+        SoundEvent::Finished(_) => 1,
+        SoundEvent::Errored(_, _) => 2,
+    });
+    assert!(matches!(
+        received[..],
+        [
+            SoundEvent::Started(_),
+            SoundEvent::Started(_),
+            SoundEvent::Finished(a),
+            SoundEvent::Errored(b, _)
+        ] if a == finishing && b == failing
+    ));
+}
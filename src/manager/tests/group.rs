@@ -0,0 +1,115 @@
+use crate::manager::{BackendSource, Manager, Renderer};
+use crate::tests::{ConstantValueSound, DEFAULT_CHANNEL_COUNT, DEFAULT_SAMPLE_RATE};
+use crate::{NextSample, Sound};
+
+fn start() -> (Manager, Renderer) {
+    let (manager, mut renderer) = Manager::new();
+    renderer.set_output_channel_count_and_sample_rate(DEFAULT_CHANNEL_COUNT, DEFAULT_SAMPLE_RATE);
+    (manager, renderer)
+}
+
+/// Start a new batch and return the first sample that is not
+/// `MetadataChanged`.
+fn next_batch_sample(renderer: &mut Renderer) -> NextSample {
+    renderer.on_start_of_batch();
+    loop {
+        match renderer.next_sample().unwrap() {
+            NextSample::MetadataChanged => {}
+            next => return next,
+        }
+    }
+}
+
+fn constant(value: i16) -> Box<ConstantValueSound> {
+    Box::new(ConstantValueSound::new(value))
+}
+
+#[test]
+fn group_volume_only_affects_its_sounds() {
+    let (mut manager, mut renderer) = start();
+    let mut music = manager.create_group();
+    manager.play(constant(100));
+    manager.play_in(&mut music, constant(1000));
+    assert_eq!(next_batch_sample(&mut renderer), NextSample::Sample(1100));
+    music.set_volume(0.5);
+    assert_eq!(next_batch_sample(&mut renderer), NextSample::Sample(600));
+    music.set_volume(0.0);
+    assert_eq!(next_batch_sample(&mut renderer), NextSample::Sample(100));
+}
+
+#[test]
+fn pausing_a_group() {
+    let (mut manager, mut renderer) = start();
+    let mut sfx = manager.create_group();
+    manager.play(constant(100));
+    sfx.play(constant(1000));
+    sfx.set_paused(true);
+    assert_eq!(next_batch_sample(&mut renderer), NextSample::Sample(100));
+    sfx.set_paused(false);
+    assert_eq!(next_batch_sample(&mut renderer), NextSample::Sample(1100));
+}
+
+#[test]
+fn nested_groups() {
+    let (mut manager, mut renderer) = start();
+    let mut music = manager.create_group();
+    let mut ambience = music.create_group();
+    ambience.play(constant(1000));
+    music.play(constant(100));
+    assert_eq!(next_batch_sample(&mut renderer), NextSample::Sample(1100));
+    music.set_volume(0.5);
+    ambience.set_volume(0.5);
+    assert_eq!(next_batch_sample(&mut renderer), NextSample::Sample(300));
+    music.set_paused(true);
+    assert_eq!(next_batch_sample(&mut renderer), NextSample::Paused);
+}
+
+#[test]
+fn clearing_keeps_groups() {
+    let (mut manager, mut renderer) = start();
+    let mut music = manager.create_group();
+    let mut ambience = music.create_group();
+    manager.play(constant(1));
+    music.play(constant(10));
+    ambience.play(constant(100));
+    assert_eq!(next_batch_sample(&mut renderer), NextSample::Sample(111));
+    music.clear();
+    assert_eq!(next_batch_sample(&mut renderer), NextSample::Sample(101));
+    manager.clear();
+    assert_eq!(next_batch_sample(&mut renderer), NextSample::Sample(100));
+    // The cleared groups can still be played in.
+    music.play(constant(10));
+    assert_eq!(next_batch_sample(&mut renderer), NextSample::Sample(110));
+}
+
+#[test]
+fn group_finishes_after_being_dropped() {
+    let (mut manager, mut renderer) = start();
+    let mut group = manager.create_group();
+    group.play(Box::new(
+        ConstantValueSound::new(5).finish_after(std::time::Duration::from_millis(1)),
+    ));
+    drop(group);
+    assert_eq!(next_batch_sample(&mut renderer), NextSample::Sample(5));
+    drop(manager);
+    while let NextSample::Sample(_) = renderer.next_sample().unwrap() {}
+    assert_eq!(next_batch_sample(&mut renderer), NextSample::Finished);
+}
+
+#[test]
+fn groups_follow_the_output_format() {
+    let (mut manager, mut renderer) = start();
+    let mut group = manager.create_group();
+    group.play(constant(7));
+    assert_eq!(next_batch_sample(&mut renderer), NextSample::Sample(7));
+    // Finish the stereo frame.
+    assert_eq!(renderer.next_sample().unwrap(), NextSample::Sample(7));
+    renderer.set_output_channel_count_and_sample_rate(1, 8000);
+    renderer.on_start_of_batch();
+    assert_eq!(renderer.next_sample().unwrap(), NextSample::MetadataChanged);
+    assert_eq!(renderer.channel_count(), 1);
+    assert_eq!(renderer.sample_rate(), 8000);
+    // The group changed its format at the start of the batch so the sound is
+    // converted once by the group instead of by the group and root mixer.
+    assert_eq!(next_batch_sample(&mut renderer), NextSample::Sample(7));
+}
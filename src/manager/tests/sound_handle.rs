@@ -0,0 +1,115 @@
+use std::time::Duration;
+
+use crate::manager::{BackendSource, Manager, Renderer, SoundStatus};
+use crate::tests::{ConstantValueSound, DEFAULT_CHANNEL_COUNT, DEFAULT_SAMPLE_RATE};
+use crate::{NextSample, Sound};
+
+fn start() -> (Manager, Renderer) {
+    let (manager, mut renderer) = Manager::new();
+    renderer.set_output_channel_count_and_sample_rate(DEFAULT_CHANNEL_COUNT, DEFAULT_SAMPLE_RATE);
+    (manager, renderer)
+}
+
+/// Start a new batch and return the first sample that is not
+/// `MetadataChanged`.
+fn next_batch_sample(renderer: &mut Renderer) -> NextSample {
+    renderer.on_start_of_batch();
+    loop {
+        match renderer.next_sample().unwrap() {
+            NextSample::MetadataChanged => {}
+            next => return next,
+        }
+    }
+}
+
+/// A sound that returns an error after its first frame.
+struct FailingSound {
+    samples_left: u16,
+}
+
+impl Sound for FailingSound {
+    fn channel_count(&self) -> u16 {
+        DEFAULT_CHANNEL_COUNT
+    }
+
+    fn sample_rate(&self) -> u32 {
+        DEFAULT_SAMPLE_RATE
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        if self.samples_left == 0 {
+            // An error that can be created without allocating.
+            return Err(std::io::Error::from(std::io::ErrorKind::Other).into());
+        }
+        self.samples_left -= 1;
+        Ok(NextSample::Sample(1))
+    }
+
+    fn on_start_of_batch(&mut self) {}
+}
+
+#[test]
+fn handle_controls_volume_and_pause() {
+    let (mut manager, mut renderer) = start();
+    let mut handle = manager.play_with_handle(Box::new(ConstantValueSound::new(1000)));
+    assert_eq!(next_batch_sample(&mut renderer), NextSample::Sample(1000));
+    handle.set_volume(0.5);
+    assert_eq!(next_batch_sample(&mut renderer), NextSample::Sample(500));
+    handle.set_paused(true);
+    assert_eq!(next_batch_sample(&mut renderer), NextSample::Paused);
+    assert_eq!(handle.status(), SoundStatus::Playing);
+    handle.set_paused(false);
+    assert_eq!(next_batch_sample(&mut renderer), NextSample::Sample(500));
+}
+
+#[test]
+fn stop_finishes_only_that_sound() {
+    let (mut manager, mut renderer) = start();
+    let mut handle = manager.play_with_handle(Box::new(ConstantValueSound::new(1000)));
+    manager.play(Box::new(ConstantValueSound::new(10)));
+    assert_eq!(next_batch_sample(&mut renderer), NextSample::Sample(1010));
+    assert!(handle.is_playing());
+    handle.stop();
+    assert_eq!(handle.status(), SoundStatus::Finished);
+    assert_eq!(next_batch_sample(&mut renderer), NextSample::Sample(10));
+}
+
+#[test]
+fn status_is_finished_after_sound_ends() {
+    let (mut manager, mut renderer) = start();
+    let handle = manager.play_with_handle(Box::new(crate::sounds::MemorySound::from_samples(
+        std::sync::Arc::new(vec![1, 2]),
+        DEFAULT_CHANNEL_COUNT,
+        DEFAULT_SAMPLE_RATE,
+    )));
+    assert_eq!(next_batch_sample(&mut renderer), NextSample::Sample(1));
+    let mut buffer = [0; 4];
+    renderer.next_samples(&mut buffer).unwrap();
+    assert_eq!(handle.status(), SoundStatus::Finished);
+}
+
+#[test]
+fn status_is_errored_after_error() {
+    let (mut manager, mut renderer) = start();
+    let handle = manager.play_with_handle(Box::new(FailingSound { samples_left: 2 }));
+    assert_eq!(next_batch_sample(&mut renderer), NextSample::Sample(1));
+    assert_eq!(handle.status(), SoundStatus::Playing);
+    renderer.next_sample().unwrap();
+    renderer.next_sample().unwrap();
+    assert_eq!(handle.status(), SoundStatus::Errored);
+}
+
+#[test]
+fn status_is_finished_after_clear() {
+    let (mut manager, mut renderer) = start();
+    let handle = manager.play_with_handle(Box::new(ConstantValueSound::new(1000)));
+    assert_eq!(next_batch_sample(&mut renderer), NextSample::Sample(1000));
+    manager.clear();
+    next_batch_sample(&mut renderer);
+    // Removed sounds are dropped on another thread.
+    let start = std::time::Instant::now();
+    while handle.is_playing() && start.elapsed() < Duration::from_secs(5) {
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    assert_eq!(handle.status(), SoundStatus::Finished);
+}
@@ -1,13 +1,17 @@
 use std::{
+    io::Write,
     ops::{Deref, DerefMut},
+    path::Path,
     time::Duration,
 };
 
 use crate::{
     sounds::{
         wrappers::{
-            AdjustableSpeed, AdjustableVolume, Controllable, Controller, FinishAfter, Pausable,
-            SetPaused,
+            AdjustableSpeed, AdjustableTempo, AdjustableVolume, ChannelCountConverter,
+            Controllable, Controller, Fade, FinishAfter, MeterHandle, Metered, Pannable, Pausable,
+            PitchShift, SampleRateConverter, Seekable, SetPaused, SpectrumHandle, SpectrumTap, Tee,
+            TeeReader, Window,
         },
         MemorySound,
     },
@@ -54,6 +58,75 @@ pub trait Sound: Send {
     /// but not for every single audio sample.
     fn on_start_of_batch(&mut self);
 
+    /// Return this sound as a [Seekable] if it supports seeking.
+    ///
+    /// This allows seeking a sound that is stored as a `Box<dyn Sound>` (e.g.
+    /// by [SoundList][crate::sounds::SoundList]). Sounds that implement
+    /// [Seekable] should return `Some(self)`. Wrappers that pass seeking
+    /// through to their inner sound should return the value from their inner
+    /// sound. The default implementation returns None.
+    ///
+    /// Decoders that are generic over their reader (e.g. `WavDecoder`) only
+    /// return Some here when created with their `new_seekable` constructor
+    /// (as [open_file][crate::sounds::open_file] does).
+    fn as_seekable(&mut self) -> Option<&mut dyn Seekable> {
+        None
+    }
+
+    /// Returns how far playback has progressed from the start of the sound if
+    /// known.
+    ///
+    /// This is the time offset of the next sample to be returned from
+    /// `next_sample`. The default implementation returns None.
+    fn position(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Returns the total length of the sound if known.
+    ///
+    /// None is returned if the length is unknown or if the sound plays forever.
+    /// The default implementation returns None.
+    fn duration(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Fill `buffer` with samples as if `next_sample` was called repeatedly.
+    ///
+    /// Returns the number of samples written to the start of `buffer` and the
+    /// variant that stopped filling. If `buffer` was completely filled then
+    /// `NextSample::Sample` with the last sample is returned. Otherwise the
+    /// `MetadataChanged`, `Paused` or `Finished` that would have been returned
+    /// by `next_sample` is returned and samples after the returned count are
+    /// unspecified. If an error is returned any samples written are lost.
+    ///
+    /// The default implementation calls `next_sample` for each sample.
+    /// Implementations can override this to process whole buffers at once
+    /// which avoids the overhead of calling `next_sample` through every
+    /// wrapper for every sample.
+    fn next_samples(&mut self, buffer: &mut [i16]) -> Result<(usize, NextSample), crate::Error> {
+        next_samples_one_by_one(self, buffer)
+    }
+
+    /// Same as [next_samples][Sound::next_samples] but fills `buffer` with
+    /// f32 samples which are nominally in the range -1.0 to 1.0.
+    ///
+    /// This allows sounds which are decoded as floats or which have their
+    /// volume adjusted to be delivered without first being truncated to i16.
+    /// Values outside of -1.0 to 1.0 are allowed (e.g. the result of mixing
+    /// multiple loud sounds) and are clipped when converted back to i16. If
+    /// `buffer` was completely filled then `NextSample::Sample` contains the
+    /// last sample converted to i16.
+    ///
+    /// The default implementation calls `next_samples` and converts the i16
+    /// samples. Sounds that produce samples with more precision than i16 and
+    /// wrappers that pass samples through should override this.
+    fn next_samples_f32(
+        &mut self,
+        buffer: &mut [f32],
+    ) -> Result<(usize, NextSample), crate::Error> {
+        next_samples_f32_from_i16(self, buffer)
+    }
+
     /// Returns the next sample for all channels.
     ///
     /// It is the callers responsibility to ensure this function is only called
@@ -124,6 +197,34 @@ pub trait Sound: Send {
         Controllable::new(self)
     }
 
+    /// Measure the peak and RMS levels of this sound so they can be read
+    /// from the returned [`MeterHandle`] on another thread.
+    fn metered(self) -> (Metered<Self>, MeterHandle)
+    where
+        Self: Sized,
+    {
+        Metered::new(self)
+    }
+
+    /// Compute the frequency spectrum of the most recent `size` frames of
+    /// this sound so it can be read from the returned [`SpectrumHandle`] on
+    /// another thread. See [SpectrumTap::new].
+    fn with_spectrum_tap(self, size: usize, window: Window) -> (SpectrumTap<Self>, SpectrumHandle)
+    where
+        Self: Sized,
+    {
+        SpectrumTap::new(self, size, window)
+    }
+
+    /// Copy the samples of this sound into a ring buffer of `capacity`
+    /// samples to be read on another thread from the returned [`TeeReader`].
+    fn tee(self, capacity: usize) -> (Tee<Self>, TeeReader)
+    where
+        Self: Sized,
+    {
+        Tee::new(self, capacity)
+    }
+
     /// Get notified via a [tokio::sync::oneshot::Receiver] when this sound
     /// has Finished.
     #[cfg(feature = "async")]
@@ -191,6 +292,70 @@ pub trait Sound: Send {
         AdjustableSpeed::new_with_speed(self, speed_adjustment)
     }
 
+    /// Allow the tempo of the sound to be adjustable with `set_tempo`.
+    ///
+    /// This adjusts the speed without changing the pitch.
+    fn with_adjustable_tempo(self) -> AdjustableTempo<Self>
+    where
+        Self: Sized,
+    {
+        AdjustableTempo::new(self)
+    }
+
+    /// Allow the tempo of the sound to be adjustable with `set_tempo` and set
+    /// the initial tempo.
+    ///
+    /// This adjusts the speed without changing the pitch.
+    fn with_adjustable_tempo_of(self, tempo: f32) -> AdjustableTempo<Self>
+    where
+        Self: Sized,
+    {
+        AdjustableTempo::new_with_tempo(self, tempo)
+    }
+
+    /// Allow the pitch of the sound to be adjustable with `set_pitch`.
+    ///
+    /// This adjusts the pitch without changing the speed.
+    fn with_adjustable_pitch(self) -> PitchShift<Self>
+    where
+        Self: Sized,
+    {
+        PitchShift::new(self)
+    }
+
+    /// Allow the pitch of the sound to be adjustable with `set_pitch` and set
+    /// the initial pitch.
+    ///
+    /// This adjusts the pitch without changing the speed.
+    fn with_adjustable_pitch_of(self, pitch: f32) -> PitchShift<Self>
+    where
+        Self: Sized,
+    {
+        PitchShift::new_with_pitch(self, pitch)
+    }
+
+    /// Allow the sound to be placed between the left and right speakers with
+    /// `set_pan`. Starts centered.
+    ///
+    /// Mono sounds are upmixed to stereo.
+    fn with_adjustable_pan(self) -> Pannable<Self>
+    where
+        Self: Sized,
+    {
+        Pannable::new(self)
+    }
+
+    /// Allow the sound to be placed between the left and right speakers with
+    /// `set_pan` and set the initial pan.
+    ///
+    /// Mono sounds are upmixed to stereo.
+    fn with_adjustable_pan_of(self, pan: f32) -> Pannable<Self>
+    where
+        Self: Sized,
+    {
+        Pannable::new_with_pan(self, pan)
+    }
+
     /// Allow for the sound to be pausable with `set_paused`. Starts unpaused.
     fn pausable(self) -> Pausable<Self>
     where
@@ -220,6 +385,64 @@ pub trait Sound: Send {
         FinishAfter::new(self, duration)
     }
 
+    /// Fade in from silence over `duration` to avoid a click when starting.
+    ///
+    /// See [Fade].
+    fn fade_in(self, duration: Duration) -> Fade<Self>
+    where
+        Self: Sized,
+    {
+        Fade::new_fade_in(self, duration)
+    }
+
+    /// Play `after` of the sound, then fade out over `duration` and finish.
+    ///
+    /// See [Fade].
+    fn fade_out_after(self, after: Duration, duration: Duration) -> Fade<Self>
+    where
+        Self: Sized,
+    {
+        Fade::new_fade_out_after(self, after, duration)
+    }
+
+    /// Allow the sound to be faded out and finished later with `fade_out`
+    /// (e.g. with [Controller::fade_out]).
+    ///
+    /// See [Fade].
+    fn fadable(self) -> Fade<Self>
+    where
+        Self: Sized,
+    {
+        Fade::new(self)
+    }
+
+    /// Write the sound to a 16 bit WAV file at `path` as fast as possible.
+    ///
+    /// The file has the channel count and sample rate of the sound when this
+    /// is called and the sound is converted if those change. Writing stops
+    /// when the sound returns `Finished` or `Paused`.
+    ///
+    /// To render multiple sounds mixed together see
+    /// [OfflineBackend][crate::backends::OfflineBackend].
+    fn write_wav<P: AsRef<Path>>(self, path: P) -> Result<(), crate::Error>
+    where
+        Self: Sized,
+    {
+        let channel_count = self.channel_count();
+        let sample_rate = self.sample_rate();
+        let mut sound =
+            SampleRateConverter::new(ChannelCountConverter::new(self, channel_count), sample_rate);
+        let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+        crate::backends::write_wav(
+            &mut sound,
+            &mut writer,
+            crate::backends::DEFAULT_FRAMES_PER_BATCH,
+            None,
+        )?;
+        writer.flush()?;
+        Ok(())
+    }
+
     /// Skip the next `duration` of samples.
     ///
     /// This is done by calling next_sample repeatedly.
@@ -301,6 +524,96 @@ impl Sound for Box<dyn Sound> {
     fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
         self.deref_mut().next_sample()
     }
+
+    fn next_samples(&mut self, buffer: &mut [i16]) -> Result<(usize, NextSample), crate::Error> {
+        self.deref_mut().next_samples(buffer)
+    }
+
+    fn next_samples_f32(
+        &mut self,
+        buffer: &mut [f32],
+    ) -> Result<(usize, NextSample), crate::Error> {
+        self.deref_mut().next_samples_f32(buffer)
+    }
+
+    fn as_seekable(&mut self) -> Option<&mut dyn Seekable> {
+        self.deref_mut().as_seekable()
+    }
+
+    fn position(&self) -> Option<Duration> {
+        self.deref().position()
+    }
+
+    fn duration(&self) -> Option<Duration> {
+        self.deref().duration()
+    }
+}
+
+/// The default implementation of [Sound::next_samples]. Useful for
+/// implementations that only process whole buffers in some cases.
+pub(crate) fn next_samples_one_by_one<S: Sound + ?Sized>(
+    sound: &mut S,
+    buffer: &mut [i16],
+) -> Result<(usize, NextSample), crate::Error> {
+    for (idx, slot) in buffer.iter_mut().enumerate() {
+        match sound.next_sample()? {
+            NextSample::Sample(s) => *slot = s,
+            next => return Ok((idx, next)),
+        }
+    }
+    Ok(filled(buffer))
+}
+
+/// The value to return from [Sound::next_samples] when all of `buffer` was
+/// filled.
+pub(crate) fn filled(buffer: &[i16]) -> (usize, NextSample) {
+    (
+        buffer.len(),
+        NextSample::Sample(buffer.last().copied().unwrap_or(0)),
+    )
+}
+
+/// The default implementation of [Sound::next_samples_f32].
+pub(crate) fn next_samples_f32_from_i16<S: Sound + ?Sized>(
+    sound: &mut S,
+    buffer: &mut [f32],
+) -> Result<(usize, NextSample), crate::Error> {
+    // Convert in chunks so no allocation is needed.
+    let mut chunk = [0_i16; 256];
+    let mut num_written = 0;
+    while num_written < buffer.len() {
+        let chunk_len = chunk.len().min(buffer.len() - num_written);
+        let (num_read, next) = sound.next_samples(&mut chunk[..chunk_len])?;
+        for (out, sample) in buffer[num_written..].iter_mut().zip(&chunk[..num_read]) {
+            *out = utils::i16_to_f32(*sample);
+        }
+        num_written += num_read;
+        if !matches!(next, NextSample::Sample(_)) {
+            return Ok((num_written, next));
+        }
+    }
+    Ok(filled_f32(buffer))
+}
+
+/// The value to return from [Sound::next_samples_f32] when all of `buffer`
+/// was filled.
+pub(crate) fn filled_f32(buffer: &[f32]) -> (usize, NextSample) {
+    (
+        buffer.len(),
+        NextSample::Sample(utils::f32_to_i16(buffer.last().copied().unwrap_or(0.0))),
+    )
+}
+
+impl Seekable for Box<dyn Sound> {
+    /// Seek the boxed sound if it supports seeking (see
+    /// [Sound::as_seekable]). Otherwise an IoError of
+    /// [ErrorKind::Unsupported][std::io::ErrorKind::Unsupported] is returned.
+    fn seek(&mut self, position: Duration) -> Result<(), crate::Error> {
+        match self.as_seekable() {
+            Some(seekable) => seekable.seek(position),
+            None => Err(std::io::Error::from(std::io::ErrorKind::Unsupported).into()),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -2,6 +2,7 @@
 pub mod decoders;
 pub mod wrappers;
 
+mod master_stage;
 mod memory_sound;
 mod open_file;
 mod silence;
@@ -10,12 +11,16 @@ mod sound_list;
 mod sound_mixer;
 mod sounds_from_fn;
 
+pub(crate) use master_stage::MasterStageProcessor;
+pub use master_stage::{Clipping, MasterStage};
 pub use memory_sound::MemorySound;
 pub use memory_sound::UnsupportedMetadataChangeError;
 pub use open_file::open_file;
 pub use open_file::open_file_with_buffer_capacity;
+pub use open_file::{open_bytes, open_reader};
 pub use silence::Silence;
 pub use sine_wav::SineWav;
 pub use sound_list::SoundList;
 pub use sound_mixer::SoundMixer;
+pub(crate) use sound_mixer::{MixedSound, MixerCommand, SharedOutputFormat, SoundCollector};
 pub use sounds_from_fn::SoundsFromFn;
@@ -21,3 +21,13 @@ pub use qoaudio::DecodeError as QoaDecodeError;
 pub use symphonia::SymphoniaDecoder;
 #[cfg(feature = "hound-wav")]
 pub use wav::WavDecoder;
+
+/// Used by decoders that are only [Seekable][crate::sounds::wrappers::Seekable]
+/// for some readers to return themselves from
+/// [as_seekable][crate::Sound::as_seekable].
+#[cfg(any(feature = "qoa", feature = "hound-wav"))]
+fn as_dyn_seekable<T: crate::sounds::wrappers::Seekable>(
+    sound: &mut T,
+) -> &mut dyn crate::sounds::wrappers::Seekable {
+    sound
+}
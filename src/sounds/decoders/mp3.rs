@@ -6,6 +6,9 @@ use std::io::Read;
 const INPUT_BUFFER_SIZE: usize = 2048;
 
 /// Decoder for the MP3 format.
+///
+/// If the first frame contains a Xing/Info header with a LAME extension the
+/// encoder delay and padding are trimmed for gapless playback.
 pub struct Mp3Decoder<R>
 where
     R: Read + Send,
@@ -24,6 +27,12 @@ where
     output_buffer_data_len: usize,
     output_buffer_next_out_idx: usize,
     metadata_changed: bool,
+    /// True until the first audio frame has been checked for an info header.
+    first_audio_frame: bool,
+    /// Frames of encoder delay that still need to be skipped.
+    frames_to_skip: u64,
+    /// The number of frames left to play before the padding if known.
+    frames_remaining: Option<u64>,
 }
 
 impl<R> Mp3Decoder<R>
@@ -44,6 +53,9 @@ where
             output_buffer_data_len: 0,
             output_buffer_next_out_idx: 0,
             metadata_changed: false,
+            first_audio_frame: true,
+            frames_to_skip: 0,
+            frames_remaining: None,
         };
         // Load the frame first so the channel_count and sample rate are set
         // appropriately
@@ -96,6 +108,10 @@ where
 {
     fn load_next_frame(&mut self) -> std::io::Result<bool> {
         loop {
+            if self.frames_remaining == Some(0) {
+                // Only padding is left.
+                return Ok(false);
+            }
             self.fill_input_buffer()?;
 
             let decoded = self.raw_decoder.next(
@@ -108,9 +124,32 @@ where
             };
 
             let got_samples = match frame {
+                rmp3::Frame::Audio(audio) if self.first_audio_frame && is_info_frame(&audio) => {
+                    self.first_audio_frame = false;
+                    // The info frame decodes to silence so skip it.
+                    if let Some(info) = read_info_header(audio.source()) {
+                        self.frames_to_skip = info.delay;
+                        self.frames_remaining = info.num_frames.map(|num_frames| {
+                            (num_frames * audio.sample_count() as u64)
+                                .saturating_sub(info.delay + info.padding)
+                        });
+                    }
+                    false
+                }
                 rmp3::Frame::Audio(audio) => {
-                    self.output_buffer_data_len = audio.samples().len();
-                    self.output_buffer_next_out_idx = 0;
+                    self.first_audio_frame = false;
+                    let channel_count = audio.channels() as usize;
+                    let frame_count = audio.sample_count() as u64;
+                    let skip = self.frames_to_skip.min(frame_count);
+                    self.frames_to_skip -= skip;
+                    let mut end = frame_count;
+                    if let Some(remaining) = &mut self.frames_remaining {
+                        let take = (*remaining).min(frame_count - skip);
+                        *remaining -= take;
+                        end = skip + take;
+                    }
+                    self.output_buffer_data_len = end as usize * channel_count;
+                    self.output_buffer_next_out_idx = skip as usize * channel_count;
                     if self.sample_rate != audio.sample_rate() {
                         self.metadata_changed = true;
                         self.sample_rate = audio.sample_rate();
@@ -119,8 +158,8 @@ where
                         self.metadata_changed = true;
                         self.channel_count = audio.channels();
                     }
-                    assert!(self.output_buffer_data_len > 0);
-                    true
+                    // The whole frame may have been trimmed.
+                    self.output_buffer_next_out_idx < self.output_buffer_data_len
                 }
                 rmp3::Frame::Other(_) => false,
             };
@@ -149,6 +188,84 @@ where
     }
 }
 
+/// Gapless information from a Xing/Info header.
+struct InfoHeader {
+    /// Frames to skip at the start including the decoder delay.
+    delay: u64,
+    /// Frames to skip at the end.
+    padding: u64,
+    /// The number of MPEG frames (excluding the info frame).
+    num_frames: Option<u64>,
+}
+
+/// The offset of the Xing/Info header in the frame.
+fn info_header_offset(frame: &[u8]) -> Option<usize> {
+    let header = frame.get(..4)?;
+    let is_mpeg1 = (header[1] >> 3) & 0b11 == 0b11;
+    let has_crc = header[1] & 1 == 0;
+    let is_mono = (header[3] >> 6) & 0b11 == 0b11;
+    let side_info_len = match (is_mpeg1, is_mono) {
+        (true, true) => 17,
+        (true, false) => 32,
+        (false, true) => 9,
+        (false, false) => 17,
+    };
+    Some(4 + if has_crc { 2 } else { 0 } + side_info_len)
+}
+
+fn is_info_frame(audio: &rmp3::Audio) -> bool {
+    let frame = audio.source();
+    info_header_offset(frame)
+        .and_then(|offset| frame.get(offset..offset + 4))
+        .is_some_and(|id| id == b"Xing" || id == b"Info")
+}
+
+/// Read the encoder delay and padding from the LAME extension of a Xing/Info
+/// header.
+fn read_info_header(frame: &[u8]) -> Option<InfoHeader> {
+    /// The decoder delay of a standard MP3 decoder that LAME compensates for.
+    const DECODER_DELAY: u64 = 528 + 1;
+
+    let mut offset = info_header_offset(frame)? + 4;
+    let read_u32 = |offset: usize| -> Option<u32> {
+        Some(u32::from_be_bytes(
+            frame.get(offset..offset + 4)?.try_into().ok()?,
+        ))
+    };
+    let flags = read_u32(offset)?;
+    offset += 4;
+    let mut num_frames = None;
+    if flags & 0x1 != 0 {
+        num_frames = Some(read_u32(offset)? as u64);
+        offset += 4;
+    }
+    if flags & 0x2 != 0 {
+        // Number of bytes
+        offset += 4;
+    }
+    if flags & 0x4 != 0 {
+        // Table of contents
+        offset += 100;
+    }
+    if flags & 0x8 != 0 {
+        // Quality
+        offset += 4;
+    }
+    let encoder = frame.get(offset..offset + 4)?;
+    if encoder != b"LAME" && encoder != b"Lavf" && encoder != b"Lavc" {
+        return None;
+    }
+    // Encoder string, revision, lowpass, replay gain and flags come before
+    // the delay and padding which are 12 bits each.
+    let trim = frame.get(offset + 21..offset + 24)?;
+    let trim = u32::from_be_bytes([0, trim[0], trim[1], trim[2]]) as u64;
+    Some(InfoHeader {
+        delay: DECODER_DELAY + (trim >> 12),
+        padding: (trim & 0xfff).saturating_sub(DECODER_DELAY),
+        num_frames,
+    })
+}
+
 #[cfg(test)]
 #[path = "./tests/mp3.rs"]
 mod tests;
@@ -1,16 +1,32 @@
 use crate::sound::NextSample;
+use crate::sounds::wrappers::Seekable;
+use crate::utils;
 use crate::Sound;
-use qoaudio::{DecodeError, QoaDecoder as RawDecoder, QoaItem};
-use std::io::Read;
+use qoaudio::{DecodeError, ProcessingMode, QoaDecoder as RawDecoder, QoaItem};
+use std::io::{Read, Seek, SeekFrom};
+use std::time::Duration;
+
+/// Number of samples per channel in every frame except the last.
+const QOA_FRAME_LEN: u64 =
+    (qoaudio::QOA_SLICE_LEN * qoaudio::MAX_SLICES_PER_CHANNEL_PER_FRAME) as u64;
+const QOA_FILE_HEADER_SIZE: u64 = 8;
 
 /// Decoder for the [QOA](https://qoaformat.org/) format.
 pub struct QoaDecoder<R>
 where
     R: Read + Send,
 {
-    raw_decoder: RawDecoder<R>,
+    /// Only None if a seek failed part way through.
+    raw_decoder: Option<RawDecoder<HeaderThenReader<R>>>,
     sample_rate: u32,
     channel_count: u16,
+    /// Number of samples per channel returned so far.
+    frames_read: u64,
+    /// Number of samples returned so far in the current frame.
+    channel_idx: u16,
+    seeked: bool,
+    /// Set if the reader can seek. See [QoaDecoder::new_seekable].
+    seekable: Option<fn(&mut QoaDecoder<R>) -> &mut dyn Seekable>,
 }
 
 impl<R> QoaDecoder<R>
@@ -18,8 +34,12 @@ where
     R: Read + Send,
 {
     /// Attempts to decode the data as QOA audio.
+    ///
+    /// The decoder is not returned by [as_seekable][Sound::as_seekable] so it
+    /// can not be seeked once boxed. Use
+    /// [new_seekable][QoaDecoder::new_seekable] for readers that can seek.
     pub fn new(data: R) -> Result<QoaDecoder<R>, DecodeError> {
-        let mut raw_decoder = RawDecoder::new(data)?;
+        let mut raw_decoder = RawDecoder::new(HeaderThenReader::new(data))?;
 
         let QoaItem::FrameHeader(first_frame) = raw_decoder
             .next()
@@ -31,15 +51,24 @@ where
         let channel_count = first_frame.num_channels as u16;
 
         Ok(QoaDecoder {
-            raw_decoder,
+            raw_decoder: Some(raw_decoder),
             sample_rate,
             channel_count,
+            frames_read: 0,
+            channel_idx: 0,
+            seeked: false,
+            seekable: None,
         })
     }
 
     /// Return the wrapped Reader
+    ///
+    /// Panics if a previous seek failed since the reader is lost.
     pub fn into_inner(self) -> R {
-        self.raw_decoder.into_inner()
+        self.raw_decoder
+            .expect("reader is lost after a failed seek")
+            .into_inner()
+            .inner
     }
 }
 
@@ -56,20 +85,35 @@ where
     }
 
     fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        if self.seeked {
+            self.seeked = false;
+            return Ok(NextSample::MetadataChanged);
+        }
+        let Some(raw_decoder) = &mut self.raw_decoder else {
+            return Ok(NextSample::Finished);
+        };
         loop {
-            let Some(next_sample) = self.raw_decoder.next() else {
+            let Some(next_sample) = raw_decoder.next() else {
                 return Ok(NextSample::Finished);
             };
             let next_sample = next_sample?;
 
             match next_sample {
-                QoaItem::Sample(s) => return Ok(NextSample::Sample(s)),
+                QoaItem::Sample(s) => {
+                    self.channel_idx += 1;
+                    if self.channel_idx >= self.channel_count {
+                        self.channel_idx = 0;
+                        self.frames_read += 1;
+                    }
+                    return Ok(NextSample::Sample(s));
+                }
                 QoaItem::FrameHeader(f) => {
                     if f.num_channels as u16 != self.channel_count
                         || f.sample_rate != self.sample_rate
                     {
                         self.channel_count = f.num_channels.into();
                         self.sample_rate = f.sample_rate;
+                        self.channel_idx = 0;
                         return Ok(NextSample::MetadataChanged);
                     }
                     // No metadata change. Continue and read next sample
@@ -80,6 +124,143 @@ where
     }
 
     fn on_start_of_batch(&mut self) {}
+
+    fn as_seekable(&mut self) -> Option<&mut dyn Seekable> {
+        self.seekable.map(|as_seekable| as_seekable(self))
+    }
+
+    fn position(&self) -> Option<Duration> {
+        Some(utils::num_samples_to_duration(
+            self.frames_read,
+            1,
+            self.sample_rate,
+        ))
+    }
+
+    /// Returns None for streaming files.
+    fn duration(&self) -> Option<Duration> {
+        match self.raw_decoder.as_ref()?.mode() {
+            ProcessingMode::FixedSamples {
+                sample_rate,
+                samples,
+                ..
+            } => Some(utils::num_samples_to_duration(
+                *samples as u64,
+                1,
+                *sample_rate,
+            )),
+            ProcessingMode::Streaming => None,
+        }
+    }
+}
+
+impl<R> QoaDecoder<R>
+where
+    R: Read + Seek + Send,
+{
+    /// Same as [new][QoaDecoder::new] but the decoder can also be seeked
+    /// through [as_seekable][Sound::as_seekable], e.g. when boxed as a
+    /// `Box<dyn Sound>`.
+    pub fn new_seekable(data: R) -> Result<QoaDecoder<R>, DecodeError> {
+        let mut decoder = Self::new(data)?;
+        decoder.seekable = Some(super::as_dyn_seekable);
+        Ok(decoder)
+    }
+}
+
+impl<R> Seekable for QoaDecoder<R>
+where
+    R: Read + Seek + Send,
+{
+    /// Only files with a known number of samples (i.e. not streaming files)
+    /// can be seeked. If an error occurs part way through seeking the decoder
+    /// will be `Finished` and later seeks return an error.
+    fn seek(&mut self, position: Duration) -> Result<(), crate::Error> {
+        let Some(raw_decoder) = self.raw_decoder.take() else {
+            return Err(std::io::Error::other("the reader was lost in a failed seek").into());
+        };
+        let ProcessingMode::FixedSamples {
+            channels, samples, ..
+        } = *raw_decoder.mode()
+        else {
+            self.raw_decoder = Some(raw_decoder);
+            return Err(std::io::Error::from(std::io::ErrorKind::Unsupported).into());
+        };
+        if samples == 0 {
+            // There is nowhere to seek to.
+            self.raw_decoder = Some(raw_decoder);
+            return Ok(());
+        }
+
+        // Every frame but the last has the same size so we can jump directly
+        // to the frame containing `position` and decode from there.
+        let target =
+            utils::duration_to_num_samples(position, 1, self.sample_rate).min(samples as u64);
+        let frame_idx = target.min(samples as u64 - 1) / QOA_FRAME_LEN;
+        let channels = channels as u64;
+        let frame_size = qoaudio::QOA_HEADER_SIZE as u64
+            + (qoaudio::QOA_LMS_LEN * 4) as u64 * channels
+            + qoaudio::MAX_SLICES_PER_CHANNEL_PER_FRAME as u64 * 8 * channels;
+
+        let mut reader = raw_decoder.into_inner().inner;
+        reader.seek(SeekFrom::Start(
+            QOA_FILE_HEADER_SIZE + frame_idx * frame_size,
+        ))?;
+        let mut header = [0_u8; QOA_FILE_HEADER_SIZE as usize];
+        header[..4].copy_from_slice(&qoaudio::QOA_MAGIC.to_be_bytes());
+        header[4..].copy_from_slice(&samples.to_be_bytes());
+        let mut raw_decoder = RawDecoder::new(HeaderThenReader {
+            header,
+            header_pos: 0,
+            inner: reader,
+        })?;
+
+        let mut to_skip = (target - frame_idx * QOA_FRAME_LEN) * channels;
+        while to_skip > 0 {
+            match raw_decoder.next() {
+                Some(Ok(QoaItem::Sample(_))) => to_skip -= 1,
+                Some(Ok(QoaItem::FrameHeader(_))) => (),
+                Some(Err(e)) => return Err(e.into()),
+                None => break,
+            }
+        }
+        self.raw_decoder = Some(raw_decoder);
+        self.frames_read = target;
+        self.channel_idx = 0;
+        self.seeked = true;
+        Ok(())
+    }
+}
+
+/// Reads `header` before reading from `inner`.
+///
+/// The raw decoder expects to read the file header first so this allows it to
+/// be recreated at the start of a frame when seeking.
+struct HeaderThenReader<R> {
+    header: [u8; QOA_FILE_HEADER_SIZE as usize],
+    header_pos: usize,
+    inner: R,
+}
+
+impl<R> HeaderThenReader<R> {
+    fn new(inner: R) -> Self {
+        HeaderThenReader {
+            header: [0; QOA_FILE_HEADER_SIZE as usize],
+            header_pos: QOA_FILE_HEADER_SIZE as usize,
+            inner,
+        }
+    }
+}
+
+impl<R: Read> Read for HeaderThenReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.header_pos < self.header.len() {
+            let num_read = (&self.header[self.header_pos..]).read(buf)?;
+            self.header_pos += num_read;
+            return Ok(num_read);
+        }
+        self.inner.read(buf)
+    }
 }
 
 impl From<DecodeError> for crate::Error {
@@ -1,16 +1,25 @@
+use std::time::Duration;
+
+use crate::sounds::wrappers::Seekable;
+use crate::utils;
 use crate::NextSample;
 use crate::Sound;
 use symphonia::core::audio::{AudioBuffer, AudioBufferRef, Channels, Signal};
 use symphonia::core::codecs::{Decoder, DecoderOptions, CODEC_TYPE_NULL};
-use symphonia::core::conv::FromSample;
-use symphonia::core::errors::Error;
-use symphonia::core::formats::{FormatOptions, FormatReader};
+use symphonia::core::conv::{ConvertibleSample, FromSample};
+use symphonia::core::errors::{Error, SeekErrorKind};
+use symphonia::core::formats::{FormatOptions, FormatReader, SeekMode, SeekTo};
 use symphonia::core::io::{MediaSource, MediaSourceStream};
-use symphonia::core::meta::{Limit, MetadataOptions};
+use symphonia::core::meta::{Limit, MetadataOptions, MetadataRevision, Value};
 use symphonia::core::probe::Hint;
 use symphonia::core::sample::Sample;
+use symphonia::core::units::{Time, TimeBase};
 
 /// Decode formats using the Symphonia crate decoders.
+///
+/// Encoder delay and padding are trimmed for gapless playback when the file
+/// contains that information (e.g. a LAME/Xing header in MP3 files or an
+/// iTunSMPB tag in MP4 files).
 pub struct SymphoniaDecoder {
     sample_rate: u32,
 
@@ -19,8 +28,25 @@ pub struct SymphoniaDecoder {
 
     channels: Channels,
     track_id: u32,
+    time_base: Option<TimeBase>,
+    /// The total number of frames in the track if known.
+    n_frames: Option<u64>,
+    /// Trimming that is not done by the Symphonia format reader.
+    gapless: Option<GaplessInfo>,
+    /// The timestamp of the last decoded packet.
+    packet_ts: u64,
     next_channel_idx: u16,
     next_sample_idx: usize,
+    /// The index after the last frame of the last decoded packet to play.
+    packet_end_idx: usize,
+    seeked: bool,
+}
+
+/// Encoder delay and number of frames from an iTunSMPB tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct GaplessInfo {
+    delay: u64,
+    num_frames: u64,
 }
 
 impl SymphoniaDecoder {
@@ -41,10 +67,17 @@ impl SymphoniaDecoder {
             limit_metadata_bytes: Limit::Maximum(1),
             limit_visual_bytes: Limit::Maximum(1),
         };
-        let fmt_opts: FormatOptions = Default::default();
-        let probed = symphonia::default::get_probe().format(&hint, mss, &fmt_opts, &meta_opts)?;
+        let fmt_opts = FormatOptions {
+            enable_gapless: true,
+            ..Default::default()
+        };
+        let mut probed =
+            symphonia::default::get_probe().format(&hint, mss, &fmt_opts, &meta_opts)?;
 
-        let format = probed.format;
+        let mut format = probed.format;
+        // Tags can be either in the container or before it (e.g. ID3).
+        let smpb = find_itunsmpb(format.metadata().current())
+            .or_else(|| find_itunsmpb(probed.metadata.get().as_ref().and_then(|m| m.current())));
 
         // Find the first audio track with a known (decodable) codec.
         let track = format
@@ -55,6 +88,13 @@ impl SymphoniaDecoder {
                 "No track with a supported codec was found",
             ))?;
         let track_id = track.id;
+        let time_base = track.codec_params.time_base;
+        // If the format reader knows the delay it trims the packets itself.
+        let gapless = smpb.filter(|_| track.codec_params.delay.is_none());
+        let n_frames = match gapless {
+            Some(gapless) => Some(gapless.num_frames),
+            None => track.codec_params.n_frames,
+        };
 
         let dec_opts: DecoderOptions = Default::default();
         let decoder = symphonia::default::get_codecs().make(&track.codec_params, &dec_opts)?;
@@ -65,8 +105,14 @@ impl SymphoniaDecoder {
             format,
             channels: Channels::empty(),
             track_id,
+            time_base,
+            n_frames,
+            gapless,
+            packet_ts: 0,
             next_channel_idx: 0,
             next_sample_idx: 0,
+            packet_end_idx: 0,
+            seeked: false,
         };
         // Ignore metadata changed since no one has seen the old values
         let _ = decoder.decode_next_packet();
@@ -84,36 +130,138 @@ impl Sound for SymphoniaDecoder {
     }
 
     fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        Ok(match self.next_sample_as::<i16>()? {
+            Ok(sample) => NextSample::Sample(sample),
+            Err(next) => next,
+        })
+    }
+
+    fn next_samples_f32(
+        &mut self,
+        buffer: &mut [f32],
+    ) -> Result<(usize, NextSample), crate::Error> {
+        for (idx, slot) in buffer.iter_mut().enumerate() {
+            match self.next_sample_as::<f32>()? {
+                Ok(sample) => *slot = sample,
+                Err(next) => return Ok((idx, next)),
+            }
+        }
+        Ok(crate::sound::filled_f32(buffer))
+    }
+
+    fn on_start_of_batch(&mut self) {}
+
+    fn as_seekable(&mut self) -> Option<&mut dyn Seekable> {
+        Some(self)
+    }
+
+    fn position(&self) -> Option<Duration> {
+        let mut frame = self.ts_to_frames(self.packet_ts) + self.next_sample_idx as u64;
+        if self.next_channel_idx as usize >= self.channels.count() {
+            frame += 1;
+        }
+        let frame = frame.saturating_sub(self.delay());
+        Some(utils::num_samples_to_duration(frame, 1, self.sample_rate))
+    }
+
+    fn duration(&self) -> Option<Duration> {
+        self.n_frames
+            .map(|n_frames| utils::num_samples_to_duration(n_frames, 1, self.sample_rate))
+    }
+}
+
+impl Seekable for SymphoniaDecoder {
+    fn seek(&mut self, position: Duration) -> Result<(), crate::Error> {
+        let delay = utils::num_samples_to_duration(self.delay(), 1, self.sample_rate);
+        let seek_result = self.format.seek(
+            SeekMode::Accurate,
+            SeekTo::Time {
+                time: Time::from((position + delay).as_secs_f64()),
+                track_id: Some(self.track_id),
+            },
+        );
+        self.decoder.reset();
+        let seeked_to = match seek_result {
+            Ok(seeked_to) => seeked_to,
+            Err(Error::SeekError(SeekErrorKind::OutOfRange)) => {
+                // Seeked past the end. Skip the remaining packets without
+                // decoding them so the next call to next_sample finishes.
+                while self.format.next_packet().is_ok() {}
+                self.next_channel_idx = 0;
+                self.next_sample_idx = self.packet_end_idx;
+                self.seeked = true;
+                return Ok(());
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        // The format reader seeks to a packet at or before the requested
+        // position so decode packets and skip frames until we get there.
+        loop {
+            match self.decode_next_packet() {
+                Ok(_) => (),
+                Err(Error::IoError(err))
+                    if err.kind() == std::io::ErrorKind::UnexpectedEof
+                        && err.to_string() == "end of stream" =>
+                {
+                    // Seeked past the end. The next call to next_sample will
+                    // hit the end of stream again and return Finished.
+                    self.next_channel_idx = 0;
+                    self.next_sample_idx = self.packet_end_idx;
+                    break;
+                }
+                Err(e) => return Err(e.into()),
+            }
+            let frames_to_skip =
+                self.ts_to_frames(seeked_to.required_ts.saturating_sub(self.packet_ts));
+            if frames_to_skip < self.packet_end_idx as u64 {
+                // The packet may also start with frames trimmed by
+                // decode_next_packet.
+                self.next_sample_idx = self.next_sample_idx.max(frames_to_skip as usize);
+                break;
+            }
+        }
+        self.seeked = true;
+        Ok(())
+    }
+}
+
+impl SymphoniaDecoder {
+    /// Return the next sample converted to `T` or the non-sample variant that
+    /// `next_sample` should return.
+    fn next_sample_as<T: ConvertibleSample>(
+        &mut self,
+    ) -> Result<Result<T, NextSample>, crate::Error> {
+        if self.seeked {
+            self.seeked = false;
+            return Ok(Err(NextSample::MetadataChanged));
+        }
         if self.next_channel_idx >= self.channels.count().try_into().unwrap() {
             self.next_channel_idx = 0;
             self.next_sample_idx += 1;
         }
-        let mut buf_ref = self.decoder.last_decoded();
-        if self.next_sample_idx >= buf_ref.frames() {
+        // Loop since packets can be empty after trimming.
+        while self.next_sample_idx >= self.packet_end_idx {
             match self.decode_next_packet() {
-                Ok(true) => return Ok(NextSample::MetadataChanged),
+                Ok(true) => return Ok(Err(NextSample::MetadataChanged)),
                 Ok(false) => (),
                 Err(Error::IoError(err))
                     if err.kind() == std::io::ErrorKind::UnexpectedEof
                         && err.to_string() == "end of stream" =>
                 {
                     // According to Symphonia this is the only way to detect an end of stream
-                    return Ok(NextSample::Finished);
+                    return Ok(Err(NextSample::Finished));
                 }
                 // TODO: Handle errors better when awedio allows returning errors.
                 Err(e) => return Err(e.into()),
             };
-            buf_ref = self.decoder.last_decoded();
         }
+        let buf_ref = self.decoder.last_decoded();
         let sample = extract_sample_from_ref(&buf_ref, self.next_channel_idx, self.next_sample_idx);
         self.next_channel_idx += 1;
-        Ok(NextSample::Sample(sample))
+        Ok(Ok(sample))
     }
 
-    fn on_start_of_batch(&mut self) {}
-}
-
-impl SymphoniaDecoder {
     fn decode_next_packet(&mut self) -> Result<bool, Error> {
         loop {
             let packet = self.format.next_packet()?;
@@ -140,8 +288,10 @@ impl SymphoniaDecoder {
                 Err(e) => return Err(e),
             };
 
+            self.packet_ts = packet.ts();
             self.next_channel_idx = 0;
             self.next_sample_idx = 0;
+            self.packet_end_idx = buf_ref.frames();
             let mut metadata_changed = false;
             if buf_ref.spec().channels != self.channels {
                 self.channels = buf_ref.spec().channels;
@@ -151,16 +301,79 @@ impl SymphoniaDecoder {
                 self.sample_rate = buf_ref.spec().rate;
                 metadata_changed = true;
             }
+            if let Some(gapless) = self.gapless {
+                self.trim_packet(gapless);
+            }
             return Ok(metadata_changed);
         }
     }
+
+    /// Limit the frames played from the last decoded packet to the frames
+    /// after the encoder delay and before the padding.
+    fn trim_packet(&mut self, gapless: GaplessInfo) {
+        let start = self.ts_to_frames(self.packet_ts);
+        let end = start + self.packet_end_idx as u64;
+        let first = gapless.delay.clamp(start, end);
+        let last = (gapless.delay + gapless.num_frames).clamp(start, end);
+        self.next_sample_idx = (first - start) as usize;
+        self.packet_end_idx = (last - start) as usize;
+    }
+
+    /// The number of frames trimmed from the start of the track that are not
+    /// included in packet timestamps.
+    fn delay(&self) -> u64 {
+        self.gapless.map(|gapless| gapless.delay).unwrap_or(0)
+    }
+
+    /// Convert a timestamp duration of the track to a number of frames.
+    fn ts_to_frames(&self, ts: u64) -> u64 {
+        match self.time_base {
+            Some(time_base) => {
+                let time = time_base.calc_time(ts);
+                time.seconds * self.sample_rate as u64
+                    + (time.frac * self.sample_rate as f64).round() as u64
+            }
+            // Without a time base timestamps are normally in frames.
+            None => ts,
+        }
+    }
+}
+
+/// Find and parse an iTunSMPB tag which is written by iTunes and other AAC
+/// encoders to indicate the encoder delay and padding.
+fn find_itunsmpb(revision: Option<&MetadataRevision>) -> Option<GaplessInfo> {
+    revision?
+        .tags()
+        .iter()
+        .filter(|tag| tag.key.ends_with("iTunSMPB"))
+        .find_map(|tag| match &tag.value {
+            Value::String(value) => parse_itunsmpb(value),
+            _ => None,
+        })
+}
+
+/// Parse the value of an iTunSMPB tag. It contains space separated hex
+/// numbers: a reserved field, the delay, the padding and the number of
+/// frames.
+fn parse_itunsmpb(value: &str) -> Option<GaplessInfo> {
+    let mut fields = value
+        .split_whitespace()
+        .map(|field| u64::from_str_radix(field, 16));
+    let _reserved = fields.next()?.ok()?;
+    let delay = fields.next()?.ok()?;
+    let _padding = fields.next()?.ok()?;
+    let num_frames = fields.next()?.ok()?;
+    if num_frames == 0 {
+        return None;
+    }
+    Some(GaplessInfo { delay, num_frames })
 }
 
-pub fn extract_sample_from_ref(
+pub fn extract_sample_from_ref<T: ConvertibleSample>(
     buffer: &AudioBufferRef,
     channel_idx: u16,
     sample_idx: usize,
-) -> i16 {
+) -> T {
     match buffer {
         AudioBufferRef::U8(buffer) => extract_sample(buffer, channel_idx, sample_idx),
         AudioBufferRef::U16(buffer) => extract_sample(buffer, channel_idx, sample_idx),
@@ -175,13 +388,13 @@ pub fn extract_sample_from_ref(
     }
 }
 
-pub fn extract_sample<S: Sample>(
+pub fn extract_sample<S: Sample, T>(
     buffer: &AudioBuffer<S>,
     channel_idx: u16,
     sample_idx: usize,
-) -> i16
+) -> T
 where
-    i16: FromSample<S>,
+    T: FromSample<S>,
 {
     FromSample::from_sample(buffer.chan(channel_idx as usize)[sample_idx])
 }
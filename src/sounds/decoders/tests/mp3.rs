@@ -8,19 +8,11 @@ fn samples_of_test_file() -> std::io::Result<()> {
     let mut decoder = Mp3Decoder::new(std::io::Cursor::new(SINE_WAVE_FILE));
     assert_eq!(decoder.sample_rate(), 44100);
     assert_eq!(decoder.channel_count(), 1);
-    for _i in 0..2258 {
-        // println!("i: {_i}");
-        let sample = decoder.next_sample().unwrap();
-        match sample {
-            NextSample::Sample(s) => {
-                // println!("s: {s}");
-                assert!(s.abs() < 700)
-            }
-            NextSample::MetadataChanged => unreachable!(),
-            NextSample::Paused => unreachable!(),
-            NextSample::Finished => unreachable!(),
-        }
-    }
+    // The info frame and encoder delay are trimmed
+    let NextSample::Sample(first) = decoder.next_sample().unwrap() else {
+        panic!("expected a sample");
+    };
+    assert!(first.abs() < 700);
     assert_eq!(decoder.next_sample().unwrap(), NextSample::Sample(4235)); // 2
     assert_eq!(decoder.next_sample().unwrap(), NextSample::Sample(8784)); // 3
     assert_eq!(decoder.next_sample().unwrap(), NextSample::Sample(12774)); // 4
@@ -33,7 +25,9 @@ fn samples_of_test_file() -> std::io::Result<()> {
     assert_eq!(decoder.next_sample().unwrap(), NextSample::Sample(30800)); // 11
     assert_eq!(decoder.next_sample().unwrap(), NextSample::Sample(31010)); // 12
     assert_eq!(decoder.next_sample().unwrap(), NextSample::Sample(30771)); // 13
-    for _i in 0..4642 {
+
+    // and so is the padding.
+    for _i in 0..4398 {
         // println!("i: {_i}");
         let sample = decoder.next_sample().unwrap();
         match sample {
@@ -47,3 +41,38 @@ fn samples_of_test_file() -> std::io::Result<()> {
     assert_eq!(decoder.next_sample().unwrap(), NextSample::Finished);
     Ok(())
 }
+
+#[test]
+fn gapless_tracks_concatenate_without_silence() {
+    let decode = || {
+        let mut decoder = Mp3Decoder::new(std::io::Cursor::new(SINE_WAVE_FILE));
+        let mut samples = Vec::new();
+        while let NextSample::Sample(s) = decoder.next_sample().unwrap() {
+            samples.push(s);
+        }
+        samples
+    };
+    let track = decode();
+    // Same length as the WAV the MP3 was encoded from.
+    assert_eq!(track.len(), 4411);
+
+    let mut list = crate::sounds::SoundList::new();
+    for _ in 0..2 {
+        list.add(Box::new(Mp3Decoder::new(std::io::Cursor::new(
+            SINE_WAVE_FILE,
+        ))));
+    }
+    let mut samples = Vec::new();
+    loop {
+        match list.next_sample().unwrap() {
+            NextSample::Sample(s) => samples.push(s),
+            NextSample::MetadataChanged => {}
+            NextSample::Paused => unreachable!(),
+            NextSample::Finished => break,
+        }
+    }
+    let expected: Vec<i16> = track.iter().chain(&track).copied().collect();
+    assert_eq!(samples, expected);
+    // The sine starts immediately in the second track.
+    assert!(samples[4411 + 2].abs() > 8000);
+}
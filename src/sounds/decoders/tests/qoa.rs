@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use super::*;
 use crate::NextSample;
 
@@ -34,3 +36,63 @@ fn samples_of_test_file() -> std::io::Result<()> {
     assert_eq!(decoder.next_sample().unwrap(), NextSample::Finished);
     Ok(())
 }
+
+#[test]
+fn seek_matches_unseeked_samples() {
+    let mut reference = QoaDecoder::new(std::io::Cursor::new(SINE_WAVE_FILE)).unwrap();
+    for _ in 0..3000 {
+        reference.next_sample().unwrap();
+    }
+    let mut decoder = QoaDecoder::new(std::io::Cursor::new(SINE_WAVE_FILE)).unwrap();
+    decoder.next_sample().unwrap();
+    // 3000 samples at 44100 Hz
+    decoder.seek(Duration::from_micros(68_028)).unwrap();
+    assert_eq!(decoder.next_sample().unwrap(), NextSample::MetadataChanged);
+    for _ in 0..100 {
+        assert_eq!(
+            decoder.next_sample().unwrap(),
+            reference.next_sample().unwrap()
+        );
+    }
+    decoder.seek(Duration::from_secs(1)).unwrap();
+    assert_eq!(decoder.next_sample().unwrap(), NextSample::MetadataChanged);
+    assert_eq!(decoder.next_sample().unwrap(), NextSample::Finished);
+}
+
+#[test]
+fn position_and_duration() {
+    let mut decoder = QoaDecoder::new(std::io::Cursor::new(SINE_WAVE_FILE)).unwrap();
+    // 4411 samples
+    assert_eq!(decoder.duration(), Some(Duration::from_micros(100_022)));
+    assert_eq!(decoder.position(), Some(Duration::ZERO));
+    for _ in 0..441 {
+        decoder.next_sample().unwrap();
+    }
+    assert_eq!(decoder.position(), Some(Duration::from_millis(10)));
+    decoder.seek(Duration::from_millis(50)).unwrap();
+    assert_eq!(decoder.position(), Some(Duration::from_millis(50)));
+}
+
+/// Reads from a cursor but fails to seek.
+struct UnseekableReader(std::io::Cursor<&'static [u8]>);
+
+impl std::io::Read for UnseekableReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+impl std::io::Seek for UnseekableReader {
+    fn seek(&mut self, _pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        Err(std::io::Error::from(std::io::ErrorKind::Unsupported))
+    }
+}
+
+#[test]
+fn seeking_after_a_failed_seek_is_an_error() {
+    let mut decoder =
+        QoaDecoder::new(UnseekableReader(std::io::Cursor::new(SINE_WAVE_FILE))).unwrap();
+    assert!(decoder.seek(Duration::from_millis(50)).is_err());
+    assert_eq!(decoder.next_sample().unwrap(), NextSample::Finished);
+    assert!(decoder.seek(Duration::ZERO).is_err());
+}
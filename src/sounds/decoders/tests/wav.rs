@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use super::*;
 use crate::NextSample;
 
@@ -35,3 +37,55 @@ fn samples_of_test_file() -> std::io::Result<()> {
     assert_eq!(decoder.next_sample().unwrap(), NextSample::Finished);
     Ok(())
 }
+
+#[test]
+fn seek_matches_unseeked_samples() {
+    let mut reference = WavDecoder::new(std::io::Cursor::new(SINE_WAVE_FILE)).unwrap();
+    for _ in 0..3000 {
+        reference.next_sample().unwrap();
+    }
+    let mut decoder = WavDecoder::new(std::io::Cursor::new(SINE_WAVE_FILE)).unwrap();
+    decoder.next_sample().unwrap();
+    // 3000 samples at 44100 Hz
+    decoder.seek(Duration::from_micros(68_028)).unwrap();
+    assert_eq!(decoder.next_sample().unwrap(), NextSample::MetadataChanged);
+    for _ in 0..100 {
+        assert_eq!(
+            decoder.next_sample().unwrap(),
+            reference.next_sample().unwrap()
+        );
+    }
+    decoder.seek(Duration::from_secs(1)).unwrap();
+    assert_eq!(decoder.next_sample().unwrap(), NextSample::MetadataChanged);
+    assert_eq!(decoder.next_sample().unwrap(), NextSample::Finished);
+}
+
+#[test]
+fn position_and_duration() {
+    let mut decoder = WavDecoder::new(std::io::Cursor::new(SINE_WAVE_FILE)).unwrap();
+    // 4411 samples
+    assert_eq!(decoder.duration(), Some(Duration::from_micros(100_022)));
+    assert_eq!(decoder.position(), Some(Duration::ZERO));
+    for _ in 0..441 {
+        decoder.next_sample().unwrap();
+    }
+    assert_eq!(decoder.position(), Some(Duration::from_millis(10)));
+    decoder.seek(Duration::from_millis(50)).unwrap();
+    assert_eq!(decoder.position(), Some(Duration::from_millis(50)));
+}
+
+#[test]
+fn f32_samples_match_i16_samples() {
+    let mut decoder = WavDecoder::new(std::io::Cursor::new(SINE_WAVE_FILE)).unwrap();
+    let mut expected = Vec::new();
+    while let NextSample::Sample(s) = decoder.next_sample().unwrap() {
+        expected.push(crate::utils::i16_to_f32(s));
+    }
+    let mut decoder = WavDecoder::new(std::io::Cursor::new(SINE_WAVE_FILE)).unwrap();
+    let mut samples = vec![0.0; expected.len() + 10];
+    assert_eq!(
+        decoder.next_samples_f32(&mut samples).unwrap(),
+        (expected.len(), NextSample::Finished)
+    );
+    assert_eq!(&samples[..expected.len()], &expected[..]);
+}
@@ -1,6 +1,9 @@
-use std::io::Read;
+use std::io::{Read, Seek};
+use std::time::Duration;
 
 use crate::sound::NextSample;
+use crate::sounds::wrappers::Seekable;
+use crate::utils;
 use crate::Sound;
 
 use hound::{SampleFormat, WavReader};
@@ -15,6 +18,11 @@ where
     reader: WavReader<R>,
     sample_rate: u32,
     channel_count: u16,
+    /// Number of samples (not frames) returned so far.
+    samples_read: u64,
+    seeked: bool,
+    /// Set if the reader can seek. See [WavDecoder::new_seekable].
+    seekable: Option<fn(&mut WavDecoder<R>) -> &mut dyn Seekable>,
 }
 
 impl<R> WavDecoder<R>
@@ -22,6 +30,10 @@ where
     R: Read + Send,
 {
     /// Attempts to decode the data as WAV.
+    ///
+    /// The decoder is not returned by [as_seekable][Sound::as_seekable] so it
+    /// can not be seeked once boxed. Use
+    /// [new_seekable][WavDecoder::new_seekable] for readers that can seek.
     pub fn new(data: R) -> Result<WavDecoder<R>, hound::Error> {
         let reader = WavReader::new(data)?;
         let spec = reader.spec();
@@ -33,6 +45,9 @@ where
             reader,
             sample_rate,
             channel_count,
+            samples_read: 0,
+            seeked: false,
+            seekable: None,
         })
     }
 
@@ -55,13 +70,17 @@ where
     }
 
     fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        if self.seeked {
+            self.seeked = false;
+            return Ok(NextSample::MetadataChanged);
+        }
         let spec = self.reader.spec();
         let maybe_sample = match (spec.sample_format, spec.bits_per_sample) {
             (SampleFormat::Float, 32) => self
                 .reader
                 .samples()
                 .next()
-                .map(|value| value.map(f32_to_i16)),
+                .map(|value| value.map(utils::f32_to_i16)),
             (SampleFormat::Int, 8) => self
                 .reader
                 .samples()
@@ -83,18 +102,99 @@ where
             }
         };
         match maybe_sample {
-            Some(Ok(sample)) => Ok(NextSample::Sample(sample)),
+            Some(Ok(sample)) => {
+                self.samples_read += 1;
+                Ok(NextSample::Sample(sample))
+            }
             Some(Err(e)) => Err(e.into()),
             None => Ok(NextSample::Finished),
         }
     }
 
+    fn next_samples_f32(
+        &mut self,
+        buffer: &mut [f32],
+    ) -> Result<(usize, NextSample), crate::Error> {
+        if self.seeked {
+            self.seeked = false;
+            return Ok((0, NextSample::MetadataChanged));
+        }
+        let spec = self.reader.spec();
+        for (idx, slot) in buffer.iter_mut().enumerate() {
+            let maybe_sample = match spec.sample_format {
+                SampleFormat::Float => self.reader.samples::<f32>().next(),
+                SampleFormat::Int => self
+                    .reader
+                    .samples::<i32>()
+                    .next()
+                    .map(|value| value.map(|i| int_to_f32(i, spec.bits_per_sample))),
+            };
+            match maybe_sample {
+                Some(Ok(sample)) => {
+                    self.samples_read += 1;
+                    *slot = sample;
+                }
+                Some(Err(e)) => return Err(e.into()),
+                None => return Ok((idx, NextSample::Finished)),
+            }
+        }
+        Ok(crate::sound::filled_f32(buffer))
+    }
+
     fn on_start_of_batch(&mut self) {}
+
+    fn as_seekable(&mut self) -> Option<&mut dyn Seekable> {
+        self.seekable.map(|as_seekable| as_seekable(self))
+    }
+
+    fn position(&self) -> Option<Duration> {
+        Some(utils::num_samples_to_duration(
+            self.samples_read,
+            self.channel_count,
+            self.sample_rate,
+        ))
+    }
+
+    fn duration(&self) -> Option<Duration> {
+        Some(utils::num_samples_to_duration(
+            self.reader.duration() as u64,
+            1,
+            self.sample_rate,
+        ))
+    }
 }
 
-// Lossy
-fn f32_to_i16(f: f32) -> i16 {
-    (f.max(-1.0).min(1.0) * i16::MAX as f32) as i16
+impl<R> WavDecoder<R>
+where
+    R: Read + Seek + Send,
+{
+    /// Same as [new][WavDecoder::new] but the decoder can also be seeked
+    /// through [as_seekable][Sound::as_seekable], e.g. when boxed as a
+    /// `Box<dyn Sound>`.
+    pub fn new_seekable(data: R) -> Result<WavDecoder<R>, hound::Error> {
+        let mut decoder = Self::new(data)?;
+        decoder.seekable = Some(super::as_dyn_seekable);
+        Ok(decoder)
+    }
+}
+
+impl<R> Seekable for WavDecoder<R>
+where
+    R: Read + Seek + Send,
+{
+    fn seek(&mut self, position: Duration) -> Result<(), crate::Error> {
+        let frame = utils::duration_to_num_samples(position, 1, self.sample_rate);
+        // hound does not allow seeking past the end.
+        let frame = frame.min(self.reader.duration() as u64) as u32;
+        self.reader.seek(frame)?;
+        self.samples_read = frame as u64 * self.channel_count as u64;
+        self.seeked = true;
+        Ok(())
+    }
+}
+
+fn int_to_f32(i: i32, bits_per_sample: u16) -> f32 {
+    i as f32 / ((1_i64 << (bits_per_sample - 1)) - 1) as f32
 }
 
 fn i8_to_i16(i: i8) -> i16 {
@@ -0,0 +1,294 @@
+use std::{collections::VecDeque, time::Duration};
+
+use crate::utils;
+
+/// The final processing applied by a [SoundMixer][super::SoundMixer] after
+/// all sounds have been summed.
+///
+/// Sounds are summed with more range than a sample so overlapping loud
+/// sounds are only limited once by `clipping` after `gain` is applied.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MasterStage {
+    /// Multiplier applied to the sum of all sounds. Values below 1.0 leave
+    /// headroom for overlapping sounds.
+    pub gain: f32,
+    /// How samples outside of the range of a sample are brought back in
+    /// range.
+    pub clipping: Clipping,
+}
+
+impl Default for MasterStage {
+    /// No gain change and hard clipping.
+    fn default() -> Self {
+        MasterStage {
+            gain: 1.0,
+            clipping: Clipping::default(),
+        }
+    }
+}
+
+/// How [MasterStage] keeps the mixed output within the range of a sample.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Clipping {
+    /// Samples beyond full scale are clamped. This is harsh but leaves
+    /// samples within range unchanged.
+    ///
+    /// `next_samples_f32` output is not clamped so the consumer of the f32
+    /// samples decides how to clip.
+    #[default]
+    Hard,
+    /// Samples above half of full scale are smoothly compressed such that
+    /// full scale is never exceeded.
+    Soft,
+    /// Delay the output by `look_ahead` and lower the gain ahead of any peak
+    /// above full scale so it is never exceeded, then return to unity gain
+    /// over roughly `release`.
+    ///
+    /// This changes the sound the least when overlapping sounds peak but
+    /// adds `look_ahead` of latency. When all sounds finish the mixer keeps
+    /// outputting until the delayed samples have been played out.
+    Limiter {
+        /// How far ahead of a peak the gain starts lowering.
+        look_ahead: Duration,
+        /// How long the gain takes to mostly recover after a peak.
+        release: Duration,
+    },
+}
+
+/// The level where [Clipping::Soft] starts compressing.
+const SOFT_CLIP_KNEE: f32 = 0.5;
+
+/// Applies a [MasterStage] to samples in order of the channels they are for.
+pub(crate) struct MasterStageProcessor {
+    stage: MasterStage,
+    channel_count: u16,
+    sample_rate: u32,
+    limiter: Option<Limiter>,
+}
+
+impl MasterStageProcessor {
+    pub(crate) fn new(stage: MasterStage, channel_count: u16, sample_rate: u32) -> Self {
+        let limiter = match stage.clipping {
+            Clipping::Hard | Clipping::Soft => None,
+            Clipping::Limiter {
+                look_ahead,
+                release,
+            } => Some(Limiter::new(
+                channel_count,
+                sample_rate,
+                look_ahead,
+                release,
+            )),
+        };
+        MasterStageProcessor {
+            stage,
+            channel_count,
+            sample_rate,
+            limiter,
+        }
+    }
+
+    pub(crate) fn stage(&self) -> MasterStage {
+        self.stage
+    }
+
+    /// True if this processes samples of the given output format.
+    pub(crate) fn is_for(&self, channel_count: u16, sample_rate: u32) -> bool {
+        (self.channel_count, self.sample_rate) == (channel_count, sample_rate)
+    }
+
+    /// Discard any state such as delayed samples without allocating. The next
+    /// sample is for the first channel.
+    pub(crate) fn reset(&mut self) {
+        if let Some(limiter) = &mut self.limiter {
+            limiter.reset();
+        }
+    }
+
+    /// True if samples are returned unchanged except for clamping.
+    pub(crate) fn is_hard_clip_only(&self) -> bool {
+        self.stage.gain == 1.0 && self.stage.clipping == Clipping::Hard
+    }
+
+    /// Process the next sample where 1.0 is full scale.
+    pub(crate) fn process(&mut self, sample: f32) -> f32 {
+        let sample = sample * self.stage.gain;
+        match self.stage.clipping {
+            Clipping::Hard => sample,
+            Clipping::Soft => soft_clip(sample),
+            Clipping::Limiter { .. } => match &mut self.limiter {
+                Some(limiter) => limiter.process(sample).clamp(-1.0, 1.0),
+                None => sample,
+            },
+        }
+    }
+
+    /// Complete a partially processed frame (e.g. when the mixer finished in
+    /// the middle of a frame) so that the next sample is for the first
+    /// channel.
+    pub(crate) fn finish_frame(&mut self) {
+        if let Some(limiter) = &mut self.limiter {
+            while limiter.next_channel_idx != 0 {
+                limiter.process(0.0);
+            }
+        }
+    }
+
+    /// True if samples are delayed before being output.
+    pub(crate) fn delays(&self) -> bool {
+        self.limiter.is_some()
+    }
+
+    /// True if delayed samples that are not silent have not been output yet.
+    pub(crate) fn has_tail(&self) -> bool {
+        self.limiter
+            .as_ref()
+            .is_some_and(|limiter| limiter.frames_until_silent > 0)
+    }
+
+    /// Process the next sample of the i16 sum of sounds.
+    pub(crate) fn process_i32(&mut self, sample: i32) -> i16 {
+        if self.is_hard_clip_only() {
+            return sample.clamp(i16::MIN as i32, i16::MAX as i32) as i16;
+        }
+        utils::f32_to_i16(self.process(sample as f32 / i16::MAX as f32))
+    }
+}
+
+fn soft_clip(sample: f32) -> f32 {
+    let magnitude = sample.abs();
+    if magnitude <= SOFT_CLIP_KNEE {
+        return sample;
+    }
+    // tanh has a slope of 1 at 0 so this joins the linear part smoothly.
+    let range = 1.0 - SOFT_CLIP_KNEE;
+    let compressed = SOFT_CLIP_KNEE + range * ((magnitude - SOFT_CLIP_KNEE) / range).tanh();
+    compressed.copysign(sample)
+}
+
+/// A look-ahead peak limiter.
+///
+/// Samples are delayed by `delay_frames`. Once a frame has been read its
+/// required gain (the gain that brings its peak to full scale) is known and
+/// the gain is ramped linearly so that it is reached by the time that frame
+/// is output.
+struct Limiter {
+    channel_count: usize,
+    delay_frames: u64,
+    /// The delayed samples. The oldest sample is at `delay_idx`.
+    delay: Vec<f32>,
+    delay_idx: usize,
+    next_channel_idx: usize,
+    /// The peak of the frame currently being read.
+    frame_peak: f32,
+    /// The index of the frame currently being read.
+    frame_idx: u64,
+    /// Frame indexes and required gains where the gains are increasing so the
+    /// front is the smallest required gain of the frames being delayed.
+    required_gains: VecDeque<(u64, f32)>,
+    gain: f32,
+    release_coefficient: f32,
+    /// Number of frames to read until the last frame read that was not
+    /// silent has been output.
+    frames_until_silent: u64,
+}
+
+impl Limiter {
+    fn new(channel_count: u16, sample_rate: u32, look_ahead: Duration, release: Duration) -> Self {
+        let channel_count = channel_count.max(1) as usize;
+        let delay_frames = utils::duration_to_num_samples(look_ahead, 1, sample_rate).max(1);
+        let release_frames = release.as_secs_f32() * sample_rate as f32;
+        let release_coefficient = if release_frames > 0.0 {
+            1.0 - (-1.0 / release_frames).exp()
+        } else {
+            1.0
+        };
+        Limiter {
+            channel_count,
+            delay_frames,
+            delay: vec![0.0; delay_frames as usize * channel_count],
+            delay_idx: 0,
+            next_channel_idx: 0,
+            frame_peak: 0.0,
+            frame_idx: 0,
+            required_gains: VecDeque::with_capacity(delay_frames as usize + 1),
+            gain: 1.0,
+            release_coefficient,
+            frames_until_silent: 0,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.delay.fill(0.0);
+        self.delay_idx = 0;
+        self.next_channel_idx = 0;
+        self.frame_peak = 0.0;
+        self.frame_idx = 0;
+        self.required_gains.clear();
+        self.gain = 1.0;
+        self.frames_until_silent = 0;
+    }
+
+    fn process(&mut self, sample: f32) -> f32 {
+        let output = self.delay[self.delay_idx] * self.gain;
+        self.delay[self.delay_idx] = sample;
+        self.delay_idx += 1;
+        if self.delay_idx == self.delay.len() {
+            self.delay_idx = 0;
+        }
+        self.frame_peak = self.frame_peak.max(sample.abs());
+        self.next_channel_idx += 1;
+        if self.next_channel_idx == self.channel_count {
+            self.next_channel_idx = 0;
+            self.on_end_of_frame();
+        }
+        output
+    }
+
+    /// Update the gain for the next frame to be output.
+    fn on_end_of_frame(&mut self) {
+        let required_gain = if self.frame_peak > 1.0 {
+            1.0 / self.frame_peak
+        } else {
+            1.0
+        };
+        self.frames_until_silent = if self.frame_peak > 0.0 {
+            self.delay_frames
+        } else {
+            self.frames_until_silent.saturating_sub(1)
+        };
+        self.frame_peak = 0.0;
+        while self
+            .required_gains
+            .back()
+            .is_some_and(|(_, gain)| *gain >= required_gain)
+        {
+            self.required_gains.pop_back();
+        }
+        self.required_gains
+            .push_back((self.frame_idx, required_gain));
+
+        // The frame that will be output next.
+        let output_frame_idx = (self.frame_idx + 1).saturating_sub(self.delay_frames);
+        while self
+            .required_gains
+            .front()
+            .is_some_and(|(idx, _)| *idx < output_frame_idx)
+        {
+            self.required_gains.pop_front();
+        }
+        self.frame_idx += 1;
+
+        let (min_idx, target) = self.required_gains[0];
+        if target < self.gain {
+            let frames_until_peak = (min_idx - output_frame_idx) as f32;
+            self.gain -= (self.gain - target) / (frames_until_peak + 1.0);
+        } else {
+            self.gain = target.min(self.gain + (1.0 - self.gain) * self.release_coefficient);
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/master_stage.rs"]
+mod tests;
@@ -1,6 +1,6 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
-use crate::{NextSample, Sound};
+use crate::{sound::filled, sounds::wrappers::Seekable, utils, NextSample, Sound};
 
 /// A Sound that stores all samples on the heap.
 ///
@@ -14,6 +14,7 @@ pub struct MemorySound {
 
     next_sample: usize,
     should_loop: bool,
+    seeked: bool,
 }
 
 /// A [MetadataChanged][NextSample::MetadataChanged] was returned while reading
@@ -69,7 +70,7 @@ impl MemorySound {
                     if channel_idx != 0 {
                         let outputs_to_stay_in_sync = channel_count as usize - channel_idx;
                         // This should be rare so lets just output 0 for the filler samples.
-                        samples.extend(std::iter::repeat(0).take(outputs_to_stay_in_sync));
+                        samples.extend(std::iter::repeat_n(0, outputs_to_stay_in_sync));
                     }
                 }
                 crate::NextSample::Paused | crate::NextSample::Finished => break,
@@ -82,6 +83,7 @@ impl MemorySound {
             sample_rate,
             next_sample: 0,
             should_loop: false,
+            seeked: false,
         })
     }
 
@@ -100,6 +102,7 @@ impl MemorySound {
             sample_rate,
             next_sample: 0,
             should_loop: false,
+            seeked: false,
         }
     }
 
@@ -120,6 +123,10 @@ impl Sound for MemorySound {
     }
 
     fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        if self.seeked {
+            self.seeked = false;
+            return Ok(NextSample::MetadataChanged);
+        }
         if let Some(sample) = self.samples.get(self.next_sample) {
             self.next_sample += 1;
             Ok(NextSample::Sample(*sample))
@@ -131,7 +138,72 @@ impl Sound for MemorySound {
         }
     }
 
+    fn next_samples(&mut self, buffer: &mut [i16]) -> Result<(usize, NextSample), crate::Error> {
+        if self.seeked {
+            self.seeked = false;
+            return Ok((0, NextSample::MetadataChanged));
+        }
+        let mut num_written = 0;
+        while num_written < buffer.len() {
+            let remaining = &self.samples[self.next_sample..];
+            if remaining.is_empty() {
+                if self.should_loop && !self.samples.is_empty() {
+                    self.next_sample = 0;
+                    continue;
+                }
+                return Ok((num_written, NextSample::Finished));
+            }
+            let num_to_copy = remaining.len().min(buffer.len() - num_written);
+            buffer[num_written..num_written + num_to_copy]
+                .copy_from_slice(&remaining[..num_to_copy]);
+            num_written += num_to_copy;
+            self.next_sample += num_to_copy;
+        }
+        Ok(filled(buffer))
+    }
+
     fn on_start_of_batch(&mut self) {}
+
+    fn as_seekable(&mut self) -> Option<&mut dyn Seekable> {
+        Some(self)
+    }
+
+    fn position(&self) -> Option<Duration> {
+        Some(utils::num_samples_to_duration(
+            self.next_sample as u64,
+            self.channel_count,
+            self.sample_rate,
+        ))
+    }
+
+    /// Returns None if looping.
+    fn duration(&self) -> Option<Duration> {
+        if self.should_loop {
+            return None;
+        }
+        Some(utils::num_samples_to_duration(
+            self.samples.len() as u64,
+            self.channel_count,
+            self.sample_rate,
+        ))
+    }
+}
+
+impl Seekable for MemorySound {
+    /// If looping, positions after the end wrap around to the start.
+    fn seek(&mut self, position: Duration) -> Result<(), crate::Error> {
+        let frame = utils::duration_to_num_samples(position, 1, self.sample_rate);
+        let sample = usize::try_from(frame)
+            .unwrap_or(usize::MAX)
+            .saturating_mul(self.channel_count as usize);
+        self.next_sample = if self.should_loop && !self.samples.is_empty() {
+            sample % self.samples.len()
+        } else {
+            sample.min(self.samples.len())
+        };
+        self.seeked = true;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -1,11 +1,18 @@
 use crate::Sound;
-use std::{fs::File, io::BufReader};
+use std::fs::File;
+#[cfg(feature = "symphonia")]
+use std::io::SeekFrom;
+use std::io::{BufReader, Cursor, Read, Seek};
+#[cfg(feature = "symphonia")]
+use std::sync::{Mutex, PoisonError};
+#[cfg(feature = "symphonia")]
+use symphonia::core::io::MediaSource;
 
 /// Create a Sound that reads from a file with the correct decoder based on the
 /// file extension.
 ///
-/// If the file type is not able to be decoded than an [ErrorKind::Unsupported]
-/// is returned.
+/// If the file type is not able to be decoded than an
+/// [ErrorKind::Unsupported][std::io::ErrorKind::Unsupported] is returned.
 ///
 /// Uses a BufReader internally with the default capacity.
 ///
@@ -29,6 +36,46 @@ pub fn open_file_with_buffer_capacity<P: AsRef<std::path::Path>>(
     open_file_with_reader(path.as_ref(), reader)
 }
 
+/// Create a Sound that decodes `bytes` held in memory, such as a file embedded
+/// with `include_bytes!` or shared in an `Arc<[u8]>`.
+///
+/// `hint` is the file extension of the data (e.g. `"mp3"`) and selects the
+/// decoder the same way as [open_file]. If it is None the format is detected
+/// from the data when the `symphonia` feature is enabled.
+///
+/// If the format is not able to be decoded than an
+/// [ErrorKind::Unsupported][std::io::ErrorKind::Unsupported] is returned.
+pub fn open_bytes<B>(bytes: B, hint: Option<&str>) -> Result<Box<dyn Sound>, crate::Error>
+where
+    B: AsRef<[u8]> + Send + Sync + 'static,
+{
+    open_with_reader(
+        Cursor::new(bytes),
+        hint,
+        #[cfg(feature = "symphonia")]
+        |reader| Box::new(reader),
+    )
+}
+
+/// Create a Sound that decodes data read from `reader`, such as an entry of
+/// an archive.
+///
+/// `hint` is used the same as in [open_bytes]. Reads are not buffered by most
+/// decoders so consider wrapping unbuffered readers in a BufReader.
+///
+/// As with [open_file], reading could block the renderer.
+pub fn open_reader<R>(reader: R, hint: Option<&str>) -> Result<Box<dyn Sound>, crate::Error>
+where
+    R: Read + Seek + Send + 'static,
+{
+    open_with_reader(
+        reader,
+        hint,
+        #[cfg(feature = "symphonia")]
+        |reader| Box::new(SyncReader(Mutex::new(reader))),
+    )
+}
+
 fn open_file_with_reader(
     path: &std::path::Path,
     reader: BufReader<File>,
@@ -37,29 +84,87 @@ fn open_file_with_reader(
         .extension()
         .unwrap_or_default()
         .to_str()
-        .unwrap_or_default()
-        .to_lowercase();
-    let decoder: Box<dyn Sound> = match extension.as_ref() {
+        .unwrap_or_default();
+    open_with_reader(
+        reader,
+        Some(extension),
+        // Symphonia does its own buffering.
+        #[cfg(feature = "symphonia")]
+        |reader| Box::new(reader.into_inner()),
+    )
+}
+
+/// Select the decoder for `reader` based on the file extension `hint`.
+///
+/// `into_source` converts the reader to what symphonia reads from.
+fn open_with_reader<R>(
+    reader: R,
+    hint: Option<&str>,
+    #[cfg(feature = "symphonia")] into_source: impl FnOnce(R) -> Box<dyn MediaSource>,
+) -> Result<Box<dyn Sound>, crate::Error>
+where
+    R: Read + Seek + Send + 'static,
+{
+    let extension = hint.map(str::to_lowercase);
+    let decoder: Box<dyn Sound> = match extension.as_deref() {
         #[cfg(feature = "rmp3-mp3")]
-        "mp3" => Box::new(super::decoders::Mp3Decoder::new(reader)),
+        Some("mp3") => Box::new(super::decoders::Mp3Decoder::new(reader)),
         #[cfg(feature = "qoa")]
-        "qoa" => Box::new(super::decoders::QoaDecoder::new(reader)?),
+        Some("qoa") => Box::new(super::decoders::QoaDecoder::new_seekable(reader)?),
         #[cfg(feature = "hound-wav")]
-        "wav" => Box::new(super::decoders::WavDecoder::new(reader)?),
-        "_SILENCE_NEVER_MATCH_" => {
-            println!(
-                "Included to satisfy unused warnings when all features are off: {:?}",
-                reader
-            );
+        Some("wav") => Box::new(super::decoders::WavDecoder::new_seekable(reader)?),
+        Some("_SILENCE_NEVER_MATCH_") => {
+            // Included to satisfy unused warnings when all features are off.
+            drop(reader);
             Box::new(crate::sounds::Silence::new(1, 1000))
         }
         #[cfg(feature = "symphonia")]
         _ => Box::new(super::decoders::SymphoniaDecoder::new(
-            Box::new(reader.into_inner()),
-            Some(&extension),
+            into_source(reader),
+            extension.as_deref(),
         )?),
         #[cfg(not(feature = "symphonia"))]
         _ => return Err(std::io::Error::from(std::io::ErrorKind::Unsupported).into()),
     };
     Ok(decoder)
 }
+
+/// Lets symphonia, which requires Sync, read from a reader that is only Send.
+/// The Mutex is never locked since reading takes `&mut self`.
+#[cfg(feature = "symphonia")]
+struct SyncReader<R>(Mutex<R>);
+
+#[cfg(feature = "symphonia")]
+impl<R: Read> Read for SyncReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.0
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner)
+            .read(buf)
+    }
+}
+
+#[cfg(feature = "symphonia")]
+impl<R: Seek> Seek for SyncReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.0
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner)
+            .seek(pos)
+    }
+}
+
+#[cfg(feature = "symphonia")]
+impl<R: Read + Seek + Send> MediaSource for SyncReader<R> {
+    fn is_seekable(&self) -> bool {
+        true
+    }
+
+    fn byte_len(&self) -> Option<u64> {
+        None
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/open_file.rs"]
+mod tests;
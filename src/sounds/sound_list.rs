@@ -1,11 +1,16 @@
+use std::time::Duration;
+
 use crate::sound::NextSample;
-use crate::sounds::wrappers::{AddSound, ClearSounds};
-use crate::Sound;
+use crate::sounds::wrappers::{
+    AddSound, ChannelCountConverter, ClearSounds, SampleRateConverter, Seekable,
+};
+use crate::{utils, Sound};
 
 /// Play Sounds sequentially one after the other.
 ///
 /// Only after a Sound has returned `NextSample::Finished` will the next Sound
-/// start playing.
+/// start playing unless a crossfade is set with
+/// [set_crossfade][SoundList::set_crossfade].
 ///
 /// If an Error is returned from a Sound it is dropped and the error is
 /// propagated to the caller. Calling next_sound again would continue
@@ -13,6 +18,21 @@ use crate::Sound;
 pub struct SoundList {
     sounds: Vec<Box<dyn Sound>>,
     was_empty: bool,
+    crossfade: Option<Duration>,
+    /// Set while the first two sounds are being crossfaded.
+    crossfading: Option<Crossfade>,
+    next_channel_idx: u16,
+}
+
+struct Crossfade {
+    num_frames: u64,
+    frames_done: u64,
+    /// Set when the incoming sound returned Paused. It is silent for the rest
+    /// of the frame so its channels stay in order.
+    incoming_paused: bool,
+    /// An error from the incoming sound. It is silent for the rest of the
+    /// frame and the error is returned at the start of the next frame.
+    incoming_error: Option<crate::Error>,
 }
 
 impl SoundList {
@@ -21,9 +41,25 @@ impl SoundList {
         SoundList {
             sounds: Vec::new(),
             was_empty: false,
+            crossfade: None,
+            crossfading: None,
+            next_channel_idx: 0,
         }
     }
 
+    /// Overlap consecutive sounds by `crossfade`, fading out the end of each
+    /// sound while fading in the next one. `None` (the default) switches to the
+    /// next sound once the current one has `Finished`.
+    ///
+    /// The crossfade starts once the remaining time of the current sound
+    /// ([duration][Sound::duration] minus [position][Sound::position]) is
+    /// shorter than `crossfade` so sounds that do not report both are not
+    /// crossfaded. The next sound is converted to the channel count and
+    /// sample rate of the current sound.
+    pub fn set_crossfade(&mut self, crossfade: Option<Duration>) {
+        self.crossfade = crossfade;
+    }
+
     /// Add a Sound to be played after any existing sounds have `Finished`.
     pub fn add(&mut self, sound: Box<dyn Sound>) {
         if self.sounds.is_empty() {
@@ -35,13 +71,20 @@ impl SoundList {
     /// Stop all sounds including the currently playing one.
     pub fn clear(&mut self) {
         self.sounds.clear();
+        self.crossfading = None;
     }
 }
 
 impl From<Vec<Box<dyn Sound>>> for SoundList {
     fn from(sounds: Vec<Box<dyn Sound>>) -> Self {
         let was_empty = sounds.is_empty();
-        SoundList { sounds, was_empty }
+        SoundList {
+            sounds,
+            was_empty,
+            crossfade: None,
+            crossfading: None,
+            next_channel_idx: 0,
+        }
     }
 }
 
@@ -76,18 +119,69 @@ impl Sound for SoundList {
         }
     }
 
+    fn as_seekable(&mut self) -> Option<&mut dyn Seekable> {
+        Some(self)
+    }
+
+    /// The position within the currently playing sound.
+    fn position(&self) -> Option<Duration> {
+        self.sounds.first().and_then(|s| s.position())
+    }
+
+    /// The duration of the currently playing sound.
+    ///
+    /// Like [seek][Seekable::seek] and [position][Sound::position] this only
+    /// considers the current sound so a scrub bar can be shown per sound.
+    fn duration(&self) -> Option<Duration> {
+        self.sounds.first().and_then(|s| s.duration())
+    }
+
     fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
-        let Some(next_sound) = self.sounds.first_mut() else {
+        if self.sounds.is_empty() {
             return Ok(NextSample::Finished);
-        };
+        }
         if self.was_empty {
             self.was_empty = false;
             return Ok(NextSample::MetadataChanged);
         }
-        let next_sample = match next_sound.next_sample() {
+        if self.next_channel_idx == 0 && self.crossfading.is_none() {
+            self.maybe_start_crossfade();
+        }
+        let next_sample = if self.crossfading.is_some() {
+            self.next_crossfaded_sample()
+        } else {
+            self.next_sample_from_first()
+        };
+        let next_sample = match next_sample {
+            Ok(next_sample) => next_sample,
+            Err(e) => {
+                // The sound that returned the error is dropped so the next
+                // sample is from the start of a frame.
+                self.next_channel_idx = 0;
+                return Err(e);
+            }
+        };
+        match next_sample {
+            NextSample::Sample(_) => {
+                self.next_channel_idx += 1;
+                if self.next_channel_idx >= self.channel_count() {
+                    self.next_channel_idx = 0;
+                }
+            }
+            NextSample::MetadataChanged | NextSample::Paused | NextSample::Finished => {
+                self.next_channel_idx = 0;
+            }
+        }
+        Ok(next_sample)
+    }
+}
+
+impl SoundList {
+    fn next_sample_from_first(&mut self) -> Result<NextSample, crate::Error> {
+        let next_sample = match self.sounds[0].next_sample() {
             Ok(s) => s,
             Err(e) => {
-                self.sounds.remove(0);
+                self.remove_first();
                 return Err(e);
             }
         };
@@ -95,7 +189,7 @@ impl Sound for SoundList {
         let ret = match next_sample {
             NextSample::Sample(_) | NextSample::MetadataChanged | NextSample::Paused => next_sample,
             NextSample::Finished => {
-                self.sounds.remove(0);
+                self.remove_first();
                 if self.sounds.is_empty() {
                     NextSample::Finished
                 } else {
@@ -107,6 +201,168 @@ impl Sound for SoundList {
         };
         Ok(ret)
     }
+
+    fn remove_first(&mut self) {
+        self.sounds.remove(0);
+        self.crossfading = None;
+    }
+
+    /// Start crossfading into the second sound if the first sound is about to
+    /// finish.
+    fn maybe_start_crossfade(&mut self) {
+        let Some(crossfade) = self.crossfade else {
+            return;
+        };
+        if self.sounds.len() < 2 {
+            return;
+        }
+        let current = &self.sounds[0];
+        let (Some(position), Some(duration)) = (current.position(), current.duration()) else {
+            return;
+        };
+        let remaining = duration.saturating_sub(position);
+        if remaining > crossfade {
+            return;
+        }
+        let channel_count = current.channel_count();
+        let sample_rate = current.sample_rate();
+        let num_frames = utils::duration_to_num_samples(remaining, 1, sample_rate);
+        if num_frames == 0 {
+            return;
+        }
+        // Mix the next sound in the format of the current one. It keeps
+        // that format after the current sound finishes.
+        let next = self.sounds.remove(1);
+        let next = if next.channel_count() == channel_count && next.sample_rate() == sample_rate {
+            next
+        } else {
+            Box::new(SampleRateConverter::new(
+                ChannelCountConverter::new(next, channel_count),
+                sample_rate,
+            ))
+        };
+        self.sounds.insert(1, next);
+        self.crossfading = Some(Crossfade {
+            num_frames,
+            frames_done: 0,
+            incoming_paused: false,
+            incoming_error: None,
+        });
+    }
+
+    /// The next sample of the sound being faded in or 0 if it has none.
+    fn next_incoming_sample(&mut self) -> Result<i16, crate::Error> {
+        let crossfade = self
+            .crossfading
+            .as_mut()
+            .expect("only called while crossfading");
+        if self.next_channel_idx == 0 {
+            crossfade.incoming_paused = false;
+        } else if crossfade.incoming_paused {
+            return Ok(0);
+        }
+        loop {
+            match self.sounds[1].next_sample() {
+                Ok(NextSample::Sample(s)) => return Ok(s),
+                // It is in the format of the current sound so the next sample
+                // is still for this channel.
+                Ok(NextSample::MetadataChanged) => {}
+                Ok(NextSample::Paused) => {
+                    crossfade.incoming_paused = true;
+                    return Ok(0);
+                }
+                Ok(NextSample::Finished) => {
+                    self.sounds.remove(1);
+                    self.crossfading = None;
+                    return Ok(0);
+                }
+                Err(e) => {
+                    // Finish the frame with the current sound so the
+                    // channels stay in order.
+                    crossfade.incoming_paused = true;
+                    crossfade.incoming_error = Some(e);
+                    return Ok(0);
+                }
+            }
+        }
+    }
+
+    /// Drop the sound being faded in and stop crossfading if it returned an
+    /// error and return the error.
+    fn take_incoming_error(&mut self) -> Option<crate::Error> {
+        let error = self.crossfading.as_mut()?.incoming_error.take()?;
+        self.sounds.remove(1);
+        self.crossfading = None;
+        Some(error)
+    }
+
+    fn next_crossfaded_sample(&mut self) -> Result<NextSample, crate::Error> {
+        let crossfade = self
+            .crossfading
+            .as_mut()
+            .expect("only called while crossfading");
+        if self.next_channel_idx == 0 && crossfade.incoming_error.is_some() {
+            return Err(self
+                .take_incoming_error()
+                .expect("checked that there is an error"));
+        }
+        if crossfade.frames_done >= crossfade.num_frames && self.next_channel_idx == 0 {
+            // The current sound is silent now even if it has not finished.
+            self.remove_first();
+            return self.next_sample_from_first();
+        }
+        // Equal power so the loudness stays constant for uncorrelated sounds.
+        let progress = crossfade.frames_done as f32 / crossfade.num_frames as f32;
+        let angle = progress * std::f32::consts::FRAC_PI_2;
+        let (out_gain, in_gain) = (angle.cos(), angle.sin());
+
+        let outgoing = match self.sounds[0].next_sample() {
+            Ok(NextSample::Sample(s)) => s,
+            Ok(NextSample::Paused) => return Ok(NextSample::Paused),
+            Ok(NextSample::MetadataChanged | NextSample::Finished) => {
+                if let Some(e) = self.take_incoming_error() {
+                    self.remove_first();
+                    return Err(e);
+                }
+                // Continue with the next sound which is already in the format
+                // of the current one.
+                self.remove_first();
+                return self.next_sample_from_first();
+            }
+            Err(e) => {
+                // Only one error can be returned.
+                self.take_incoming_error();
+                self.remove_first();
+                return Err(e);
+            }
+        };
+        let incoming = self.next_incoming_sample()?;
+        if self.next_channel_idx + 1 >= self.sounds[0].channel_count() {
+            if let Some(crossfade) = &mut self.crossfading {
+                crossfade.frames_done += 1;
+            }
+        }
+        let mixed = outgoing as f32 * out_gain + incoming as f32 * in_gain;
+        // `as` saturates at the bounds of i16.
+        Ok(NextSample::Sample(mixed.round() as i16))
+    }
+}
+
+impl Seekable for SoundList {
+    /// Seek within the currently playing sound.
+    ///
+    /// Sounds are dropped once they have finished so `position` is relative to
+    /// the start of the current sound and not the start of the list. Does
+    /// nothing if the list is empty.
+    fn seek(&mut self, position: Duration) -> Result<(), crate::Error> {
+        // Seeking away from the end cancels a crossfade. The next sound keeps
+        // the position it was faded in to.
+        self.crossfading = None;
+        match self.sounds.first_mut() {
+            Some(sound) => sound.seek(position),
+            None => Ok(()),
+        }
+    }
 }
 
 impl AddSound for SoundList {
@@ -132,6 +388,7 @@ impl std::fmt::Debug for SoundList {
         f.debug_struct("SoundList")
             .field("sounds", &format!("{} sounds", self.sounds.len()))
             .field("was_empty", &self.was_empty)
+            .field("crossfade", &self.crossfade)
             .finish()
     }
 }
@@ -1,8 +1,107 @@
-use super::wrappers::{AddSound, ChannelCountConverter, ClearSounds, SampleRateConverter};
-use crate::sound::NextSample;
+use super::master_stage::MasterStageProcessor;
+use super::wrappers::{
+    AddSound, ChannelCountConverter, ClearSounds, ResampleQuality, SampleRateConverter,
+};
+use super::MasterStage;
+use crate::manager::StartTime;
+use crate::sound::{filled, filled_f32, NextSample};
 use crate::Sound;
+use crossbeam_queue::ArrayQueue;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock, Weak};
+use std::thread::Thread;
+use std::time::Duration;
 
-type MixedSound = SampleRateConverter<ChannelCountConverter<Box<dyn Sound>>>;
+type ConvertedSound = SampleRateConverter<ChannelCountConverter<Box<dyn Sound>>>;
+
+/// A sound being mixed.
+///
+/// The mixers of a [Manager][crate::manager::Manager] receive sounds already
+/// converted so the audio thread does not allocate to convert them. The
+/// conversion is boxed so it can be passed through a command queue cheaply.
+pub(crate) struct MixedSound {
+    sound: Box<ConvertedSound>,
+    /// Groups are kept when the mixer is cleared.
+    is_group: bool,
+}
+
+impl MixedSound {
+    /// Convert `sound` to the output format `format` with `quality` to be
+    /// added to a mixer later.
+    pub(crate) fn new(
+        sound: Box<dyn Sound>,
+        format: &SharedOutputFormat,
+        quality: ResampleQuality,
+        is_group: bool,
+    ) -> Self {
+        let (channel_count, sample_rate) = format.load();
+        Self::convert(sound, channel_count, sample_rate, quality, is_group)
+    }
+
+    fn convert(
+        sound: Box<dyn Sound>,
+        channel_count: u16,
+        sample_rate: u32,
+        quality: ResampleQuality,
+        is_group: bool,
+    ) -> Self {
+        MixedSound {
+            sound: Box::new(SampleRateConverter::with_quality(
+                ChannelCountConverter::new(sound, channel_count),
+                sample_rate,
+                quality,
+            )),
+            is_group,
+        }
+    }
+
+    /// The inner sound and the quality it was converted with.
+    fn into_inner(self) -> (Box<dyn Sound>, ResampleQuality) {
+        let quality = self.sound.quality();
+        (self.sound.into_inner().into_inner(), quality)
+    }
+}
+
+impl Deref for MixedSound {
+    type Target = ConvertedSound;
+
+    fn deref(&self) -> &Self::Target {
+        &self.sound
+    }
+}
+
+impl DerefMut for MixedSound {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.sound
+    }
+}
+
+/// A command sent to the mixer of a [Manager][crate::manager::Manager] or a
+/// [Group][crate::manager::Group] through its
+/// [Controller][super::wrappers::Controller].
+pub(crate) enum MixerCommand {
+    /// Add a converted sound.
+    Add(MixedSound),
+    /// Add a converted sound that starts exactly at a later time.
+    AddAt(MixedSound, StartTime),
+    /// Replace the master stage with a processor created off the audio
+    /// thread.
+    ReplaceMasterStage(Box<MasterStageProcessor>),
+    /// Remove all sounds except groups.
+    Clear,
+}
+
+/// A sound waiting to be mixed from an exact output frame.
+struct ScheduledSound {
+    start_frame: u64,
+    sound: MixedSound,
+}
+
+/// The number of samples mixed at once by `next_samples` and
+/// `next_samples_f32`. Larger buffers are mixed in parts so the scratch
+/// buffers are allocated once when the mixer is created.
+const SCRATCH_LEN: usize = 1024;
 
 /// Mix multiple sounds together to be played simultaneously.
 ///
@@ -12,13 +111,76 @@ type MixedSound = SampleRateConverter<ChannelCountConverter<Box<dyn Sound>>>;
 ///
 /// If a Sound returns an Error from next_sample, the error is logged and the
 /// Sound is dropped but other sounds keep playing.
+///
+/// Sounds are summed with more range than a sample and then passed through a
+/// [MasterStage] which by default clamps the sum. See
+/// [set_master_stage][SoundMixer::set_master_stage].
+///
+/// Sounds can be scheduled to start at an exact output frame with
+/// [add_at][SoundMixer::add_at].
+///
+/// The mixer itself does not allocate while mixing. Sounds that finish are
+/// dropped while mixing except in the mixers of a
+/// [Manager][crate::manager::Manager] which hand them to a separate thread to
+/// be dropped.
 pub struct SoundMixer {
     sounds: Vec<MixedSound>,
     paused_sounds: Vec<MixedSound>,
+    /// Sounds waiting to start, ordered so the next to start is last.
+    scheduled: Vec<ScheduledSound>,
+    /// The number of complete frames output.
+    frame_clock: u64,
+    /// Where frame_clock is stored at the start of each batch if set.
+    published_frame_clock: Option<Arc<AtomicU64>>,
     output_channel_count: u16,
     output_sample_rate: u32,
     metadata_changed: bool,
     next_output_channel_idx: u16,
+    resample_quality: ResampleQuality,
+    /// Scratch space used by next_samples to read each sound into. All
+    /// scratch buffers are SCRATCH_LEN long.
+    sound_buffer: Vec<i16>,
+    /// Scratch space used by next_samples_f32 to read each sound into.
+    sound_buffer_f32: Vec<f32>,
+    /// Scratch space used by next_samples to sum the sounds into.
+    mix_buffer: Vec<i32>,
+    /// Boxed so a replacement can be created off the audio thread.
+    master: Box<MasterStageProcessor>,
+    format_sharing: FormatSharing,
+    /// Where removed sounds are sent to be dropped if set.
+    collector: Option<Arc<SoundCollector>>,
+}
+
+/// An output channel count and sample rate that can be read from any thread.
+pub(crate) struct SharedOutputFormat(AtomicU64);
+
+impl SharedOutputFormat {
+    pub(crate) fn new(channel_count: u16, sample_rate: u32) -> Self {
+        SharedOutputFormat(AtomicU64::new(Self::pack(channel_count, sample_rate)))
+    }
+
+    pub(crate) fn load(&self) -> (u16, u32) {
+        let packed = self.0.load(Ordering::Relaxed);
+        ((packed >> 32) as u16, packed as u32)
+    }
+
+    fn store(&self, channel_count: u16, sample_rate: u32) {
+        self.0
+            .store(Self::pack(channel_count, sample_rate), Ordering::Relaxed);
+    }
+
+    fn pack(channel_count: u16, sample_rate: u32) -> u64 {
+        ((channel_count as u64) << 32) | sample_rate as u64
+    }
+}
+
+/// How a mixer shares its output format with the mixers of groups.
+enum FormatSharing {
+    None,
+    /// Store the output format whenever it is set.
+    Publish(Arc<SharedOutputFormat>),
+    /// Change the output format at the start of a batch to match.
+    Follow(Arc<SharedOutputFormat>),
 }
 
 impl SoundMixer {
@@ -28,13 +190,156 @@ impl SoundMixer {
         SoundMixer {
             sounds: Vec::new(),
             paused_sounds: Vec::new(),
+            scheduled: Vec::new(),
+            frame_clock: 0,
+            published_frame_clock: None,
             output_channel_count,
             output_sample_rate,
             metadata_changed: false,
             next_output_channel_idx: 0,
+            resample_quality: ResampleQuality::default(),
+            sound_buffer: vec![0; SCRATCH_LEN],
+            sound_buffer_f32: vec![0.0; SCRATCH_LEN],
+            mix_buffer: vec![0; SCRATCH_LEN],
+            master: Box::new(MasterStageProcessor::new(
+                MasterStage::default(),
+                output_channel_count,
+                output_sample_rate,
+            )),
+            format_sharing: FormatSharing::None,
+            collector: None,
+        }
+    }
+
+    /// Create a mixer whose output format follows `format`. Used for the
+    /// mixers of groups so they match the mixer they are played in and no
+    /// conversion is needed.
+    pub(crate) fn new_following(format: Arc<SharedOutputFormat>) -> Self {
+        let (channel_count, sample_rate) = format.load();
+        let mut mixer = SoundMixer::new(channel_count, sample_rate);
+        mixer.format_sharing = FormatSharing::Follow(format);
+        mixer
+    }
+
+    /// Publish the output format of this mixer to `format` from now on.
+    pub(crate) fn publish_output_format(&mut self, format: Arc<SharedOutputFormat>) {
+        format.store(self.output_channel_count, self.output_sample_rate);
+        self.format_sharing = FormatSharing::Publish(format);
+    }
+
+    /// Send removed sounds to `collector` to be dropped from now on instead of
+    /// dropping them while mixing.
+    pub(crate) fn set_collector(&mut self, collector: Arc<SoundCollector>) {
+        self.collector = Some(collector);
+    }
+
+    /// Store the [current_frame][SoundMixer::current_frame] in `clock` at
+    /// the start of each batch from now on.
+    pub(crate) fn publish_frame_clock(&mut self, clock: Arc<AtomicU64>) {
+        clock.store(self.frame_clock, Ordering::Relaxed);
+        self.published_frame_clock = Some(clock);
+    }
+
+    /// The number of complete frames output so far. Sounds added with
+    /// [add_at][SoundMixer::add_at] are started relative to this.
+    pub fn current_frame(&self) -> u64 {
+        self.frame_clock
+    }
+
+    /// Add a sound that starts playing exactly when
+    /// [current_frame][SoundMixer::current_frame] reaches `start_frame`.
+    ///
+    /// Until then it is not played and the mixer outputs silence instead of
+    /// finishing. If `start_frame` has already passed the sound starts at the
+    /// next frame. If the output sample rate changes before the sound starts,
+    /// the time remaining until it starts is kept.
+    pub fn add_at(&mut self, sound: Box<dyn Sound>, start_frame: u64) {
+        let sound = self.convert(sound, self.resample_quality, false);
+        self.add_mixed_at(sound, start_frame);
+    }
+
+    /// Same as [add_at][SoundMixer::add_at] for a sound that is already
+    /// converted.
+    pub(crate) fn add_mixed_at(&mut self, sound: MixedSound, start_frame: u64) {
+        let sound = self.conform(sound);
+        self.schedule(ScheduledSound { start_frame, sound });
+    }
+
+    /// Add a sound that is already converted.
+    pub(crate) fn add_mixed(&mut self, sound: MixedSound) {
+        let sound = self.conform(sound);
+        self.sounds.push(sound);
+        self.reserve_moves();
+    }
+
+    /// Run a command sent by a Manager or Group.
+    pub(crate) fn run_command(&mut self, command: MixerCommand) {
+        match command {
+            MixerCommand::Add(sound) => self.add_mixed(sound),
+            MixerCommand::AddAt(sound, start) => {
+                let start_frame = match start {
+                    StartTime::Frame(frame) => frame,
+                    StartTime::After(duration) => {
+                        self.frame_clock
+                            .saturating_add(crate::utils::duration_to_num_samples(
+                                duration,
+                                1,
+                                self.output_sample_rate,
+                            ))
+                    }
+                };
+                self.add_mixed_at(sound, start_frame)
+            }
+            MixerCommand::ReplaceMasterStage(master) => self.replace_master_stage(master),
+            MixerCommand::Clear => self.clear(),
         }
     }
 
+    /// Make room for `capacity` sounds so that adding, scheduling and moving
+    /// up to that many sounds does not allocate.
+    pub(crate) fn reserve(&mut self, capacity: usize) {
+        self.sounds.reserve(capacity);
+        self.paused_sounds.reserve(capacity);
+        self.scheduled.reserve(capacity);
+    }
+
+    /// Set the processing applied to the sum of all sounds.
+    ///
+    /// Any state (e.g. samples delayed by a limiter) is discarded. Must only be
+    /// called when the next sample is for the first channel in the frame.
+    pub fn set_master_stage(&mut self, stage: MasterStage) {
+        self.replace_master_stage(Box::new(MasterStageProcessor::new(
+            stage,
+            self.output_channel_count,
+            self.output_sample_rate,
+        )));
+    }
+
+    /// Same as [set_master_stage][SoundMixer::set_master_stage] with a
+    /// processor created off the audio thread. The old processor is dropped
+    /// on the thread of the collector if set.
+    pub(crate) fn replace_master_stage(&mut self, master: Box<MasterStageProcessor>) {
+        let master = if master.is_for(self.output_channel_count, self.output_sample_rate) {
+            master
+        } else {
+            // The output format changed since the processor was created.
+            let stage = master.stage();
+            retire(&self.collector, master);
+            Box::new(MasterStageProcessor::new(
+                stage,
+                self.output_channel_count,
+                self.output_sample_rate,
+            ))
+        };
+        let old = std::mem::replace(&mut self.master, master);
+        retire(&self.collector, old);
+    }
+
+    /// The processing applied to the sum of all sounds.
+    pub fn master_stage(&self) -> MasterStage {
+        self.master.stage()
+    }
+
     /// Set the output channel count and sample rate.
     /// Added sounds will be converted to the output values. Must only be called
     /// when the next sample is for the first channel in the frame.
@@ -45,21 +350,147 @@ impl SoundMixer {
     ) {
         self.metadata_changed = true;
 
+        let old_sample_rate = self.output_sample_rate;
         self.output_channel_count = output_channel_count;
         self.output_sample_rate = output_sample_rate;
+        self.set_master_stage(self.master.stage());
+        if let FormatSharing::Publish(format) = &self.format_sharing {
+            format.store(output_channel_count, output_sample_rate);
+        }
 
         // Now re-wrap all the sounds with the new values.
 
         // Move all sounds to a single vec for simplicity
         self.sounds.append(&mut self.paused_sounds);
 
-        let mut old = Vec::new();
+        // Keep the capacity reserved for the sounds.
+        let mut old = Vec::with_capacity(self.sounds.capacity());
         std::mem::swap(&mut self.sounds, &mut old);
         for mixed_sound in old {
-            let inner = mixed_sound.into_inner().into_inner();
-            // add will rewrap the sound
-            self.add(inner);
+            let is_group = mixed_sound.is_group;
+            let (inner, quality) = mixed_sound.into_inner();
+            self.push(inner, quality, is_group);
+        }
+
+        let mut old = Vec::with_capacity(self.scheduled.capacity());
+        std::mem::swap(&mut self.scheduled, &mut old);
+        for scheduled in old {
+            let frames_until_start = scheduled.start_frame.saturating_sub(self.frame_clock) as u128
+                * output_sample_rate as u128
+                / old_sample_rate.max(1) as u128;
+            let (inner, quality) = scheduled.sound.into_inner();
+            self.schedule(ScheduledSound {
+                start_frame: self
+                    .frame_clock
+                    .saturating_add(u64::try_from(frames_until_start).unwrap_or(u64::MAX)),
+                sound: self.convert(inner, quality, false),
+            });
+        }
+    }
+
+    /// Set the [ResampleQuality] used for sounds added with
+    /// [add][AddSound::add] from now on. Defaults to
+    /// [ResampleQuality::Linear].
+    pub fn set_resample_quality(&mut self, quality: ResampleQuality) {
+        self.resample_quality = quality;
+    }
+
+    /// Add a sound that is converted to the output sample rate with `quality`
+    /// if its sample rate differs.
+    pub fn add_with_resample_quality(&mut self, sound: Box<dyn Sound>, quality: ResampleQuality) {
+        self.push(sound, quality, false);
+    }
+
+    fn push(&mut self, sound: Box<dyn Sound>, quality: ResampleQuality, is_group: bool) {
+        let sound = self.convert(sound, quality, is_group);
+        self.add_mixed(sound);
+    }
+
+    fn convert(
+        &self,
+        sound: Box<dyn Sound>,
+        quality: ResampleQuality,
+        is_group: bool,
+    ) -> MixedSound {
+        MixedSound::convert(
+            sound,
+            self.output_channel_count,
+            self.output_sample_rate,
+            quality,
+            is_group,
+        )
+    }
+
+    /// Convert `sound` again if it was converted for a different output
+    /// format, such as when the format changed after it was sent to this
+    /// mixer.
+    fn conform(&mut self, sound: MixedSound) -> MixedSound {
+        if (sound.channel_count(), sound.sample_rate())
+            == (self.output_channel_count, self.output_sample_rate)
+        {
+            return sound;
         }
+        let is_group = sound.is_group;
+        let (inner, quality) = sound.into_inner();
+        allow_format_change(|| self.convert(inner, quality, is_group))
+    }
+
+    fn schedule(&mut self, scheduled: ScheduledSound) {
+        let idx = self
+            .scheduled
+            .partition_point(|s| s.start_frame > scheduled.start_frame);
+        self.scheduled.insert(idx, scheduled);
+        self.reserve_moves();
+    }
+
+    /// Make sure any sound can be moved between `scheduled`, `sounds` and
+    /// `paused_sounds` while mixing without the Vecs growing.
+    fn reserve_moves(&mut self) {
+        let total = self.sounds.len() + self.paused_sounds.len() + self.scheduled.len();
+        self.sounds.reserve(total - self.sounds.len());
+        self.paused_sounds.reserve(total - self.paused_sounds.len());
+    }
+
+    /// Remove the sound at `idx` from the sounds being mixed and either keep
+    /// it until the next batch if `paused` or drop it.
+    fn remove(&mut self, idx: usize, paused: bool) {
+        let sound = self.sounds.swap_remove(idx);
+        if paused {
+            self.paused_sounds.push(sound);
+        } else {
+            retire(&self.collector, sound);
+        }
+    }
+
+    /// Move scheduled sounds that are due into the sounds being mixed. Must
+    /// only be called when the next sample is for the first channel in the
+    /// frame.
+    fn start_due_sounds(&mut self) {
+        let clock = self.frame_clock;
+        while let Some(scheduled) = self.scheduled.pop_if(|s| s.start_frame <= clock) {
+            self.sounds.push(scheduled.sound);
+        }
+    }
+
+    /// The number of samples that can be mixed before the next scheduled
+    /// sound starts, if any are scheduled.
+    fn samples_until_next_start(&self) -> Option<usize> {
+        let next = self.scheduled.last()?;
+        let channel_count = self.output_channel_count as u64;
+        // A sound can only start on the first channel of a frame.
+        let min_frames = if self.next_output_channel_idx == 0 {
+            0
+        } else {
+            1
+        };
+        let frames = next
+            .start_frame
+            .saturating_sub(self.frame_clock)
+            .max(min_frames);
+        let samples = frames
+            .saturating_mul(channel_count)
+            .saturating_sub(self.next_output_channel_idx as u64);
+        Some(usize::try_from(samples).unwrap_or(usize::MAX))
     }
 }
 
@@ -73,6 +504,20 @@ impl Sound for SoundMixer {
     }
 
     fn on_start_of_batch(&mut self) {
+        if let FormatSharing::Follow(format) = &self.format_sharing {
+            let (channel_count, sample_rate) = format.load();
+            if (channel_count, sample_rate) != (self.output_channel_count, self.output_sample_rate)
+            {
+                allow_format_change(|| {
+                    self.set_output_channel_count_and_sample_rate(channel_count, sample_rate)
+                });
+            }
+        }
+
+        if let Some(clock) = &self.published_frame_clock {
+            clock.store(self.frame_clock, Ordering::Relaxed);
+        }
+
         // Attempt to grab from paused sounds again
         self.sounds.append(&mut self.paused_sounds);
 
@@ -89,16 +534,23 @@ impl Sound for SoundMixer {
             return Ok(NextSample::MetadataChanged);
         }
 
-        let mut output: i16 = 0;
+        if self.next_output_channel_idx == 0 {
+            self.start_due_sounds();
+        }
 
-        let mut to_remove = Vec::new();
+        let mut output: i32 = 0;
 
-        for (idx, sound) in self.sounds.iter_mut().enumerate() {
-            loop {
+        // Sounds are removed as they are mixed by swapping the last sound into
+        // their place so nothing needs to be allocated.
+        let mut idx = 0;
+        while idx < self.sounds.len() {
+            let sound = &mut self.sounds[idx];
+            // Whether the sound is removed and if so whether it is paused.
+            let removal = loop {
                 match sound.next_sample() {
                     Ok(NextSample::Sample(s)) => {
-                        output = output.saturating_add(s);
-                        break;
+                        output += s as i32;
+                        break None;
                     }
                     Ok(NextSample::MetadataChanged) => {
                         // We know that the channel_count and sample_rate haven't changed because
@@ -111,40 +563,33 @@ impl Sound for SoundMixer {
                             // In the rare case we see MetadataChange not on
                             // the first channel, lets pause the sound until the
                             // next batch to avoid de-syncing the channels.
-                            to_remove.push((idx, true));
-                            break;
+                            break Some(true);
                         }
                     }
-                    Ok(NextSample::Paused) => {
-                        to_remove.push((idx, true));
-                        break;
-                    }
-                    Ok(NextSample::Finished) => {
-                        to_remove.push((idx, false));
-                        break;
-                    }
+                    Ok(NextSample::Paused) => break Some(true),
+                    Ok(NextSample::Finished) => break Some(false),
                     Err(e) => {
-                        // TODO probably want to let applications subscribe to be notified of these
-                        // errors
-                        log::error!("dropping sound in SoundMixer which returned error: {}", e);
-                        to_remove.push((idx, false));
-                        break;
+                        report_error(&self.collector, e);
+                        break Some(false);
                     }
                 }
+            };
+            match removal {
+                Some(paused) => self.remove(idx, paused),
+                None => idx += 1,
             }
         }
 
-        for (idx, paused) in to_remove.into_iter().rev() {
-            let sound = self.sounds.swap_remove(idx);
-            if paused {
-                self.paused_sounds.push(sound);
-            }
-            // otherwise drop finished sound
-        }
-
         self.next_output_channel_idx += 1;
         if self.next_output_channel_idx == self.output_channel_count {
             self.next_output_channel_idx = 0;
+            self.frame_clock += 1;
+        }
+
+        if self.sounds.is_empty() && (!self.scheduled.is_empty() || self.master.has_tail()) {
+            // Output silence until the next scheduled sound starts or the
+            // samples delayed by the master stage have been played out.
+            return Ok(NextSample::Sample(self.master.process_i32(0)));
         }
 
         match (self.sounds.is_empty(), self.paused_sounds.is_empty()) {
@@ -154,31 +599,396 @@ impl Sound for SoundMixer {
             // is changed to a Paused by the wrapper.
             (true, true) => {
                 self.next_output_channel_idx = 0;
+                self.master.finish_frame();
                 Ok(NextSample::Finished)
             }
             (true, false) => {
                 self.next_output_channel_idx = 0;
+                self.master.finish_frame();
                 Ok(NextSample::Finished)
             }
-            (false, _) => Ok(NextSample::Sample(output)),
+            (false, _) => Ok(NextSample::Sample(self.master.process_i32(output))),
         }
     }
+
+    /// Guaranteed to not return an Error.
+    fn next_samples(&mut self, buffer: &mut [i16]) -> Result<(usize, NextSample), crate::Error> {
+        Ok(self
+            .mix_until_starts(buffer, Self::mix_i16)
+            .unwrap_or_else(|| filled(buffer)))
+    }
+
+    /// With the default [MasterStage] this mixes without saturating so the
+    /// result may be outside of -1.0 to 1.0 if multiple loud sounds are
+    /// playing.
+    ///
+    /// Guaranteed to not return an Error.
+    fn next_samples_f32(
+        &mut self,
+        buffer: &mut [f32],
+    ) -> Result<(usize, NextSample), crate::Error> {
+        Ok(self
+            .mix_until_starts(buffer, Self::mix_f32)
+            .unwrap_or_else(|| filled_f32(buffer)))
+    }
+}
+
+impl SoundMixer {
+    /// Split `buffer` where scheduled sounds start so they start on the exact
+    /// frame and into parts of at most SCRATCH_LEN samples and mix each part
+    /// with `mix`. Returns None if all of `buffer` was filled.
+    fn mix_until_starts<T>(
+        &mut self,
+        buffer: &mut [T],
+        mix: impl Fn(&mut Self, &mut [T]) -> Option<(usize, NextSample)>,
+    ) -> Option<(usize, NextSample)> {
+        let mut num_filled = 0;
+        while num_filled < buffer.len() {
+            if self.next_output_channel_idx == 0 && !self.metadata_changed {
+                self.start_due_sounds();
+            }
+            let remaining = (buffer.len() - num_filled).min(SCRATCH_LEN);
+            let len = self
+                .samples_until_next_start()
+                .map_or(remaining, |samples| samples.min(remaining));
+            if let Some((num_read, next)) = mix(self, &mut buffer[num_filled..num_filled + len]) {
+                return Some((num_filled + num_read, next));
+            }
+            num_filled += len;
+        }
+        None
+    }
+
+    fn mix_i16(&mut self, buffer: &mut [i16]) -> Option<(usize, NextSample)> {
+        let mut sound_buffer = std::mem::take(&mut self.sound_buffer);
+        let mut mix_buffer = std::mem::take(&mut self.mix_buffer);
+        let result = self.mix_samples(
+            &mut mix_buffer[..buffer.len()],
+            &mut sound_buffer,
+            |sound, buffer| sound.next_samples(buffer),
+            |sum, sample| sum + sample as i32,
+        );
+        let num_mixed = result.map_or(buffer.len(), |(num_read, _)| num_read);
+        for (output, sum) in buffer[..num_mixed].iter_mut().zip(&mix_buffer) {
+            *output = self.master.process_i32(*sum);
+        }
+        self.on_mixed(result);
+        self.sound_buffer = sound_buffer;
+        self.mix_buffer = mix_buffer;
+        result
+    }
+
+    fn mix_f32(&mut self, buffer: &mut [f32]) -> Option<(usize, NextSample)> {
+        let mut sound_buffer = std::mem::take(&mut self.sound_buffer_f32);
+        let result = self.mix_samples(
+            buffer,
+            &mut sound_buffer,
+            |sound, buffer| sound.next_samples_f32(buffer),
+            |a, b| a + b,
+        );
+        if !self.master.is_hard_clip_only() {
+            let num_mixed = result.map_or(buffer.len(), |(num_read, _)| num_read);
+            for sample in &mut buffer[..num_mixed] {
+                *sample = self.master.process(*sample);
+            }
+        }
+        self.on_mixed(result);
+        self.sound_buffer_f32 = sound_buffer;
+        result
+    }
+
+    /// Shared implementation of `next_samples` and `next_samples_f32`.
+    ///
+    /// `sound_buffer` is scratch space at least as long as `buffer` to read
+    /// each sound into before it is combined into `buffer` with `mix`. Returns
+    /// None if all of `buffer` was filled.
+    fn mix_samples<T: Copy + Default, A: Copy + Default + PartialEq>(
+        &mut self,
+        buffer: &mut [A],
+        sound_buffer: &mut [T],
+        read: impl Fn(&mut MixedSound, &mut [T]) -> Result<(usize, NextSample), crate::Error>,
+        mix: impl Fn(A, T) -> A,
+    ) -> Option<(usize, NextSample)> {
+        if self.metadata_changed {
+            assert!(self.next_output_channel_idx == 0);
+            self.metadata_changed = false;
+            return Some((0, NextSample::MetadataChanged));
+        }
+
+        buffer.fill(A::default());
+        let sound_buffer = &mut sound_buffer[..buffer.len()];
+        let channel_count = self.output_channel_count as usize;
+        let first_channel_idx = self.next_output_channel_idx as usize;
+
+        let mut max_num_read = 0;
+
+        // See next_sample for how sounds are removed.
+        let mut idx = 0;
+        while idx < self.sounds.len() {
+            let sound = &mut self.sounds[idx];
+            let mut num_read = 0;
+            let mut removal = None;
+            while num_read < buffer.len() {
+                let (count, next) = match read(sound, &mut sound_buffer[num_read..]) {
+                    Ok(result) => result,
+                    Err(e) => {
+                        report_error(&self.collector, e);
+                        removal = Some(false);
+                        break;
+                    }
+                };
+                for (output, sample) in buffer[num_read..num_read + count]
+                    .iter_mut()
+                    .zip(&sound_buffer[num_read..num_read + count])
+                {
+                    *output = mix(*output, *sample);
+                }
+                num_read += count;
+                match next {
+                    NextSample::Sample(_) => {}
+                    NextSample::MetadataChanged => {
+                        // Same as in next_sample, only keep reading if we are
+                        // on the first channel of a frame.
+                        if !(first_channel_idx + num_read).is_multiple_of(channel_count) {
+                            removal = Some(true);
+                            break;
+                        }
+                    }
+                    NextSample::Paused => {
+                        removal = Some(true);
+                        break;
+                    }
+                    NextSample::Finished => {
+                        removal = Some(false);
+                        break;
+                    }
+                }
+            }
+            max_num_read = max_num_read.max(num_read);
+            match removal {
+                Some(paused) => self.remove(idx, paused),
+                None => idx += 1,
+            }
+        }
+
+        // The mixed samples have not been through the master stage yet so
+        // they may add to its tail.
+        let has_tail = self.master.has_tail()
+            || (self.master.delays()
+                && buffer[..max_num_read]
+                    .iter()
+                    .any(|sample| *sample != A::default()));
+        if self.sounds.is_empty() && self.scheduled.is_empty() && !has_tail {
+            // See next_sample for why this is Finished even with paused sounds.
+            self.next_output_channel_idx = 0;
+            self.frame_clock += ((first_channel_idx + max_num_read) / channel_count) as u64;
+            return Some((max_num_read, NextSample::Finished));
+        }
+
+        // Otherwise the rest of the buffer is silence until the next
+        // scheduled sound starts or the samples delayed by the master stage
+        // are played out.
+        let end = first_channel_idx + buffer.len();
+        self.frame_clock += (end / channel_count) as u64;
+        self.next_output_channel_idx = (end % channel_count) as u16;
+        None
+    }
+}
+
+impl SoundMixer {
+    /// Keep the master stage aligned to frames after mixing stopped early.
+    fn on_mixed(&mut self, result: Option<(usize, NextSample)>) {
+        if let Some((_, NextSample::Finished)) = result {
+            self.master.finish_frame();
+        }
+    }
+}
+
+/// Run `change` which converts sounds to a new output format. This is rare
+/// and allocates so it is allowed even with the assert-no-alloc feature.
+fn allow_format_change<T>(change: impl FnOnce() -> T) -> T {
+    #[cfg(feature = "assert-no-alloc")]
+    return assert_no_alloc::permit_alloc(change);
+    #[cfg(not(feature = "assert-no-alloc"))]
+    change()
+}
+
+/// Something removed from a mixer that is dropped by a [SoundCollector].
+// The fields are only held to be dropped.
+#[allow(dead_code)]
+enum Garbage {
+    Sound(MixedSound),
+    MasterStage(Box<MasterStageProcessor>),
+}
+
+impl From<MixedSound> for Garbage {
+    fn from(sound: MixedSound) -> Self {
+        Garbage::Sound(sound)
+    }
+}
+
+impl From<Box<MasterStageProcessor>> for Garbage {
+    fn from(master: Box<MasterStageProcessor>) -> Self {
+        Garbage::MasterStage(master)
+    }
+}
+
+/// Drop a sound or master stage removed from a mixer on the thread of
+/// `collector` if set.
+fn retire(collector: &Option<Arc<SoundCollector>>, garbage: impl Into<Garbage>) {
+    match collector {
+        Some(collector) => collector.collect(garbage.into()),
+        None => drop(garbage),
+    }
+}
+
+/// Log the error of a sound removed from a mixer on the thread of `collector`
+/// if set.
+fn report_error(collector: &Option<Arc<SoundCollector>>, error: crate::Error) {
+    match collector {
+        Some(collector) => collector.collect_error(error),
+        None => log_error(error),
+    }
+}
+
+fn log_error(error: crate::Error) {
+    log::error!(
+        "dropping sound in SoundMixer which returned error: {}",
+        error
+    );
+}
+
+/// Drops sounds removed from mixers on a separate thread so their decoders,
+/// files and buffers are not freed on the audio thread. Errors of removed
+/// sounds are logged on the same thread and a task can be set to do other
+/// work off the audio thread such as sending events.
+///
+/// The thread exits once the collector is dropped.
+pub(crate) struct SoundCollector {
+    garbage: ArrayQueue<Garbage>,
+    errors: ArrayQueue<crate::Error>,
+    /// Run on the thread each time it wakes up.
+    task: OnceLock<Box<dyn Fn() + Send + Sync>>,
+    /// Not set if the thread could not be spawned.
+    thread: OnceLock<Thread>,
+}
+
+impl SoundCollector {
+    /// How often the thread checks for sounds without being woken up.
+    const INTERVAL: Duration = Duration::from_millis(100);
+
+    /// Start a collector that can hold `capacity` sounds or master stages and
+    /// `capacity` errors waiting to be dropped.
+    pub(crate) fn spawn(capacity: usize) -> Arc<Self> {
+        let collector = Arc::new(SoundCollector {
+            garbage: ArrayQueue::new(capacity),
+            errors: ArrayQueue::new(capacity),
+            task: OnceLock::new(),
+            thread: OnceLock::new(),
+        });
+        let weak = Arc::downgrade(&collector);
+        match std::thread::Builder::new()
+            .name("awedio-collector".to_owned())
+            .spawn(move || Self::run(weak))
+        {
+            Ok(handle) => {
+                let _ = collector.thread.set(handle.thread().clone());
+            }
+            Err(e) => log::warn!("dropping sounds on the audio thread: {}", e),
+        }
+        collector
+    }
+
+    fn run(collector: Weak<SoundCollector>) {
+        while let Some(collector) = collector.upgrade() {
+            while let Some(garbage) = collector.garbage.pop() {
+                drop(garbage);
+            }
+            while let Some(error) = collector.errors.pop() {
+                log_error(error);
+            }
+            // After the sounds are dropped so what they report when dropped
+            // is handled right away.
+            if let Some(task) = collector.task.get() {
+                task();
+            }
+            drop(collector);
+            std::thread::park_timeout(Self::INTERVAL);
+        }
+    }
+
+    /// Set the task run on the thread each time it wakes up. Only the first
+    /// task set is kept.
+    pub(crate) fn set_task(&self, task: Box<dyn Fn() + Send + Sync>) {
+        let _ = self.task.set(task);
+    }
+
+    /// Wake the thread up to run the task soon. Does not allocate unless the
+    /// thread could not be spawned in which case the task is run right away.
+    pub(crate) fn wake(&self) {
+        match self.thread.get() {
+            Some(thread) => thread.unpark(),
+            None => {
+                if let Some(task) = self.task.get() {
+                    task();
+                }
+            }
+        }
+    }
+
+    /// Hand `garbage` to the thread to be dropped. Does not allocate.
+    fn collect(&self, garbage: Garbage) {
+        let Some(thread) = self.thread.get() else {
+            drop(garbage);
+            return;
+        };
+        if let Err(garbage) = self.garbage.push(garbage) {
+            // The thread has fallen far behind so there is no better option.
+            drop(garbage);
+        }
+        thread.unpark();
+    }
+
+    /// Hand `error` to the thread to be logged. Does not allocate.
+    fn collect_error(&self, error: crate::Error) {
+        let Some(thread) = self.thread.get() else {
+            log_error(error);
+            return;
+        };
+        // If the thread has fallen far behind the error is not logged.
+        let _ = self.errors.push(error);
+        thread.unpark();
+    }
 }
 
 impl AddSound for SoundMixer {
     fn add(&mut self, sound: Box<dyn Sound>) {
-        self.sounds.push(SampleRateConverter::new(
-            ChannelCountConverter::new(sound, self.output_channel_count),
-            self.output_sample_rate,
-        ));
+        self.add_with_resample_quality(sound, self.resample_quality);
     }
 }
 
 impl ClearSounds for SoundMixer {
-    /// Remove all audio sounds.
+    /// Remove all audio sounds including scheduled sounds that have not
+    /// started yet.
+    ///
+    /// The sounds of groups created with
+    /// [Manager::create_group][crate::manager::Manager::create_group] are
+    /// kept so the groups can still be played in.
     fn clear(&mut self) {
-        self.sounds.clear();
-        self.paused_sounds.clear();
+        for sounds in [&mut self.sounds, &mut self.paused_sounds] {
+            let mut idx = 0;
+            while idx < sounds.len() {
+                if sounds[idx].is_group {
+                    idx += 1;
+                } else {
+                    retire(&self.collector, sounds.swap_remove(idx));
+                }
+            }
+        }
+        while let Some(scheduled) = self.scheduled.pop() {
+            retire(&self.collector, scheduled.sound);
+        }
+        self.master.reset();
     }
 }
 
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use crate::{NextSample, Sound};
 
 type SoundGenerator = Box<dyn FnMut() -> Option<Box<dyn Sound>> + Send>;
@@ -74,6 +76,16 @@ impl Sound for SoundsFromFn {
         }
     }
 
+    /// The position within the currently playing sound.
+    fn position(&self) -> Option<Duration> {
+        self.current.as_ref().and_then(|s| s.position())
+    }
+
+    /// The duration of the currently playing sound.
+    fn duration(&self) -> Option<Duration> {
+        self.current.as_ref().and_then(|s| s.duration())
+    }
+
     fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
         loop {
             let Some(current) = &mut self.current else {
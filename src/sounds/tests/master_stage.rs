@@ -0,0 +1,90 @@
+use super::*;
+
+const SAMPLE_RATE: u32 = 10_000;
+
+fn processor(gain: f32, clipping: Clipping) -> MasterStageProcessor {
+    MasterStageProcessor::new(MasterStage { gain, clipping }, 2, SAMPLE_RATE)
+}
+
+fn limiter() -> Clipping {
+    Clipping::Limiter {
+        // 10 frames
+        look_ahead: Duration::from_millis(1),
+        release: Duration::from_millis(2),
+    }
+}
+
+#[test]
+fn default_only_clamps() {
+    let mut master = processor(1.0, Clipping::Hard);
+    assert_eq!(master.process_i32(1000), 1000);
+    assert_eq!(master.process_i32(i16::MAX as i32 * 2), i16::MAX);
+    assert_eq!(master.process_i32(i16::MIN as i32 * 2), i16::MIN);
+    assert_eq!(master.process(1.5), 1.5);
+}
+
+#[test]
+fn headroom_gain() {
+    let mut master = processor(0.5, Clipping::Hard);
+    assert_eq!(master.process(1.5), 0.75);
+    assert_eq!(master.process_i32(i16::MAX as i32), i16::MAX / 2);
+}
+
+#[test]
+fn soft_clip_is_smooth_and_bounded() {
+    let mut master = processor(1.0, Clipping::Soft);
+    assert_eq!(master.process(0.25), 0.25);
+    assert_eq!(master.process(-0.5), -0.5);
+    let mut previous = 0.0;
+    for i in 0..400 {
+        let input = i as f32 / 100.0;
+        let output = master.process(input);
+        assert!(output >= previous && output < 1.0, "{input} {output}");
+        assert!(output - previous <= 0.0101, "{input} {output}");
+        assert_eq!(master.process(-input), -output);
+        previous = output;
+    }
+}
+
+#[test]
+fn limiter_delays_by_look_ahead() {
+    let mut master = processor(1.0, limiter());
+    let output: Vec<f32> = (1..=30).map(|i| master.process(i as f32 / 100.0)).collect();
+    // 10 frames of 2 channels
+    assert_eq!(output[..20], [0.0; 20]);
+    for (i, sample) in output[20..].iter().enumerate() {
+        assert!((sample - (i + 1) as f32 / 100.0).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn limiter_never_exceeds_full_scale() {
+    let mut master = processor(1.0, limiter());
+    let input = |frame: usize| {
+        if (50..60).contains(&frame) {
+            3.0
+        } else {
+            0.8
+        }
+    };
+    let mut output = Vec::new();
+    for frame in 0..200 {
+        for channel in [1.0, -1.0] {
+            output.push(master.process(input(frame) * channel));
+        }
+    }
+    let output: Vec<f32> = output.chunks(2).map(|frame| frame[0]).skip(10).collect();
+    for (frame, sample) in output.iter().enumerate() {
+        assert!(*sample <= 1.0, "{frame} {sample}");
+        assert!(*sample > 0.0, "{frame} {sample}");
+    }
+    // The peak is limited to full scale instead of being clamped.
+    assert!((output[55] - 1.0).abs() < 1e-6);
+    // The gain ramps down ahead of the peak instead of jumping.
+    for pair in output[40..50].windows(2) {
+        assert!(pair[1] <= pair[0]);
+    }
+    assert!(output[45] < 0.8 && output[45] > 0.3, "{}", output[45]);
+    // And recovers afterwards.
+    assert!((output[185] - 0.8).abs() < 0.01, "{}", output[185]);
+}
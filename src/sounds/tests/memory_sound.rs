@@ -1,4 +1,7 @@
-use crate::{sounds::SoundList, NextSample, Sound};
+use crate::{
+    sounds::{wrappers::Seekable, SoundList},
+    NextSample, Sound,
+};
 
 use super::*;
 
@@ -83,3 +86,73 @@ fn loop_forever() {
     assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(2));
     assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(1));
 }
+
+#[test]
+fn seek() {
+    let mut sound = MemorySound::from_samples(Arc::new(vec![1, 2, 3, 4, 5, 6]), 2, 1000);
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(1));
+    sound.seek(Duration::from_millis(2)).unwrap();
+    assert_eq!(sound.next_sample().unwrap(), NextSample::MetadataChanged);
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(5));
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(6));
+    sound.seek(Duration::ZERO).unwrap();
+    assert_eq!(sound.next_sample().unwrap(), NextSample::MetadataChanged);
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(1));
+    sound.seek(Duration::from_secs(10)).unwrap();
+    assert_eq!(sound.next_sample().unwrap(), NextSample::MetadataChanged);
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Finished);
+}
+
+#[test]
+fn seek_looping_wraps_around() {
+    let mut sound = MemorySound::from_samples(Arc::new(vec![1, 2, 3, 4, 5, 6]), 2, 1000);
+    sound.set_looping(true);
+    sound.seek(Duration::from_millis(4)).unwrap();
+    assert_eq!(sound.next_sample().unwrap(), NextSample::MetadataChanged);
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(3));
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(4));
+}
+
+#[test]
+fn position_and_duration() {
+    let mut sound = MemorySound::from_samples(Arc::new(vec![1, 2, 3, 4, 5, 6]), 2, 1000);
+    assert_eq!(sound.duration(), Some(Duration::from_millis(3)));
+    assert_eq!(sound.position(), Some(Duration::ZERO));
+    sound.next_sample().unwrap();
+    sound.next_sample().unwrap();
+    assert_eq!(sound.position(), Some(Duration::from_millis(1)));
+    sound.set_looping(true);
+    assert_eq!(sound.duration(), None);
+}
+
+#[test]
+fn next_samples_fills_buffer() {
+    let mut sound = MemorySound::from_samples(Arc::new(vec![1, 2, 3, 4, 5]), 1, 1000);
+    let mut buffer = [0; 3];
+    assert_eq!(
+        sound.next_samples(&mut buffer).unwrap(),
+        (3, NextSample::Sample(3))
+    );
+    assert_eq!(buffer, [1, 2, 3]);
+    assert_eq!(
+        sound.next_samples(&mut buffer).unwrap(),
+        (2, NextSample::Finished)
+    );
+    assert_eq!(&buffer[..2], &[4, 5]);
+    assert_eq!(
+        sound.next_samples(&mut buffer).unwrap(),
+        (0, NextSample::Finished)
+    );
+}
+
+#[test]
+fn next_samples_wraps_when_looping() {
+    let mut sound = MemorySound::from_samples(Arc::new(vec![1, 2]), 1, 1000);
+    sound.set_looping(true);
+    let mut buffer = [0; 5];
+    assert_eq!(
+        sound.next_samples(&mut buffer).unwrap(),
+        (5, NextSample::Sample(1))
+    );
+    assert_eq!(buffer, [1, 2, 1, 2, 1]);
+}
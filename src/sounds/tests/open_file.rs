@@ -0,0 +1,73 @@
+use super::*;
+
+const SINE_WAVE_WAV: &[u8] =
+    include_bytes!("../decoders/tests/audiocheck.net_sin_1000Hz_0dBFS_0.1s.wav");
+
+/// Check the first samples of the 1000Hz test file.
+#[cfg(any(feature = "hound-wav", feature = "symphonia"))]
+fn assert_sine_start(mut sound: Box<dyn Sound>) {
+    assert_eq!(sound.sample_rate(), 44100);
+    assert_eq!(sound.channel_count(), 1);
+    let mut samples = Vec::new();
+    while samples.len() < 4 {
+        match sound.next_sample().unwrap() {
+            crate::NextSample::Sample(s) => samples.push(s),
+            crate::NextSample::MetadataChanged => {}
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+    assert_eq!(samples, [0, 4647, 9201, 13567]);
+}
+
+#[cfg(any(feature = "hound-wav", feature = "symphonia"))]
+#[test]
+fn open_bytes_with_hint() {
+    assert_sine_start(open_bytes(SINE_WAVE_WAV, Some("WAV")).unwrap());
+    let shared: std::sync::Arc<[u8]> = SINE_WAVE_WAV.into();
+    assert_sine_start(open_bytes(shared, Some("wav")).unwrap());
+}
+
+#[cfg(feature = "symphonia")]
+#[test]
+fn open_reader_detects_format_without_hint() {
+    let reader = std::io::Cursor::new(SINE_WAVE_WAV.to_vec());
+    assert_sine_start(open_reader(reader, None).unwrap());
+}
+
+#[cfg(not(feature = "symphonia"))]
+#[test]
+fn unknown_format_is_unsupported() {
+    assert!(open_bytes(SINE_WAVE_WAV, None).is_err());
+}
+
+/// Seek a decoder through `Box<dyn Sound>` as a scrub bar would.
+#[cfg(any(feature = "hound-wav", feature = "qoa"))]
+fn assert_boxed_seek(mut sound: Box<dyn Sound>) {
+    use crate::sounds::wrappers::Seekable;
+    use std::time::Duration;
+
+    assert!(sound.as_seekable().is_some());
+    sound.seek(Duration::from_millis(50)).unwrap();
+    assert_eq!(sound.position(), Some(Duration::from_millis(50)));
+    assert_eq!(
+        sound.next_sample().unwrap(),
+        crate::NextSample::MetadataChanged
+    );
+    assert!(matches!(
+        sound.next_sample().unwrap(),
+        crate::NextSample::Sample(_)
+    ));
+}
+
+#[cfg(feature = "hound-wav")]
+#[test]
+fn boxed_wav_is_seekable() {
+    assert_boxed_seek(open_bytes(SINE_WAVE_WAV, Some("wav")).unwrap());
+}
+
+#[cfg(feature = "qoa")]
+#[test]
+fn boxed_qoa_is_seekable() {
+    let qoa = include_bytes!("../decoders/tests/audiocheck.net_sin_1000Hz_0dBFS_0.1s.qoa");
+    assert_boxed_seek(open_bytes(qoa, Some("qoa")).unwrap());
+}
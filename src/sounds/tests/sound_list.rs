@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use crate::{sounds::MemorySound, NextSample, Sound};
 
@@ -27,3 +27,176 @@ fn empty_gives_metadata_changed_on_next() {
     assert_eq!(list.next_sample().unwrap(), NextSample::Sample(6));
     assert_eq!(list.next_sample().unwrap(), NextSample::Finished);
 }
+
+#[test]
+fn seek_within_current_sound() {
+    let mut list = SoundList::new();
+    let first = MemorySound::from_samples(Arc::new(vec![1, 2, 3, 4]), 1, 1000);
+    list.add(Box::new(first));
+    let second = MemorySound::from_samples(Arc::new(vec![5, 6]), 1, 1000);
+    list.add(Box::new(second));
+    assert_eq!(list.next_sample().unwrap(), NextSample::MetadataChanged);
+    assert_eq!(list.next_sample().unwrap(), NextSample::Sample(1));
+    list.seek(Duration::from_millis(3)).unwrap();
+    assert_eq!(list.next_sample().unwrap(), NextSample::MetadataChanged);
+    assert_eq!(list.next_sample().unwrap(), NextSample::Sample(4));
+    assert_eq!(list.next_sample().unwrap(), NextSample::MetadataChanged);
+    assert_eq!(list.next_sample().unwrap(), NextSample::Sample(5));
+    list.seek(Duration::from_millis(1)).unwrap();
+    assert_eq!(list.next_sample().unwrap(), NextSample::MetadataChanged);
+    assert_eq!(list.next_sample().unwrap(), NextSample::Sample(6));
+    assert_eq!(list.next_sample().unwrap(), NextSample::Finished);
+}
+
+#[test]
+fn seek_unsupported_sound() {
+    let mut list = SoundList::new();
+    list.add(Box::new(crate::sounds::SineWav::new(400.0)));
+    assert!(list.seek(Duration::from_millis(1)).is_err());
+}
+
+#[test]
+fn crossfade_overlaps_the_end() {
+    let mut list = SoundList::new();
+    list.set_crossfade(Some(Duration::from_millis(4)));
+    // 10 frames each at 1000 Hz
+    list.add(Box::new(MemorySound::from_samples(
+        Arc::new(vec![1000; 10]),
+        1,
+        1000,
+    )));
+    list.add(Box::new(MemorySound::from_samples(
+        Arc::new(vec![2000; 10]),
+        1,
+        1000,
+    )));
+    assert_eq!(list.next_sample().unwrap(), NextSample::MetadataChanged);
+    let mut output = Vec::new();
+    loop {
+        match list.next_sample().unwrap() {
+            NextSample::Sample(s) => output.push(s),
+            NextSample::MetadataChanged => panic!("no switch without a crossfade"),
+            NextSample::Paused => panic!("unexpected pause"),
+            NextSample::Finished => break,
+        }
+    }
+    // The last 4 frames of the first overlap the first 4 of the second.
+    assert_eq!(output.len(), 16);
+    assert_eq!(&output[..6], &[1000; 6]);
+    assert_eq!(output[6], 1000);
+    for s in &output[7..10] {
+        assert!(*s > 1000 && *s < 2500, "{output:?}");
+    }
+    assert_eq!(&output[10..], &[2000; 6]);
+}
+
+#[test]
+fn crossfade_converts_the_next_sound() {
+    let mut list = SoundList::new();
+    list.set_crossfade(Some(Duration::from_millis(2)));
+    list.add(Box::new(MemorySound::from_samples(
+        Arc::new(vec![0; 10]),
+        1,
+        1000,
+    )));
+    list.add(Box::new(MemorySound::from_samples(
+        Arc::new(vec![100; 20]),
+        2,
+        1000,
+    )));
+    assert_eq!(list.next_sample().unwrap(), NextSample::MetadataChanged);
+    let mut output = Vec::new();
+    while let NextSample::Sample(s) = list.next_sample().unwrap() {
+        output.push(s);
+        // The second sound keeps the format of the first.
+        assert_eq!(list.channel_count(), 1);
+    }
+    assert_eq!(output.len(), 10 + 10 - 2);
+    assert_eq!(&output[10..], &[100; 8]);
+}
+
+#[test]
+fn crossfade_keeps_stereo_channels_in_order() {
+    let mut list = SoundList::new();
+    list.set_crossfade(Some(Duration::from_millis(4)));
+    let stereo = |left: i16| MemorySound::from_samples(Arc::new([left, -left].repeat(10)), 2, 1000);
+    list.add(Box::new(stereo(1000)));
+    // The incoming list returns MetadataChanged before its first sample.
+    let mut incoming = SoundList::new();
+    incoming.add(Box::new(stereo(2000)));
+    list.add(Box::new(incoming));
+    assert_eq!(list.next_sample().unwrap(), NextSample::MetadataChanged);
+    let mut output = Vec::new();
+    loop {
+        match list.next_sample().unwrap() {
+            NextSample::Sample(s) => output.push(s),
+            NextSample::MetadataChanged => {}
+            NextSample::Paused => panic!("unexpected pause"),
+            NextSample::Finished => break,
+        }
+    }
+    assert_eq!(output.len(), 2 * 16);
+    for frame in output.chunks(2) {
+        assert!(frame[0] >= 1000 && frame[1] <= -1000, "{output:?}");
+        assert_eq!(frame[0], -frame[1], "{output:?}");
+    }
+}
+
+/// Returns `samples` samples of stereo audio at 1000 Hz and then an error.
+struct FailingSound {
+    samples: usize,
+}
+
+impl Sound for FailingSound {
+    fn channel_count(&self) -> u16 {
+        2
+    }
+
+    fn sample_rate(&self) -> u32 {
+        1000
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        if self.samples == 0 {
+            return Err(std::io::Error::other("failed").into());
+        }
+        self.samples -= 1;
+        Ok(NextSample::Sample(2000))
+    }
+
+    fn on_start_of_batch(&mut self) {}
+}
+
+#[test]
+fn crossfade_finishes_the_frame_when_the_incoming_sound_errors() {
+    let mut list = SoundList::new();
+    list.set_crossfade(Some(Duration::from_millis(4)));
+    list.add(Box::new(MemorySound::from_samples(
+        Arc::new([1000, -1000].repeat(10)),
+        2,
+        1000,
+    )));
+    // Fails part way through the second frame of the crossfade.
+    list.add(Box::new(FailingSound { samples: 3 }));
+    assert_eq!(list.next_sample().unwrap(), NextSample::MetadataChanged);
+    let mut output = Vec::new();
+    let mut num_errors = 0;
+    loop {
+        match list.next_sample() {
+            Ok(NextSample::Sample(s)) => output.push(s),
+            Ok(NextSample::Finished) => break,
+            Ok(next) => panic!("unexpected {next:?}"),
+            Err(_) => {
+                // Reported at the start of a frame.
+                assert_eq!(output.len() % 2, 0);
+                num_errors += 1;
+            }
+        }
+    }
+    assert_eq!(num_errors, 1);
+    // The current sound plays to its end.
+    assert_eq!(output.len(), 20);
+    for frame in output.chunks(2) {
+        assert!(frame[0] > 0 && frame[1] < 0, "{output:?}");
+    }
+}
@@ -29,3 +29,285 @@ fn two_sounds_add_together() {
     assert_eq!(mixer.next_sample().unwrap(), NextSample::Sample(12));
     assert_eq!(mixer.next_sample().unwrap(), NextSample::Sample(12));
 }
+
+#[test]
+fn next_samples_matches_next_sample() {
+    let make_mixer = || {
+        let mut mixer = SoundMixer::new(2, 1000);
+        mixer.add(Box::new(crate::sounds::MemorySound::from_samples(
+            std::sync::Arc::new(vec![1, 2, 3, 4, 5, 6]),
+            2,
+            1000,
+        )));
+        mixer.add(Box::new(crate::sounds::MemorySound::from_samples(
+            std::sync::Arc::new(vec![10, 20, 30]),
+            1,
+            1000,
+        )));
+        mixer
+    };
+    let mut expected = Vec::new();
+    let mut mixer = make_mixer();
+    while let NextSample::Sample(s) = mixer.next_sample().unwrap() {
+        expected.push(s);
+    }
+    let mut mixer = make_mixer();
+    let mut buffer = [0; 4];
+    assert_eq!(
+        mixer.next_samples(&mut buffer).unwrap(),
+        (4, NextSample::Sample(24))
+    );
+    assert_eq!(&buffer[..], &expected[..4]);
+    assert_eq!(
+        mixer.next_samples(&mut buffer).unwrap(),
+        (2, NextSample::Finished)
+    );
+    assert_eq!(&buffer[..2], &expected[4..]);
+}
+
+#[test]
+fn f32_mixing_does_not_saturate() {
+    let mut mixer = SoundMixer::new(DEFAULT_CHANNEL_COUNT, DEFAULT_SAMPLE_RATE);
+    mixer.add(Box::new(ConstantValueSound::new(i16::MAX)));
+    mixer.add(Box::new(ConstantValueSound::new(i16::MAX)));
+    let mut buffer = [0.0; 4];
+    assert_eq!(
+        mixer.next_samples_f32(&mut buffer).unwrap(),
+        (4, NextSample::Sample(i16::MAX))
+    );
+    assert_eq!(buffer, [2.0; 4]);
+}
+
+#[test]
+fn sums_with_more_range_than_a_sample() {
+    let mut mixer = SoundMixer::new(DEFAULT_CHANNEL_COUNT, DEFAULT_SAMPLE_RATE);
+    mixer.add(Box::new(ConstantValueSound::new(30000)));
+    mixer.add(Box::new(ConstantValueSound::new(30000)));
+    mixer.add(Box::new(ConstantValueSound::new(-30000)));
+    // Saturating after each sound would give 2767.
+    let expected = 30000;
+    assert_eq!(mixer.next_sample().unwrap(), NextSample::Sample(expected));
+    let mut buffer = [0; 4];
+    mixer.next_samples(&mut buffer).unwrap();
+    assert_eq!(buffer, [expected; 4]);
+}
+
+#[test]
+fn soft_clip_master_stage() {
+    let mut mixer = SoundMixer::new(DEFAULT_CHANNEL_COUNT, DEFAULT_SAMPLE_RATE);
+    mixer.set_master_stage(MasterStage {
+        gain: 1.0,
+        clipping: crate::sounds::Clipping::Soft,
+    });
+    mixer.add(Box::new(ConstantValueSound::new(10000)));
+    assert_eq!(mixer.next_sample().unwrap(), NextSample::Sample(10000));
+    mixer.add(Box::new(ConstantValueSound::new(20000)));
+    mixer.add(Box::new(ConstantValueSound::new(20000)));
+    let NextSample::Sample(loud) = mixer.next_sample().unwrap() else {
+        panic!("expected sample");
+    };
+    assert!((30000..i16::MAX).contains(&loud), "{loud}");
+    let mut buffer = [0.0; 4];
+    mixer.next_samples_f32(&mut buffer).unwrap();
+    for sample in buffer {
+        assert!(sample < 1.0 && sample > 0.9, "{sample}");
+    }
+}
+
+fn ramp(len: i16) -> Box<crate::sounds::MemorySound> {
+    Box::new(crate::sounds::MemorySound::from_samples(
+        std::sync::Arc::new((1..=len).collect()),
+        1,
+        1000,
+    ))
+}
+
+#[test]
+fn scheduled_sound_starts_at_exact_frame_within_buffer() {
+    let mut mixer = SoundMixer::new(2, 1000);
+    mixer.add_at(ramp(3), 3);
+    let mut buffer = [-1; 10];
+    assert_eq!(
+        mixer.next_samples(&mut buffer).unwrap(),
+        (10, NextSample::Sample(2))
+    );
+    assert_eq!(buffer, [0, 0, 0, 0, 0, 0, 1, 1, 2, 2]);
+    assert_eq!(mixer.current_frame(), 5);
+    assert_eq!(
+        mixer.next_samples(&mut buffer).unwrap(),
+        (2, NextSample::Finished)
+    );
+    assert_eq!(buffer[..2], [3, 3]);
+    assert_eq!(mixer.current_frame(), 6);
+}
+
+#[test]
+fn scheduled_next_samples_matches_next_sample() {
+    let make_mixer = || {
+        let mut mixer = SoundMixer::new(2, 1000);
+        mixer.add(ramp(2));
+        mixer.add_at(ramp(4), 5);
+        mixer.add_at(ramp(2), 1);
+        mixer
+    };
+    let mut expected = Vec::new();
+    let mut mixer = make_mixer();
+    while let NextSample::Sample(s) = mixer.next_sample().unwrap() {
+        expected.push(s);
+    }
+    assert_eq!(expected.len(), 18);
+
+    let mut mixer = make_mixer();
+    let mut buffer = [0.0; 7];
+    let mut actual = Vec::new();
+    loop {
+        let (num_read, next) = mixer.next_samples_f32(&mut buffer).unwrap();
+        actual.extend(
+            buffer[..num_read]
+                .iter()
+                .map(|s| crate::utils::f32_to_i16(*s)),
+        );
+        if next == NextSample::Finished {
+            break;
+        }
+    }
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn scheduled_sound_already_due_starts_at_next_frame() {
+    let mut mixer = SoundMixer::new(2, 1000);
+    mixer.add(Box::new(ConstantValueSound::new(10)));
+    assert_eq!(mixer.next_sample().unwrap(), NextSample::Sample(10));
+    mixer.add_at(ramp(1), 0);
+    assert_eq!(mixer.next_sample().unwrap(), NextSample::Sample(10));
+    assert_eq!(mixer.next_sample().unwrap(), NextSample::Sample(11));
+    assert_eq!(mixer.next_sample().unwrap(), NextSample::Sample(11));
+    assert_eq!(mixer.next_sample().unwrap(), NextSample::Sample(10));
+}
+
+#[test]
+fn scheduled_start_keeps_time_across_sample_rate_change() {
+    let mut mixer = SoundMixer::new(1, 1000);
+    mixer.add_at(ramp(1), 10);
+    let mut buffer = [0; 4];
+    mixer.next_samples(&mut buffer).unwrap();
+    // 6 frames at 1kHz remain which is 12 frames at 2kHz.
+    mixer.set_output_channel_count_and_sample_rate(1, 2000);
+    assert_eq!(mixer.next_sample().unwrap(), NextSample::MetadataChanged);
+    let mut buffer = [-1; 13];
+    mixer.next_samples(&mut buffer).unwrap();
+    assert_eq!(buffer[..12], [0; 12]);
+    assert_ne!(buffer[12], 0);
+}
+
+#[test]
+fn clear_removes_scheduled_sounds() {
+    let mut mixer = SoundMixer::new(1, 1000);
+    mixer.add_at(ramp(1), 10);
+    mixer.clear();
+    assert_eq!(mixer.next_sample().unwrap(), NextSample::Finished);
+}
+
+/// Finishes immediately and records the thread it is dropped on.
+struct RecordsDropThread(std::sync::mpsc::Sender<std::thread::ThreadId>);
+
+impl Sound for RecordsDropThread {
+    fn channel_count(&self) -> u16 {
+        DEFAULT_CHANNEL_COUNT
+    }
+
+    fn sample_rate(&self) -> u32 {
+        DEFAULT_SAMPLE_RATE
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        Ok(NextSample::Finished)
+    }
+
+    fn on_start_of_batch(&mut self) {}
+}
+
+impl Drop for RecordsDropThread {
+    fn drop(&mut self) {
+        let _ = self.0.send(std::thread::current().id());
+    }
+}
+
+#[test]
+fn finished_sounds_are_dropped_by_collector() {
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let mut mixer = SoundMixer::new(DEFAULT_CHANNEL_COUNT, DEFAULT_SAMPLE_RATE);
+    mixer.set_collector(SoundCollector::spawn(4));
+    mixer.add(Box::new(RecordsDropThread(sender.clone())));
+    mixer.add(Box::new(ConstantValueSound::new(5)));
+    assert_eq!(mixer.next_sample().unwrap(), NextSample::Sample(5));
+    let dropped_on = receiver
+        .recv_timeout(std::time::Duration::from_secs(5))
+        .unwrap();
+    assert_ne!(dropped_on, std::thread::current().id());
+
+    // Without a collector sounds are dropped while mixing.
+    let mut mixer = SoundMixer::new(DEFAULT_CHANNEL_COUNT, DEFAULT_SAMPLE_RATE);
+    mixer.add(Box::new(RecordsDropThread(sender)));
+    let mut buffer = [0; 4];
+    assert_eq!(
+        mixer.next_samples(&mut buffer).unwrap(),
+        (0, NextSample::Finished)
+    );
+    assert_eq!(receiver.try_recv(), Ok(std::thread::current().id()));
+}
+
+fn limited_mixer() -> SoundMixer {
+    let mut mixer = SoundMixer::new(1, 1000);
+    mixer.set_master_stage(MasterStage {
+        gain: 1.0,
+        clipping: crate::sounds::Clipping::Limiter {
+            // 10 frames
+            look_ahead: std::time::Duration::from_millis(10),
+            release: std::time::Duration::from_millis(10),
+        },
+    });
+    mixer
+}
+
+#[test]
+fn limiter_tail_is_played_before_finishing() {
+    let mut mixer = limited_mixer();
+    for len in [20, 5] {
+        mixer.add(ramp(len));
+        let mut output = Vec::new();
+        loop {
+            match mixer.next_sample().unwrap() {
+                NextSample::Sample(s) => output.push(s),
+                NextSample::Finished => break,
+                next => panic!("{next:?}"),
+            }
+        }
+        // The delay is silent and the tail of the first sound is not played
+        // at the start of the second.
+        let expected: Vec<i16> = std::iter::repeat_n(0, 10).chain(1..=len).collect();
+        assert_eq!(output, expected);
+    }
+}
+
+#[test]
+fn limiter_tail_is_played_before_finishing_in_blocks() {
+    let mut mixer = limited_mixer();
+    for len in [20, 5] {
+        mixer.add(ramp(len));
+        let mut output = Vec::new();
+        loop {
+            let mut buffer = [0; 16];
+            let (num_read, next) = mixer.next_samples(&mut buffer).unwrap();
+            output.extend_from_slice(&buffer[..num_read]);
+            if next == NextSample::Finished {
+                break;
+            }
+        }
+        let expected: Vec<i16> = std::iter::repeat_n(0, 10).chain(1..=len).collect();
+        assert_eq!(output[..expected.len()], expected);
+        // Only silence is output after the tail.
+        assert!(output[expected.len()..].iter().all(|s| *s == 0));
+    }
+}
@@ -6,30 +6,49 @@
 //! instead of directly.
 
 mod adjustable_speed;
+mod adjustable_tempo;
 mod adjustable_volume;
 #[cfg(feature = "async")]
 pub mod async_completion_notifier;
 mod channel_count_converter;
 mod completion_notifier;
 mod controllable;
+mod fade;
 mod finish_after;
+mod metered;
+mod pannable;
 mod pausable;
+mod pitch_shift;
 mod sample_rate_converter;
+mod spectrum_tap;
+mod tee;
 mod wrapper;
 
 pub use adjustable_speed::AdjustableSpeed;
 pub use adjustable_speed::SetSpeed;
+pub use adjustable_tempo::AdjustableTempo;
+pub use adjustable_tempo::SetTempo;
 pub use adjustable_volume::AdjustableVolume;
 pub use adjustable_volume::SetVolume;
 #[cfg(feature = "async")]
 pub use async_completion_notifier::AsyncCompletionNotifier;
-pub use channel_count_converter::ChannelCountConverter;
+pub use channel_count_converter::{
+    ChannelCountConverter, ChannelMatrix, InvalidChannelMatrixError,
+};
 pub use completion_notifier::CompletionNotifier;
-pub use controllable::{Controllable, Controller};
+pub use controllable::{CommandQueueFull, Controllable, Controller, DEFAULT_COMMAND_CAPACITY};
+pub use fade::{Fade, FadeOut};
 pub use finish_after::FinishAfter;
+pub use metered::{MeterHandle, Metered, MAX_METERED_CHANNELS};
+pub use pannable::Pannable;
+pub use pannable::SetPan;
 pub use pausable::Pausable;
 pub use pausable::SetPaused;
-pub use sample_rate_converter::SampleRateConverter;
+pub use pitch_shift::PitchShift;
+pub use pitch_shift::SetPitch;
+pub use sample_rate_converter::{ResampleQuality, SampleRateConverter};
+pub use spectrum_tap::{SpectrumHandle, SpectrumTap, Window};
+pub use tee::{Tee, TeeRead, TeeReader};
 pub use wrapper::Wrapper;
 
 /// A Sound which contains other sounds that can be added to it.
@@ -44,3 +63,15 @@ pub trait ClearSounds {
     /// Clear all sounds currently playing or scheduled to play.
     fn clear(&mut self);
 }
+
+/// A Sound which can jump to a position.
+pub trait Seekable {
+    /// Jump to `position` measured from the start of the sound.
+    ///
+    /// Seeking past the end of the sound is not an error. The sound will
+    /// return `Finished` from the next call to `next_sample`. After a
+    /// successful seek `MetadataChanged` is returned from the next call to
+    /// `next_sample` so consumers discard any partially read frame and
+    /// continue with the first channel.
+    fn seek(&mut self, position: std::time::Duration) -> Result<(), crate::Error>;
+}
@@ -1,6 +1,8 @@
-use crate::Sound;
+use std::time::Duration;
 
-use super::{SetPaused, SetVolume};
+use crate::{utils, Sound};
+
+use super::{FadeOut, Seekable, SetPan, SetPaused, SetPitch, SetTempo, SetVolume};
 
 /// A sound that can have the playback speed adjusted.
 ///
@@ -11,13 +13,51 @@ pub trait SetSpeed {
     /// 1.0 is the normal playback speed. 2.0 would be twice as fast, 0.5 would
     /// be half has fast.
     fn set_speed(&mut self, multiplier: f32);
+
+    /// Change the playback speed gradually over `duration`.
+    ///
+    /// The default implementation changes the speed immediately.
+    fn set_speed_over(&mut self, multiplier: f32, duration: Duration) {
+        let _ = duration;
+        self.set_speed(multiplier)
+    }
 }
 
+/// The time between speed changes while ramping. Each change is reported as
+/// `MetadataChanged` so the speed is changed in blocks instead of per sample.
+///
+/// Every `MetadataChanged` makes the converters after this re-initialize:
+/// a [SampleRateConverter][super::SampleRateConverter] using
+/// [ResampleQuality::Linear][super::ResampleQuality::Linear] drops the frame it
+/// was interpolating from and the other qualities update the rates of their
+/// window. The step is long enough that this happens rarely while short
+/// enough for the steps in pitch to not be heard as such.
+const RAMP_STEP: Duration = Duration::from_millis(50);
+
 /// A wrapper that adjusts the speed of the inner sound.
+///
+/// Speed changes can be ramped in, in steps of 50ms of output, either
+/// explicitly with [SetSpeed::set_speed_over] or for every change by setting
+/// a smoothing time with [AdjustableSpeed::set_smoothing]. Each step is
+/// reported as `MetadataChanged` so use
+/// [ResampleQuality::Cubic][super::ResampleQuality::Cubic] or better when
+/// converting the sample rate of a ramped sound to avoid a small glitch at
+/// each step.
 pub struct AdjustableSpeed<S: Sound> {
     inner: S,
+    /// The speed currently applied.
     speed_adjustment: f32,
     speed_changed: bool,
+    /// The speed once ramping completes.
+    target_speed: f32,
+    speed_step: f32,
+    ramp_steps_remaining: u32,
+    /// Samples to return before the next ramp step.
+    samples_until_step: usize,
+    smoothing: Duration,
+    /// Index of the channel of the next sample. Changes are only reported at
+    /// the start of a frame so the channels stay in order.
+    next_channel_idx: u16,
 }
 
 impl<S> AdjustableSpeed<S>
@@ -36,9 +76,27 @@ where
             inner,
             speed_adjustment,
             speed_changed: false,
+            target_speed: speed_adjustment,
+            speed_step: 0.0,
+            ramp_steps_remaining: 0,
+            samples_until_step: 0,
+            smoothing: Duration::ZERO,
+            next_channel_idx: 0,
         }
     }
 
+    /// Ramp each later call to [SetSpeed::set_speed] over `duration`.
+    ///
+    /// The default is zero which changes the speed immediately.
+    pub fn set_smoothing(&mut self, duration: Duration) {
+        self.smoothing = duration;
+    }
+
+    /// The time each call to [SetSpeed::set_speed] is ramped over.
+    pub fn smoothing(&self) -> Duration {
+        self.smoothing
+    }
+
     /// Get a reference to the wrapped inner Sound.
     pub fn inner(&self) -> &S {
         &self.inner
@@ -70,16 +128,59 @@ where
     }
 
     fn next_sample(&mut self) -> Result<crate::NextSample, crate::Error> {
-        if self.speed_changed {
-            self.speed_changed = false;
+        if self.report_change() {
             return Ok(crate::NextSample::MetadataChanged);
         }
-        self.inner.next_sample()
+        let next = self.inner.next_sample()?;
+        let num_read = match next {
+            crate::NextSample::Sample(_) => 1,
+            _ => 0,
+        };
+        self.samples_read(num_read, next);
+        Ok(next)
+    }
+
+    fn next_samples(
+        &mut self,
+        buffer: &mut [i16],
+    ) -> Result<(usize, crate::NextSample), crate::Error> {
+        if self.report_change() {
+            return Ok((0, crate::NextSample::MetadataChanged));
+        }
+        let len = self.max_samples_until_step(buffer.len());
+        let (num_read, next) = self.inner.next_samples(&mut buffer[..len])?;
+        self.samples_read(num_read, next);
+        Ok((num_read, next))
+    }
+
+    fn next_samples_f32(
+        &mut self,
+        buffer: &mut [f32],
+    ) -> Result<(usize, crate::NextSample), crate::Error> {
+        if self.report_change() {
+            return Ok((0, crate::NextSample::MetadataChanged));
+        }
+        let len = self.max_samples_until_step(buffer.len());
+        let (num_read, next) = self.inner.next_samples_f32(&mut buffer[..len])?;
+        self.samples_read(num_read, next);
+        Ok((num_read, next))
     }
 
     fn on_start_of_batch(&mut self) {
         self.inner.on_start_of_batch()
     }
+
+    fn as_seekable(&mut self) -> Option<&mut dyn Seekable> {
+        self.inner.as_seekable()
+    }
+
+    fn position(&self) -> Option<Duration> {
+        self.inner.position()
+    }
+
+    fn duration(&self) -> Option<Duration> {
+        self.inner.duration()
+    }
 }
 
 impl<S> SetSpeed for AdjustableSpeed<S>
@@ -87,8 +188,22 @@ where
     S: Sound,
 {
     fn set_speed(&mut self, new: f32) {
-        self.speed_changed = true;
-        self.speed_adjustment = new;
+        self.set_speed_over(new, self.smoothing);
+    }
+
+    fn set_speed_over(&mut self, new: f32, duration: Duration) {
+        let num_steps = duration.as_micros().div_ceil(RAMP_STEP.as_micros());
+        self.target_speed = new;
+        if num_steps <= 1 {
+            self.speed_changed = true;
+            self.speed_adjustment = new;
+            self.ramp_steps_remaining = 0;
+            return;
+        }
+        self.speed_step = (new - self.speed_adjustment) / num_steps as f32;
+        self.ramp_steps_remaining = num_steps.min(u32::MAX as u128) as u32;
+        // The first step is taken immediately.
+        self.samples_until_step = 0;
     }
 }
 
@@ -97,8 +212,66 @@ where
     S: Sound,
 {
     /// Return the current speed multiplier. 1.0 is the default speed.
+    ///
+    /// While ramping this is the speed being ramped to.
     pub fn speed(&self) -> f32 {
-        self.speed_adjustment
+        self.target_speed
+    }
+
+    /// Take the next ramp step if it is due. Returns true if the speed
+    /// changed.
+    fn step_ramp(&mut self) -> bool {
+        if self.ramp_steps_remaining == 0 || self.samples_until_step > 0 {
+            return false;
+        }
+        self.ramp_steps_remaining -= 1;
+        if self.ramp_steps_remaining == 0 {
+            self.speed_adjustment = self.target_speed;
+        } else {
+            self.speed_adjustment += self.speed_step;
+        }
+        let step_frames =
+            utils::duration_to_num_samples(RAMP_STEP, 1, self.sample_rate()).max(1) as usize;
+        self.samples_until_step = step_frames * self.inner.channel_count() as usize;
+        true
+    }
+
+    /// Returns true if a speed change is due and should be reported as
+    /// MetadataChanged now.
+    fn report_change(&mut self) -> bool {
+        if self.next_channel_idx != 0 || !(self.speed_changed || self.step_ramp()) {
+            return false;
+        }
+        self.speed_changed = false;
+        true
+    }
+
+    /// Update the position after inner returned `num_read` samples followed
+    /// by `next`.
+    fn samples_read(&mut self, num_read: usize, next: crate::NextSample) {
+        self.samples_until_step = self.samples_until_step.saturating_sub(num_read);
+        if let crate::NextSample::MetadataChanged = next {
+            self.next_channel_idx = 0;
+        } else {
+            let channel_count = self.inner.channel_count() as usize;
+            self.next_channel_idx =
+                ((self.next_channel_idx as usize + num_read) % channel_count) as u16;
+        }
+    }
+
+    /// Limit `len` so that reading stops at the next ramp step.
+    fn max_samples_until_step(&self, len: usize) -> usize {
+        let step_due = self.ramp_steps_remaining > 0 && self.samples_until_step == 0;
+        if self.next_channel_idx != 0 && (self.speed_changed || step_due) {
+            // Finish the frame so the change can be reported.
+            let channel_count = self.inner.channel_count();
+            return len.min(channel_count.saturating_sub(self.next_channel_idx) as usize);
+        }
+        if self.ramp_steps_remaining == 0 {
+            len
+        } else {
+            len.min(self.samples_until_step)
+        }
     }
 }
 
@@ -118,6 +291,55 @@ where
     fn set_volume(&mut self, multiplier: f32) {
         self.inner.set_volume(multiplier)
     }
+
+    fn set_volume_over(&mut self, multiplier: f32, duration: Duration) {
+        self.inner.set_volume_over(multiplier, duration)
+    }
+}
+
+impl<S> SetTempo for AdjustableSpeed<S>
+where
+    S: Sound + SetTempo,
+{
+    fn set_tempo(&mut self, multiplier: f32) {
+        self.inner.set_tempo(multiplier)
+    }
+}
+
+impl<S> SetPitch for AdjustableSpeed<S>
+where
+    S: Sound + SetPitch,
+{
+    fn set_pitch(&mut self, multiplier: f32) {
+        self.inner.set_pitch(multiplier)
+    }
+}
+
+impl<S> FadeOut for AdjustableSpeed<S>
+where
+    S: Sound + FadeOut,
+{
+    fn fade_out(&mut self, duration: std::time::Duration) {
+        self.inner.fade_out(duration)
+    }
+}
+
+impl<S> SetPan for AdjustableSpeed<S>
+where
+    S: Sound + SetPan,
+{
+    fn set_pan(&mut self, pan: f32) {
+        self.inner.set_pan(pan)
+    }
+}
+
+impl<S> Seekable for AdjustableSpeed<S>
+where
+    S: Sound + Seekable,
+{
+    fn seek(&mut self, position: std::time::Duration) -> Result<(), crate::Error> {
+        self.inner.seek(position)
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,547 @@
+use std::time::Duration;
+
+use crate::{NextSample, Sound};
+
+use super::{FadeOut, Seekable, SetPan, SetPaused, SetPitch, SetSpeed, SetVolume};
+
+/// A sound that can have the tempo adjusted without changing the pitch.
+///
+/// This is [time stretching](https://en.wikipedia.org/wiki/Audio_time_stretching_and_pitch_scaling)
+/// unlike [SetSpeed] which changes both speed and pitch.
+pub trait SetTempo {
+    /// Change the tempo.
+    ///
+    /// 1.0 is the normal tempo. 2.0 would play twice as fast, 0.5 would play
+    /// half as fast. The pitch is unchanged.
+    fn set_tempo(&mut self, multiplier: f32);
+}
+
+/// The length of the overlapping segments.
+const SEGMENT_MILLIS: u32 = 30;
+/// How far from the nominal position a segment may be moved to best match the
+/// previous segment.
+const SEARCH_MILLIS: u32 = 8;
+/// Step size of the first coarse search for the best matching position.
+const COARSE_SEARCH_STEP: usize = 4;
+
+/// A wrapper that adjusts the tempo of the inner sound while keeping the
+/// pitch using WSOLA (waveform similarity based overlap-add).
+///
+/// While the tempo is 1.0 and has never been changed samples are passed
+/// through unchanged. Otherwise the output is delayed by about 30ms (one
+/// segment) which is also how much audio is buffered.
+///
+/// The buffers are allocated when the wrapper is created so changing the
+/// tempo does not allocate unless the channel count or sample rate of the
+/// inner sound increases or the tempo is above about 4.0.
+///
+/// [Sound::position] and [Sound::duration] report the position and duration
+/// of the inner sound (i.e. not adjusted by the tempo).
+pub struct AdjustableTempo<S: Sound> {
+    inner: S,
+    tempo: f32,
+    /// Index of the channel of the next sample while passing through. Used to
+    /// only start stretching at the start of a frame.
+    next_channel_idx: u16,
+    /// False while passing through.
+    stretching: bool,
+    stretcher: Stretcher,
+}
+
+impl<S> AdjustableTempo<S>
+where
+    S: Sound,
+{
+    /// Wrap `inner` such that its tempo can be adjusted.
+    pub fn new(inner: S) -> Self {
+        Self::new_with_tempo(inner, 1.0)
+    }
+
+    /// Wrap `inner` such that its tempo can be adjusted and set an initial
+    /// tempo.
+    pub fn new_with_tempo(inner: S, tempo: f32) -> Self {
+        let stretcher = Stretcher::new(inner.channel_count(), inner.sample_rate());
+        AdjustableTempo {
+            inner,
+            tempo,
+            next_channel_idx: 0,
+            stretching: false,
+            stretcher,
+        }
+    }
+
+    /// Get a reference to the wrapped inner Sound.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the wrapped inner Sound.
+    pub fn inner_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    /// Unwrap and return the previously wrapped Sound.
+    ///
+    /// Any buffered samples are lost.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    /// Return the current tempo multiplier. 1.0 is the default tempo.
+    pub fn tempo(&self) -> f32 {
+        self.tempo
+    }
+
+    fn next_passed_through_sample(&mut self) -> Result<NextSample, crate::Error> {
+        let next = self.inner.next_sample()?;
+        match next {
+            NextSample::Sample(_) => {
+                self.next_channel_idx += 1;
+                if self.next_channel_idx >= self.inner.channel_count() {
+                    self.next_channel_idx = 0;
+                }
+            }
+            NextSample::MetadataChanged | NextSample::Paused | NextSample::Finished => {
+                self.next_channel_idx = 0;
+            }
+        }
+        Ok(next)
+    }
+}
+
+impl<S> Sound for AdjustableTempo<S>
+where
+    S: Sound,
+{
+    fn channel_count(&self) -> u16 {
+        self.inner.channel_count()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        if !self.stretching {
+            if self.tempo == 1.0 || self.next_channel_idx != 0 {
+                return self.next_passed_through_sample();
+            }
+            self.stretcher
+                .reset(self.inner.channel_count(), self.inner.sample_rate());
+            self.stretching = true;
+        }
+        match self.stretcher.next_sample(&mut self.inner, self.tempo)? {
+            NextSample::Sample(s) => Ok(NextSample::Sample(s)),
+            next @ (NextSample::MetadataChanged | NextSample::Finished) => {
+                // Start over in case the channel count or sample rate changed
+                // or more samples come later.
+                self.stretching = false;
+                Ok(next)
+            }
+            NextSample::Paused => Ok(NextSample::Paused),
+        }
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.inner.on_start_of_batch()
+    }
+
+    fn as_seekable(&mut self) -> Option<&mut dyn Seekable> {
+        // Seeking through self drops the buffered samples.
+        self.inner.as_seekable()?;
+        Some(self)
+    }
+
+    fn position(&self) -> Option<Duration> {
+        self.inner.position()
+    }
+
+    fn duration(&self) -> Option<Duration> {
+        self.inner.duration()
+    }
+}
+
+impl<S> SetTempo for AdjustableTempo<S>
+where
+    S: Sound,
+{
+    fn set_tempo(&mut self, multiplier: f32) {
+        self.tempo = multiplier;
+    }
+}
+
+impl<S> SetPaused for AdjustableTempo<S>
+where
+    S: Sound + SetPaused,
+{
+    fn set_paused(&mut self, paused: bool) {
+        self.inner.set_paused(paused)
+    }
+}
+
+impl<S> SetVolume for AdjustableTempo<S>
+where
+    S: Sound + SetVolume,
+{
+    fn set_volume(&mut self, multiplier: f32) {
+        self.inner.set_volume(multiplier)
+    }
+
+    fn set_volume_over(&mut self, multiplier: f32, duration: Duration) {
+        self.inner.set_volume_over(multiplier, duration)
+    }
+}
+
+impl<S> SetSpeed for AdjustableTempo<S>
+where
+    S: Sound + SetSpeed,
+{
+    fn set_speed(&mut self, multiplier: f32) {
+        self.inner.set_speed(multiplier)
+    }
+
+    fn set_speed_over(&mut self, multiplier: f32, duration: Duration) {
+        self.inner.set_speed_over(multiplier, duration)
+    }
+}
+
+impl<S> SetPitch for AdjustableTempo<S>
+where
+    S: Sound + SetPitch,
+{
+    fn set_pitch(&mut self, multiplier: f32) {
+        self.inner.set_pitch(multiplier)
+    }
+}
+
+impl<S> FadeOut for AdjustableTempo<S>
+where
+    S: Sound + FadeOut,
+{
+    fn fade_out(&mut self, duration: std::time::Duration) {
+        self.inner.fade_out(duration)
+    }
+}
+
+impl<S> SetPan for AdjustableTempo<S>
+where
+    S: Sound + SetPan,
+{
+    fn set_pan(&mut self, pan: f32) {
+        self.inner.set_pan(pan)
+    }
+}
+
+impl<S> Seekable for AdjustableTempo<S>
+where
+    S: Sound,
+{
+    /// Seek the inner sound if it supports seeking (see
+    /// [Sound::as_seekable]) and drop the buffered samples. Otherwise an
+    /// IoError of [ErrorKind::Unsupported][std::io::ErrorKind::Unsupported]
+    /// is returned.
+    fn seek(&mut self, position: std::time::Duration) -> Result<(), crate::Error> {
+        match self.inner.as_seekable() {
+            Some(seekable) => seekable.seek(position)?,
+            None => return Err(std::io::Error::from(std::io::ErrorKind::Unsupported).into()),
+        }
+        self.stretching = false;
+        self.next_channel_idx = 0;
+        Ok(())
+    }
+}
+
+/// WSOLA state.
+///
+/// Output is produced one segment hop (half a segment) at a time by
+/// overlap-adding Hann windowed segments of the input. Each segment is taken
+/// from near its nominal position (advanced by `tempo` hops each time) at the
+/// offset that best matches the continuation of the previous segment which
+/// avoids phase cancellation.
+///
+/// Positions are in frames of the inner sound. The first segment is placed
+/// one hop before the start (where the input is silent) so the output does not
+/// fade in.
+struct Stretcher {
+    channel_count: usize,
+    /// Segment length in frames.
+    segment_len: usize,
+    /// Output hop (half of segment_len) in frames.
+    hop: usize,
+    /// Maximum offset from the nominal position in frames.
+    search_range: usize,
+    /// Hann window of segment_len.
+    window: Vec<f32>,
+    /// Interleaved input frames starting at `input_start`.
+    input: Vec<f32>,
+    /// Sum of the channels of each frame in `input` used for matching.
+    mono: Vec<f32>,
+    input_start: i64,
+    /// Position after the last frame of inner once it finished.
+    input_end: Option<i64>,
+    /// Nominal position of the next segment.
+    nominal_pos: f64,
+    /// Position of the previous segment.
+    prev_pos: Option<i64>,
+    /// Overlap-add accumulator of segment_len frames.
+    accumulator: Vec<f32>,
+    /// Finished output samples.
+    output: Vec<i16>,
+    next_output_idx: usize,
+    /// Set once all of the input has been output.
+    done: bool,
+    /// Scratch space for reading a frame from inner.
+    frame: Vec<i16>,
+}
+
+impl Stretcher {
+    fn new(channel_count: u16, sample_rate: u32) -> Stretcher {
+        let mut stretcher = Stretcher {
+            channel_count: 0,
+            segment_len: 0,
+            hop: 0,
+            search_range: 0,
+            window: Vec::new(),
+            input: Vec::new(),
+            mono: Vec::new(),
+            input_start: 0,
+            input_end: None,
+            nominal_pos: 0.0,
+            prev_pos: None,
+            accumulator: Vec::new(),
+            output: Vec::new(),
+            next_output_idx: 0,
+            done: false,
+            frame: Vec::new(),
+        };
+        stretcher.reset(channel_count, sample_rate);
+        stretcher
+    }
+
+    /// Go back to the state before any input was read for a sound with
+    /// `channel_count` and `sample_rate`.
+    ///
+    /// The buffers are reused so this only allocates if they need to be
+    /// larger than before.
+    fn reset(&mut self, channel_count: u16, sample_rate: u32) {
+        let channel_count = channel_count.max(1) as usize;
+        let hop = ((sample_rate * SEGMENT_MILLIS / 2000) as usize).max(8);
+        let segment_len = hop * 2;
+        if segment_len != self.segment_len {
+            self.window.clear();
+            self.window.extend((0..segment_len).map(|i| {
+                0.5 - 0.5 * (2.0 * std::f32::consts::PI * i as f32 / segment_len as f32).cos()
+            }));
+        }
+        self.channel_count = channel_count;
+        self.segment_len = segment_len;
+        self.hop = hop;
+        self.search_range = ((sample_rate * SEARCH_MILLIS / 1000) as usize).max(1);
+
+        let start = -(hop as i64);
+        // Silence before the start of the sound.
+        self.input.clear();
+        self.input.resize(hop * channel_count, 0.0);
+        self.mono.clear();
+        self.mono.resize(hop, 0.0);
+        // Room for the input kept between segments at tempos up to about 4.0
+        // so reading it does not allocate.
+        let max_input_frames = segment_len * 3 + 2 * self.search_range;
+        self.input
+            .reserve(max_input_frames * channel_count - self.input.len());
+        self.mono.reserve(max_input_frames - self.mono.len());
+        self.input_start = start;
+        self.input_end = None;
+        self.nominal_pos = start as f64;
+        self.prev_pos = None;
+        self.accumulator.clear();
+        self.accumulator.resize(segment_len * channel_count, 0.0);
+        self.output.clear();
+        self.output.reserve(hop * channel_count);
+        self.next_output_idx = 0;
+        self.done = false;
+        self.frame.clear();
+        self.frame.reserve(channel_count);
+    }
+
+    fn input_len(&self) -> i64 {
+        self.mono.len() as i64
+    }
+
+    fn next_sample<S: Sound>(
+        &mut self,
+        inner: &mut S,
+        tempo: f32,
+    ) -> Result<NextSample, crate::Error> {
+        while self.next_output_idx >= self.output.len() {
+            if self.done {
+                return Ok(NextSample::Finished);
+            }
+            if let Some(next) = self.process_segment(inner, tempo)? {
+                return Ok(next);
+            }
+        }
+        let sample = self.output[self.next_output_idx];
+        self.next_output_idx += 1;
+        Ok(NextSample::Sample(sample))
+    }
+
+    /// Read input frames until `end` or until inner returns something other
+    /// than a sample which is returned. Silence is added after inner finished.
+    fn read_input_until<S: Sound>(
+        &mut self,
+        inner: &mut S,
+        end: i64,
+    ) -> Result<Option<NextSample>, crate::Error> {
+        while self.input_start + self.input_len() < end {
+            if self.input_end.is_some() {
+                self.input
+                    .extend(std::iter::repeat_n(0.0, self.channel_count));
+                self.mono.push(0.0);
+                continue;
+            }
+            self.frame.clear();
+            match inner.append_next_frame_to(&mut self.frame) {
+                Ok(()) => {
+                    let mut sum = 0.0;
+                    for sample in &self.frame {
+                        let sample = *sample as f32;
+                        self.input.push(sample);
+                        sum += sample;
+                    }
+                    self.mono.push(sum);
+                }
+                Err(Ok(NextSample::Sample(_))) => unreachable!(),
+                Err(Ok(NextSample::Finished)) => {
+                    self.input_end = Some(self.input_start + self.input_len());
+                }
+                Err(Ok(next)) => return Ok(Some(next)),
+                Err(Err(e)) => return Err(e),
+            }
+        }
+        Ok(None)
+    }
+
+    /// Add the next segment and move the finished hop to `output`.
+    ///
+    /// Returns the variant from inner if more input could not be read.
+    fn process_segment<S: Sound>(
+        &mut self,
+        inner: &mut S,
+        tempo: f32,
+    ) -> Result<Option<NextSample>, crate::Error> {
+        let nominal = self.nominal_pos.round() as i64;
+        let search_range = self.search_range as i64;
+        let (search_start, search_end) = match self.prev_pos {
+            Some(_) => (
+                (nominal - search_range).max(self.input_start),
+                nominal + search_range,
+            ),
+            None => (nominal, nominal),
+        };
+        let needed_end = match self.prev_pos {
+            Some(prev_pos) => {
+                (search_end + self.segment_len as i64).max(prev_pos + (self.hop * 3) as i64)
+            }
+            None => nominal + self.segment_len as i64,
+        };
+        if let Some(next) = self.read_input_until(inner, needed_end)? {
+            return Ok(Some(next));
+        }
+
+        if self.input_end.is_some_and(|input_end| nominal >= input_end) {
+            // All of the input has been output.
+            self.done = true;
+            return Ok(None);
+        }
+
+        let pos = match self.prev_pos {
+            Some(prev_pos) => self.best_match(search_start, search_end, prev_pos + self.hop as i64),
+            None => nominal,
+        };
+
+        let offset = ((pos - self.input_start) as usize) * self.channel_count;
+        for (idx, acc) in self.accumulator.iter_mut().enumerate() {
+            *acc += self.window[idx / self.channel_count] * self.input[offset + idx];
+        }
+
+        let hop_samples = self.hop * self.channel_count;
+        self.output.clear();
+        self.next_output_idx = 0;
+        if self.prev_pos.is_some() {
+            self.output.extend(
+                self.accumulator[..hop_samples]
+                    .iter()
+                    // `as` saturates at the bounds of i16.
+                    .map(|s| s.round() as i16),
+            );
+        }
+        self.accumulator.copy_within(hop_samples.., 0);
+        let len = self.accumulator.len();
+        self.accumulator[len - hop_samples..].fill(0.0);
+
+        self.nominal_pos = match self.prev_pos {
+            Some(_) => self.nominal_pos + self.hop as f64 * tempo.max(0.01) as f64,
+            // The first segment only fades in the start of the input.
+            None => 0.0,
+        };
+        self.prev_pos = Some(pos);
+        // Drop input that can no longer be used by a segment.
+        let keep_from = ((self.nominal_pos.round() as i64 - search_range)
+            .min(pos + self.hop as i64)
+            - self.input_start)
+            .clamp(0, self.input_len()) as usize;
+        self.input.drain(..keep_from * self.channel_count);
+        self.mono.drain(..keep_from);
+        self.input_start += keep_from as i64;
+
+        Ok(None)
+    }
+
+    /// Return the position between `start` and `end` (inclusive) whose segment
+    /// best matches the segment at `target`.
+    fn best_match(&self, start: i64, end: i64, target: i64) -> i64 {
+        // Only the overlapping half of the segments is compared.
+        let len = self.hop;
+        let target = &self.mono[(target - self.input_start) as usize..][..len];
+        let score = |pos: i64| {
+            let candidate = &self.mono[(pos - self.input_start) as usize..][..len];
+            let mut correlation = 0.0;
+            let mut energy = 0.0;
+            for (a, b) in candidate.iter().zip(target) {
+                correlation += a * b;
+                energy += a * a;
+            }
+            if energy <= f32::EPSILON {
+                0.0
+            } else {
+                correlation / energy.sqrt()
+            }
+        };
+        let best_in = |from: i64, to: i64, step: usize, initial: i64| {
+            let mut best = (initial, score(initial));
+            for pos in (from..=to).step_by(step) {
+                let s = score(pos);
+                if s > best.1 {
+                    best = (pos, s);
+                }
+            }
+            best.0
+        };
+        // Prefer the nominal position (the middle) on ties so a tempo of 1.0
+        // is unchanged.
+        let middle = (start + end) / 2;
+        let coarse = best_in(start, end, COARSE_SEARCH_STEP, middle);
+        let step = COARSE_SEARCH_STEP as i64;
+        best_in(
+            (coarse - step + 1).max(start),
+            (coarse + step - 1).min(end),
+            1,
+            coarse,
+        )
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/adjustable_tempo.rs"]
+mod tests;
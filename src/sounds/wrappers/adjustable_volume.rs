@@ -1,6 +1,9 @@
-use crate::Sound;
+use std::time::Duration;
 
-use super::{SetPaused, SetSpeed};
+use crate::sound::{filled, filled_f32};
+use crate::{utils, Sound};
+
+use super::{FadeOut, Seekable, SetPan, SetPaused, SetPitch, SetSpeed, SetTempo};
 
 /// A sound that can have the loudness adjusted.
 pub trait SetVolume {
@@ -9,12 +12,32 @@ pub trait SetVolume {
     /// The samples are multiplied by `multiplier` so 1.0 would leave the Sound
     /// unchanged.
     fn set_volume(&mut self, multiplier: f32);
+
+    /// Change the loudness gradually over `duration` to avoid clicks.
+    ///
+    /// The default implementation changes the volume immediately.
+    fn set_volume_over(&mut self, multiplier: f32, duration: Duration) {
+        let _ = duration;
+        self.set_volume(multiplier)
+    }
 }
 
 /// A wrapper that adjusts the volume of the inner sound.
+///
+/// Volume changes can be ramped in linearly, one step per frame, either
+/// explicitly with [SetVolume::set_volume_over] or for every change by setting
+/// a smoothing time with [AdjustableVolume::set_smoothing].
 pub struct AdjustableVolume<S: Sound> {
     inner: S,
+    /// The volume currently applied.
     volume_adjustment: f32,
+    /// The volume once ramping completes.
+    target_volume: f32,
+    /// Volume change per frame while ramping.
+    volume_step: f32,
+    ramp_frames_remaining: u64,
+    smoothing: Duration,
+    next_channel_idx: u16,
 }
 
 impl<S> AdjustableVolume<S>
@@ -25,10 +48,7 @@ where
     ///
     /// The value is set to 1.0 so not adjustment is made.
     pub fn new(inner: S) -> Self {
-        AdjustableVolume {
-            inner,
-            volume_adjustment: 1.0,
-        }
+        Self::new_with_volume(inner, 1.0)
     }
 
     /// Wrap `inner` such that its volume can be adjusted and set an initial
@@ -37,9 +57,27 @@ where
         AdjustableVolume {
             inner,
             volume_adjustment,
+            target_volume: volume_adjustment,
+            volume_step: 0.0,
+            ramp_frames_remaining: 0,
+            smoothing: Duration::ZERO,
+            next_channel_idx: 0,
         }
     }
 
+    /// Ramp each later call to [SetVolume::set_volume] over `duration`.
+    ///
+    /// This avoids zipper noise when the volume is changed often (e.g. from a
+    /// slider). The default is zero which changes the volume immediately.
+    pub fn set_smoothing(&mut self, duration: Duration) {
+        self.smoothing = duration;
+    }
+
+    /// The time each call to [SetVolume::set_volume] is ramped over.
+    pub fn smoothing(&self) -> Duration {
+        self.smoothing
+    }
+
     /// Get a reference to the wrapped inner Sound.
     pub fn inner(&self) -> &S {
         &self.inner
@@ -73,17 +111,87 @@ where
         Ok(match next {
             crate::NextSample::Sample(s) => {
                 let adjusted = (s as f32 * self.volume_adjustment) as i16;
+                self.on_sample();
                 crate::NextSample::Sample(adjusted)
             }
             crate::NextSample::MetadataChanged
             | crate::NextSample::Paused
-            | crate::NextSample::Finished => next,
+            | crate::NextSample::Finished => {
+                self.next_channel_idx = 0;
+                next
+            }
         })
     }
 
+    fn next_samples(
+        &mut self,
+        buffer: &mut [i16],
+    ) -> Result<(usize, crate::NextSample), crate::Error> {
+        let (num_read, next) = self.inner.next_samples(buffer)?;
+        if self.ramp_frames_remaining == 0 {
+            for sample in &mut buffer[..num_read] {
+                *sample = (*sample as f32 * self.volume_adjustment) as i16;
+            }
+            self.skip_samples(num_read);
+        } else {
+            for sample in &mut buffer[..num_read] {
+                *sample = (*sample as f32 * self.volume_adjustment) as i16;
+                self.on_sample();
+            }
+        }
+        match next {
+            crate::NextSample::Sample(_) => Ok(filled(buffer)),
+            crate::NextSample::MetadataChanged
+            | crate::NextSample::Paused
+            | crate::NextSample::Finished => {
+                self.next_channel_idx = 0;
+                Ok((num_read, next))
+            }
+        }
+    }
+
+    fn next_samples_f32(
+        &mut self,
+        buffer: &mut [f32],
+    ) -> Result<(usize, crate::NextSample), crate::Error> {
+        let (num_read, next) = self.inner.next_samples_f32(buffer)?;
+        if self.ramp_frames_remaining == 0 {
+            for sample in &mut buffer[..num_read] {
+                *sample *= self.volume_adjustment;
+            }
+            self.skip_samples(num_read);
+        } else {
+            for sample in &mut buffer[..num_read] {
+                *sample *= self.volume_adjustment;
+                self.on_sample();
+            }
+        }
+        match next {
+            crate::NextSample::Sample(_) => Ok(filled_f32(buffer)),
+            crate::NextSample::MetadataChanged
+            | crate::NextSample::Paused
+            | crate::NextSample::Finished => {
+                self.next_channel_idx = 0;
+                Ok((num_read, next))
+            }
+        }
+    }
+
     fn on_start_of_batch(&mut self) {
         self.inner.on_start_of_batch()
     }
+
+    fn as_seekable(&mut self) -> Option<&mut dyn Seekable> {
+        self.inner.as_seekable()
+    }
+
+    fn position(&self) -> Option<Duration> {
+        self.inner.position()
+    }
+
+    fn duration(&self) -> Option<Duration> {
+        self.inner.duration()
+    }
 }
 
 impl<S> AdjustableVolume<S>
@@ -91,8 +199,34 @@ where
     S: Sound,
 {
     /// Return the current volume multiplier. 1.0 is the default volume.
+    ///
+    /// While ramping this is the volume being ramped to.
     pub fn volume(&self) -> f32 {
-        self.volume_adjustment
+        self.target_volume
+    }
+
+    /// Track the channel of a sample that was just returned and step the ramp
+    /// at the end of each frame.
+    fn on_sample(&mut self) {
+        self.next_channel_idx += 1;
+        if self.next_channel_idx < self.inner.channel_count() {
+            return;
+        }
+        self.next_channel_idx = 0;
+        if self.ramp_frames_remaining > 0 {
+            self.ramp_frames_remaining -= 1;
+            self.volume_adjustment += self.volume_step;
+            if self.ramp_frames_remaining == 0 {
+                self.volume_adjustment = self.target_volume;
+            }
+        }
+    }
+
+    /// Track the channel after `num_samples` samples while not ramping.
+    fn skip_samples(&mut self, num_samples: usize) {
+        let channel_count = self.inner.channel_count().max(1) as usize;
+        self.next_channel_idx =
+            ((self.next_channel_idx as usize + num_samples) % channel_count) as u16;
     }
 }
 
@@ -101,7 +235,19 @@ where
     S: Sound,
 {
     fn set_volume(&mut self, new: f32) {
-        self.volume_adjustment = new;
+        self.set_volume_over(new, self.smoothing);
+    }
+
+    fn set_volume_over(&mut self, new: f32, duration: Duration) {
+        let num_frames = utils::duration_to_num_samples(duration, 1, self.inner.sample_rate());
+        self.target_volume = new;
+        self.ramp_frames_remaining = num_frames;
+        if num_frames == 0 {
+            self.volume_adjustment = new;
+            self.volume_step = 0.0;
+        } else {
+            self.volume_step = (new - self.volume_adjustment) / num_frames as f32;
+        }
     }
 }
 
@@ -121,6 +267,55 @@ where
     fn set_speed(&mut self, multiplier: f32) {
         self.inner.set_speed(multiplier)
     }
+
+    fn set_speed_over(&mut self, multiplier: f32, duration: Duration) {
+        self.inner.set_speed_over(multiplier, duration)
+    }
+}
+
+impl<S> SetTempo for AdjustableVolume<S>
+where
+    S: Sound + SetTempo,
+{
+    fn set_tempo(&mut self, multiplier: f32) {
+        self.inner.set_tempo(multiplier)
+    }
+}
+
+impl<S> SetPitch for AdjustableVolume<S>
+where
+    S: Sound + SetPitch,
+{
+    fn set_pitch(&mut self, multiplier: f32) {
+        self.inner.set_pitch(multiplier)
+    }
+}
+
+impl<S> FadeOut for AdjustableVolume<S>
+where
+    S: Sound + FadeOut,
+{
+    fn fade_out(&mut self, duration: std::time::Duration) {
+        self.inner.fade_out(duration)
+    }
+}
+
+impl<S> SetPan for AdjustableVolume<S>
+where
+    S: Sound + SetPan,
+{
+    fn set_pan(&mut self, pan: f32) {
+        self.inner.set_pan(pan)
+    }
+}
+
+impl<S> Seekable for AdjustableVolume<S>
+where
+    S: Sound + Seekable,
+{
+    fn seek(&mut self, position: std::time::Duration) -> Result<(), crate::Error> {
+        self.inner.seek(position)
+    }
 }
 
 #[cfg(test)]
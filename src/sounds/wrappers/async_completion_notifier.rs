@@ -1,9 +1,11 @@
 //! [AsyncCompletionNotifier] and re-export of the tokio [`oneshot`] channel it
 //! uses for convenience.
 
+use super::Seekable;
 use super::Wrapper;
 use crate::NextSample;
 use crate::Sound;
+use std::time::Duration;
 pub use tokio::sync::oneshot;
 
 /// Notify via a [tokio::sync::oneshot::Receiver] when the contained Sound has
@@ -31,6 +33,16 @@ where
 
         (controllable, receiver)
     }
+
+    fn notify_if_finished(&mut self, next: NextSample) {
+        if let NextSample::Finished = next {
+            if let Some(sender) = self.sender.take() {
+                // If the consumer dropped their receiver because they don't need it anymore its
+                // not an error.
+                let _res = sender.send(());
+            }
+        }
+    }
 }
 
 impl<S> Sound for AsyncCompletionNotifier<S>
@@ -47,19 +59,40 @@ where
 
     fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
         let next = self.inner.next_sample()?;
-        if let NextSample::Finished = next {
-            if let Some(sender) = self.sender.take() {
-                // If the consumer dropped their receiver because they don't need it anymore its
-                // not an error.
-                let _res = sender.send(());
-            }
-        }
+        self.notify_if_finished(next);
         Ok(next)
     }
 
+    fn next_samples(&mut self, buffer: &mut [i16]) -> Result<(usize, NextSample), crate::Error> {
+        let (num_read, next) = self.inner.next_samples(buffer)?;
+        self.notify_if_finished(next);
+        Ok((num_read, next))
+    }
+
+    fn next_samples_f32(
+        &mut self,
+        buffer: &mut [f32],
+    ) -> Result<(usize, NextSample), crate::Error> {
+        let (num_read, next) = self.inner.next_samples_f32(buffer)?;
+        self.notify_if_finished(next);
+        Ok((num_read, next))
+    }
+
     fn on_start_of_batch(&mut self) {
         self.inner.on_start_of_batch();
     }
+
+    fn as_seekable(&mut self) -> Option<&mut dyn Seekable> {
+        self.inner.as_seekable()
+    }
+
+    fn position(&self) -> Option<Duration> {
+        self.inner.position()
+    }
+
+    fn duration(&self) -> Option<Duration> {
+        self.inner.duration()
+    }
 }
 
 impl<S> Wrapper for AsyncCompletionNotifier<S>
@@ -1,60 +1,413 @@
-use crate::{NextSample, Sound};
+use std::time::Duration;
 
-use super::Wrapper;
+use crate::{
+    sound::{filled, filled_f32, next_samples_f32_from_i16, next_samples_one_by_one},
+    utils, NextSample, Sound,
+};
+
+use super::{Seekable, Wrapper};
 
 /// Convert a Sound to have a specified number of output channels.
 /// For example convert a mono sound to stereo or vice versa.
+///
+/// By default the [standard][ChannelMatrix::standard] up-mixing and
+/// down-mixing rules are used. A custom [ChannelMatrix] can be given with
+/// [with_matrix][ChannelCountConverter::with_matrix].
 pub struct ChannelCountConverter<S: Sound> {
     inner: S,
+    /// The channel count of inner the converter type was chosen for.
+    from_count: u16,
     to_count: u16,
+    custom_matrix: Option<ChannelMatrix>,
     converter_type: ConverterType,
+    /// The matrix used by [ConverterType::Matrix]. Kept when the type changes
+    /// so its buffers can be reused.
+    matrix: ChannelMatrix,
+    /// Samples of the current input frame.
+    input: Vec<i16>,
+    /// Samples of the current output frame.
+    output: Vec<i16>,
+    /// Index into output of the next sample to return. Equal to the length of
+    /// output when a new frame needs to be read.
+    next_output_idx: usize,
 }
 
 enum ConverterType {
     PassThrough,
     MonoToStereo { last_sample: Option<i16> },
     StereoToMono,
+    Matrix,
+}
+
+/// The highest channel count with a [standard][ChannelMatrix::standard]
+/// conversion. Buffers are allocated up front for up to this many channels so
+/// a change of the channel count does not allocate.
+pub(super) const MAX_STANDARD_CHANNEL_COUNT: usize = 8;
+
+/// The gain from each input channel to each output channel used by a
+/// [ChannelCountConverter].
+///
+/// Channels are assumed to be in the standard WAV order: L, R for stereo; L,
+/// R, SL, SR for quad; L, R, C, LFE, SL, SR for 5.1 and L, R, C, LFE, BL, BR,
+/// SL, SR for 7.1.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelMatrix {
+    from_count: u16,
+    to_count: u16,
+    /// `to_count` rows of `from_count` gains.
+    gains: Vec<f32>,
 }
 
+/// Returned when creating a [ChannelMatrix] with gains that do not match the
+/// channel counts.
+#[derive(Debug)]
+pub struct InvalidChannelMatrixError {}
+
+impl std::fmt::Display for InvalidChannelMatrixError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "channel matrix must have non-zero channel counts and from_count * to_count gains"
+        )
+    }
+}
+
+impl std::error::Error for InvalidChannelMatrixError {}
+
+const SQRT_HALF: f32 = std::f32::consts::FRAC_1_SQRT_2;
+
+impl ChannelMatrix {
+    /// Create a custom matrix.
+    ///
+    /// `gains` contains `to_count` rows, one for each output channel, of
+    /// `from_count` gains, one for each input channel. Each output sample is
+    /// the sum of the input samples of the frame multiplied by their gain.
+    pub fn new(
+        from_count: u16,
+        to_count: u16,
+        gains: Vec<f32>,
+    ) -> Result<ChannelMatrix, InvalidChannelMatrixError> {
+        if from_count == 0
+            || to_count == 0
+            || gains.len() != from_count as usize * to_count as usize
+        {
+            return Err(InvalidChannelMatrixError {});
+        }
+        Ok(ChannelMatrix {
+            from_count,
+            to_count,
+            gains,
+        })
+    }
+
+    /// The standard matrix for converting between channel counts.
+    ///
+    /// Conversions between mono, stereo, quad and 5.1 follow the Web Audio
+    /// [up-mixing and down-mixing rules](https://developer.mozilla.org/en-US/docs/Web/API/Web_Audio_API/Basic_concepts_behind_Web_Audio_API#up-mixing_and_down-mixing).
+    /// 7.1 is down-mixed by combining the back and side channels into the 5.1
+    /// surround channels and up-mixed by playing the 5.1 surround channels on
+    /// the side channels. Any other combination uses
+    /// [discrete][ChannelMatrix::discrete].
+    ///
+    /// # Panics
+    ///
+    /// Panics if either count is 0.
+    pub fn standard(from_count: u16, to_count: u16) -> ChannelMatrix {
+        let mut matrix = ChannelMatrix {
+            from_count,
+            to_count,
+            gains: Vec::new(),
+        };
+        matrix.set_standard(from_count, to_count);
+        matrix
+    }
+
+    /// A matrix that copies each input channel to the output channel with the
+    /// same index. Extra output channels are silent and extra input channels
+    /// are dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either count is 0.
+    pub fn discrete(from_count: u16, to_count: u16) -> ChannelMatrix {
+        let mut gains = vec![0.0; from_count as usize * to_count as usize];
+        for channel in 0..from_count.min(to_count) as usize {
+            gains[channel * from_count as usize + channel] = 1.0;
+        }
+        ChannelMatrix::new(from_count, to_count, gains).expect("channel counts must not be 0")
+    }
+
+    /// The number of input channels.
+    pub fn from_count(&self) -> u16 {
+        self.from_count
+    }
+
+    /// The number of output channels.
+    pub fn to_count(&self) -> u16 {
+        self.to_count
+    }
+
+    /// The gain applied to input channel `from_channel` when mixed into
+    /// output channel `to_channel`.
+    pub fn gain(&self, to_channel: u16, from_channel: u16) -> f32 {
+        self.gains[to_channel as usize * self.from_count as usize + from_channel as usize]
+    }
+
+    /// Change self to the standard matrix reusing the buffer of the gains.
+    fn set_standard(&mut self, from_count: u16, to_count: u16) {
+        assert!(
+            from_count > 0 && to_count > 0,
+            "channel counts must not be 0"
+        );
+        self.from_count = from_count;
+        self.to_count = to_count;
+        self.gains.clear();
+        for to_channel in 0..to_count {
+            for from_channel in 0..from_count {
+                self.gains.push(standard_gain(
+                    from_count,
+                    to_count,
+                    to_channel,
+                    from_channel,
+                ));
+            }
+        }
+    }
+
+    /// Change self to a copy of `other` reusing the buffer of the gains.
+    fn set_to(&mut self, other: &ChannelMatrix) {
+        self.from_count = other.from_count;
+        self.to_count = other.to_count;
+        self.gains.clear();
+        self.gains.extend_from_slice(&other.gains);
+    }
+
+    /// Mix one frame of `input` into `output`.
+    fn apply(&self, input: &[i16], output: &mut [i16]) {
+        for (to_channel, out) in output.iter_mut().enumerate() {
+            let row = &self.gains[to_channel * self.from_count as usize..][..input.len()];
+            let sum: f32 = row
+                .iter()
+                .zip(input)
+                .map(|(gain, sample)| gain * *sample as f32)
+                .sum();
+            // `as` saturates at the bounds of i16.
+            *out = sum.round() as i16;
+        }
+    }
+}
+
+/// Gain from `from_channel` to `to_channel` of the standard matrix.
+fn standard_gain(from_count: u16, to_count: u16, to_channel: u16, from_channel: u16) -> f32 {
+    let known = |count: u16| matches!(count, 1 | 2 | 4 | 6 | 8);
+    if from_count == to_count || !known(from_count) || !known(to_count) {
+        return discrete_gain(to_channel, from_channel);
+    }
+    match (from_count, to_count) {
+        (8, _) => (0..6)
+            .map(|mid| {
+                standard_gain(6, to_count, to_channel, mid)
+                    * table_gain(SEVEN_ONE_TO_FIVE_ONE, 8, mid, from_channel)
+            })
+            .sum(),
+        (_, 8) => (0..6)
+            .map(|mid| {
+                table_gain(FIVE_ONE_TO_SEVEN_ONE, 6, to_channel, mid)
+                    * standard_gain(from_count, 6, mid, from_channel)
+            })
+            .sum(),
+        _ => web_audio_gain(from_count, to_count, to_channel, from_channel),
+    }
+}
+
+fn discrete_gain(to_channel: u16, from_channel: u16) -> f32 {
+    if to_channel == from_channel {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+/// Gain from a table of `from_count` gains per output channel.
+fn table_gain(gains: &[f32], from_count: u16, to_channel: u16, from_channel: u16) -> f32 {
+    gains[to_channel as usize * from_count as usize + from_channel as usize]
+}
+
+fn web_audio_gain(from_count: u16, to_count: u16, to_channel: u16, from_channel: u16) -> f32 {
+    #[rustfmt::skip]
+    let gains: &[f32] = match (from_count, to_count) {
+        (1, 2) => &[
+            1.0,
+            1.0,
+        ],
+        (1, 4) => &[
+            1.0,
+            1.0,
+            0.0,
+            0.0,
+        ],
+        (1, 6) => &[
+            0.0,
+            0.0,
+            1.0,
+            0.0,
+            0.0,
+            0.0,
+        ],
+        (2, 1) => &[
+            0.5, 0.5,
+        ],
+        (2, 4) | (2, 6) => return discrete_gain(to_channel, from_channel),
+        (4, 1) => &[
+            0.25, 0.25, 0.25, 0.25,
+        ],
+        (4, 2) => &[
+            0.5, 0.0, 0.5, 0.0,
+            0.0, 0.5, 0.0, 0.5,
+        ],
+        (4, 6) => &[
+            1.0, 0.0, 0.0, 0.0,
+            0.0, 1.0, 0.0, 0.0,
+            0.0, 0.0, 0.0, 0.0,
+            0.0, 0.0, 0.0, 0.0,
+            0.0, 0.0, 1.0, 0.0,
+            0.0, 0.0, 0.0, 1.0,
+        ],
+        (6, 1) => &[
+            SQRT_HALF, SQRT_HALF, 1.0, 0.0, 0.5, 0.5,
+        ],
+        (6, 2) => &[
+            1.0, 0.0, SQRT_HALF, 0.0, SQRT_HALF, 0.0,
+            0.0, 1.0, SQRT_HALF, 0.0, 0.0, SQRT_HALF,
+        ],
+        (6, 4) => &[
+            1.0, 0.0, SQRT_HALF, 0.0, 0.0, 0.0,
+            0.0, 1.0, SQRT_HALF, 0.0, 0.0, 0.0,
+            0.0, 0.0, 0.0, 0.0, 1.0, 0.0,
+            0.0, 0.0, 0.0, 0.0, 0.0, 1.0,
+        ],
+        _ => unreachable!("web_audio_gain called with unsupported counts"),
+    };
+    table_gain(gains, from_count, to_channel, from_channel)
+}
+
+#[rustfmt::skip]
+const SEVEN_ONE_TO_FIVE_ONE: &[f32] = &[
+    1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0,
+    0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0,
+    0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0,
+    0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0,
+    0.0, 0.0, 0.0, 0.0, SQRT_HALF, 0.0, SQRT_HALF, 0.0,
+    0.0, 0.0, 0.0, 0.0, 0.0, SQRT_HALF, 0.0, SQRT_HALF,
+];
+
+#[rustfmt::skip]
+const FIVE_ONE_TO_SEVEN_ONE: &[f32] = &[
+    1.0, 0.0, 0.0, 0.0, 0.0, 0.0,
+    0.0, 1.0, 0.0, 0.0, 0.0, 0.0,
+    0.0, 0.0, 1.0, 0.0, 0.0, 0.0,
+    0.0, 0.0, 0.0, 1.0, 0.0, 0.0,
+    0.0, 0.0, 0.0, 0.0, 0.0, 0.0,
+    0.0, 0.0, 0.0, 0.0, 0.0, 0.0,
+    0.0, 0.0, 0.0, 0.0, 1.0, 0.0,
+    0.0, 0.0, 0.0, 0.0, 0.0, 1.0,
+];
+
 impl<S> ChannelCountConverter<S>
 where
     S: Sound,
 {
-    /// Wrap `inner` such that it will output `to_count` channels.
+    /// Wrap `inner` such that it will output `to_count` channels using the
+    /// [standard][ChannelMatrix::standard] conversion.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `to_count` is 0.
     pub fn new(inner: S, to_count: u16) -> ChannelCountConverter<S> {
-        let converter_type = Self::get_type(inner.channel_count(), to_count);
+        assert!(to_count > 0, "to_count must not be 0");
+        Self::with_optional_matrix(inner, to_count, None)
+    }
 
-        ChannelCountConverter {
+    /// Wrap `inner` such that it will output `matrix.to_count()` channels
+    /// mixed using `matrix`.
+    ///
+    /// If the channel count of `inner` is not `matrix.from_count()` (possibly
+    /// after a MetadataChanged) the [standard][ChannelMatrix::standard]
+    /// conversion is used instead.
+    pub fn with_matrix(inner: S, matrix: ChannelMatrix) -> ChannelCountConverter<S> {
+        let to_count = matrix.to_count;
+        Self::with_optional_matrix(inner, to_count, Some(matrix))
+    }
+
+    fn with_optional_matrix(
+        inner: S,
+        to_count: u16,
+        custom_matrix: Option<ChannelMatrix>,
+    ) -> ChannelCountConverter<S> {
+        let max_count = MAX_STANDARD_CHANNEL_COUNT
+            .max(inner.channel_count() as usize)
+            .max(to_count as usize);
+        let mut converter = ChannelCountConverter {
             inner,
+            from_count: 0,
             to_count,
-            converter_type,
-        }
+            custom_matrix,
+            converter_type: ConverterType::PassThrough,
+            matrix: ChannelMatrix {
+                from_count: 0,
+                to_count: 0,
+                gains: Vec::with_capacity(max_count * max_count),
+            },
+            input: Vec::with_capacity(max_count),
+            output: Vec::with_capacity(max_count),
+            next_output_idx: 0,
+        };
+        converter.set_from_count(converter.inner.channel_count());
+        converter
     }
 
-    fn get_type(from_count: u16, to_count: u16) -> ConverterType {
-        if from_count == to_count {
-            ConverterType::PassThrough
-        } else if from_count == 1 && to_count == 2 {
-            ConverterType::MonoToStereo { last_sample: None }
-        } else if from_count == 2 && to_count == 1 {
-            ConverterType::StereoToMono
-        } else {
-            // Can implement more conversions like
-            // https://developer.mozilla.org/en-US/docs/Web/API/Web_Audio_API/Basic_concepts_behind_Web_Audio_API#up-mixing_and_down-mixing
-            todo!(
-                "ChannelCountConverter for {} to {} channels not implemented.",
-                from_count,
-                to_count
-            );
+    /// Choose the converter type for an inner sound with `from_count`
+    /// channels.
+    ///
+    /// Allocates only if a channel count is above
+    /// [MAX_STANDARD_CHANNEL_COUNT] or a custom matrix is larger.
+    fn set_from_count(&mut self, from_count: u16) {
+        // A sound should never have 0 channels but do not panic if one does.
+        let from_count = from_count.max(1);
+        if from_count == self.from_count {
+            // Only a partial frame is dropped.
+            self.next_output_idx = self.output.len();
+            return;
         }
+        self.from_count = from_count;
+        let to_count = self.to_count;
+        self.converter_type = match &self.custom_matrix {
+            Some(matrix) if matrix.from_count == from_count => {
+                self.matrix.set_to(matrix);
+                ConverterType::Matrix
+            }
+            _ if from_count == to_count => ConverterType::PassThrough,
+            _ if from_count == 1 && to_count == 2 => {
+                ConverterType::MonoToStereo { last_sample: None }
+            }
+            _ if from_count == 2 && to_count == 1 => ConverterType::StereoToMono,
+            _ => {
+                self.matrix.set_standard(from_count, to_count);
+                ConverterType::Matrix
+            }
+        };
+        self.input.clear();
+        self.input.resize(from_count as usize, 0);
+        self.output.clear();
+        self.output.resize(to_count as usize, 0);
+        self.next_output_idx = self.output.len();
     }
 
     // We could save the metadata of the inner Source and only return MetadataChange
     // if the metadata change is something we can't handle (i.e. a Rate Change).
     fn handle_possible_channel_count_change(&mut self, next: NextSample) {
         if let NextSample::MetadataChanged = next {
-            let from_count = self.inner.channel_count();
-            self.converter_type = Self::get_type(from_count, self.to_count);
+            self.set_from_count(self.inner.channel_count());
         }
     }
 
@@ -128,12 +481,151 @@ where
                 let avg = ((sample1 as i32 + sample2 as i32) / 2) as i16;
                 Ok(NextSample::Sample(avg))
             }
+            ConverterType::Matrix => {
+                if self.next_output_idx < self.output.len() {
+                    let sample = self.output[self.next_output_idx];
+                    self.next_output_idx += 1;
+                    return Ok(NextSample::Sample(sample));
+                }
+                for slot in self.input.iter_mut() {
+                    let next = self.inner.next_sample()?;
+                    match next {
+                        NextSample::Sample(s) => *slot = s,
+                        NextSample::MetadataChanged | NextSample::Paused | NextSample::Finished => {
+                            // Like StereoToMono, a partial frame is dropped.
+                            self.handle_possible_channel_count_change(next);
+                            return Ok(next);
+                        }
+                    }
+                }
+                self.matrix.apply(&self.input, &mut self.output);
+                self.next_output_idx = 1;
+                Ok(NextSample::Sample(self.output[0]))
+            }
+        }
+    }
+
+    fn next_samples(&mut self, buffer: &mut [i16]) -> Result<(usize, NextSample), crate::Error> {
+        match &mut self.converter_type {
+            ConverterType::PassThrough => {
+                let (num_read, next) = self.inner.next_samples(buffer)?;
+                self.handle_possible_channel_count_change(next);
+                Ok((num_read, next))
+            }
+            ConverterType::MonoToStereo { last_sample } => {
+                let mut num_written = 0;
+                if let Some(sample) = last_sample.take() {
+                    let Some(first) = buffer.first_mut() else {
+                        *last_sample = Some(sample);
+                        return Ok(filled(buffer));
+                    };
+                    *first = sample;
+                    num_written = 1;
+                }
+                let out = &mut buffer[num_written..];
+                let num_frames = out.len() / 2;
+                let (num_read, next) = self.inner.next_samples(&mut out[..num_frames])?;
+                // Duplicate in place starting from the end so no sample is
+                // overwritten before it is read.
+                for idx in (0..num_read).rev() {
+                    out[idx * 2 + 1] = out[idx];
+                    out[idx * 2] = out[idx];
+                }
+                num_written += num_read * 2;
+                if num_read < num_frames {
+                    self.handle_possible_channel_count_change(next);
+                    return Ok((num_written, next));
+                }
+                // There might be a single slot left over.
+                next_samples_one_by_one(self, &mut buffer[num_written..])
+                    .map(|(num_read, next)| (num_written + num_read, next))
+            }
+            ConverterType::StereoToMono => {
+                let mut num_written = 0;
+                while num_written < buffer.len() {
+                    let remaining = buffer.len() - num_written;
+                    if remaining == 1 {
+                        return next_samples_one_by_one(self, &mut buffer[num_written..])
+                            .map(|(num_read, next)| (num_written + num_read, next));
+                    }
+                    // Read two samples for every output sample into the
+                    // unfilled part of buffer and then average in place.
+                    let out = &mut buffer[num_written..num_written + remaining / 2 * 2];
+                    let (num_read, next) = self.inner.next_samples(out)?;
+                    let num_frames = num_read / 2;
+                    for idx in 0..num_frames {
+                        out[idx] = ((out[idx * 2] as i32 + out[idx * 2 + 1] as i32) / 2) as i16;
+                    }
+                    num_written += num_frames;
+                    if num_read < out.len() {
+                        // Like next_sample, a partial frame is dropped.
+                        self.handle_possible_channel_count_change(next);
+                        return Ok((num_written, next));
+                    }
+                }
+                Ok(filled(buffer))
+            }
+            ConverterType::Matrix => next_samples_one_by_one(self, buffer),
+        }
+    }
+
+    fn next_samples_f32(
+        &mut self,
+        buffer: &mut [f32],
+    ) -> Result<(usize, NextSample), crate::Error> {
+        match &mut self.converter_type {
+            ConverterType::PassThrough => {
+                let (num_read, next) = self.inner.next_samples_f32(buffer)?;
+                self.handle_possible_channel_count_change(next);
+                Ok((num_read, next))
+            }
+            ConverterType::MonoToStereo { last_sample } => {
+                let mut num_written = 0;
+                if let Some(sample) = last_sample.take() {
+                    let Some(first) = buffer.first_mut() else {
+                        *last_sample = Some(sample);
+                        return Ok(filled_f32(buffer));
+                    };
+                    *first = utils::i16_to_f32(sample);
+                    num_written = 1;
+                }
+                let out = &mut buffer[num_written..];
+                let num_frames = out.len() / 2;
+                let (num_read, next) = self.inner.next_samples_f32(&mut out[..num_frames])?;
+                for idx in (0..num_read).rev() {
+                    out[idx * 2 + 1] = out[idx];
+                    out[idx * 2] = out[idx];
+                }
+                num_written += num_read * 2;
+                if num_read < num_frames {
+                    self.handle_possible_channel_count_change(next);
+                    return Ok((num_written, next));
+                }
+                next_samples_f32_from_i16(self, &mut buffer[num_written..])
+                    .map(|(num_read, next)| (num_written + num_read, next))
+            }
+            // Mixing is done in i16.
+            ConverterType::StereoToMono | ConverterType::Matrix => {
+                next_samples_f32_from_i16(self, buffer)
+            }
         }
     }
 
     fn on_start_of_batch(&mut self) {
         self.inner.on_start_of_batch()
     }
+
+    fn as_seekable(&mut self) -> Option<&mut dyn Seekable> {
+        self.inner.as_seekable()
+    }
+
+    fn position(&self) -> Option<Duration> {
+        self.inner.position()
+    }
+
+    fn duration(&self) -> Option<Duration> {
+        self.inner.duration()
+    }
 }
 
 impl<S: Sound> Wrapper for ChannelCountConverter<S> {
@@ -151,3 +643,7 @@ impl<S: Sound> Wrapper for ChannelCountConverter<S> {
         self.inner
     }
 }
+
+#[cfg(test)]
+#[path = "./tests/channel_count_converter.rs"]
+mod tests;
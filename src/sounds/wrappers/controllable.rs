@@ -1,13 +1,26 @@
 use crate::sounds::wrappers::SetPaused;
 use crate::sounds::wrappers::SetVolume;
 use crate::Sound;
-use std::sync::mpsc;
+use crossbeam_queue::ArrayQueue;
+use std::convert::Infallible;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 
 use super::AddSound;
 use super::ClearSounds;
+use super::FadeOut;
+use super::Seekable;
+use super::SetPan;
+use super::SetPitch;
 use super::SetSpeed;
+use super::SetTempo;
 use super::Wrapper;
 
+/// The number of commands that can be queued by the [Controller]s of a
+/// [Controllable] created with [Controllable::new].
+pub const DEFAULT_COMMAND_CAPACITY: usize = 64;
+
 /// Wrap a Sound so that it can be controlled via a [Controller] even after it
 /// has been added to the Manager and/or started playing.
 ///
@@ -15,33 +28,114 @@ use super::Wrapper;
 /// returns Finished it will be converted to Paused by Controllable. Only after
 /// the controller has dropped and all sounds have played will Finished be
 /// returned;
-pub struct Controllable<S: Sound> {
+///
+/// At the start of each batch the position and duration of the inner sound are
+/// published so they can be read from the [Controller].
+///
+/// Commands are passed through a bounded lock-free queue so the audio thread
+/// never blocks or allocates to receive them. See
+/// [Controller::try_send_command] for what happens when the queue is full.
+///
+/// `C` is the type of the commands specific to the inner sound. The sounds of
+/// this crate use it to send their own commands without allocating. It is
+/// [Infallible] when there are none.
+pub struct Controllable<S: Sound, C = Infallible> {
     inner: S,
-    command_receiver: mpsc::Receiver<Command<S>>,
+    shared: Arc<Shared<S, C>>,
     finished: bool,
 }
 
+/// State shared between a [Controllable] and its [Controller]s.
+struct Shared<S, C> {
+    commands: ArrayQueue<Command<S, C>>,
+    /// Runs the commands of type `C` on the inner sound.
+    run_command: fn(&mut S, C),
+    /// The number of [Controller]s that exist.
+    controller_count: AtomicUsize,
+    dropped_commands: AtomicU64,
+    /// Values published by the [Controllable] for its [Controller]s to read
+    /// without blocking the audio thread.
+    position_micros: AtomicU64,
+    duration_micros: AtomicU64,
+}
+
+/// Stored in place of a duration in microseconds when the value is None.
+const UNKNOWN_MICROS: u64 = u64::MAX;
+
+fn to_micros(duration: Option<Duration>) -> u64 {
+    duration
+        .and_then(|d| u64::try_from(d.as_micros()).ok())
+        .map(|micros| micros.min(UNKNOWN_MICROS - 1))
+        .unwrap_or(UNKNOWN_MICROS)
+}
+
+fn from_micros(micros: u64) -> Option<Duration> {
+    (micros != UNKNOWN_MICROS).then(|| Duration::from_micros(micros))
+}
+
 impl<S> Controllable<S>
 where
     S: Sound,
 {
-    /// Wrap `inner` so it can be controlled.
+    /// Wrap `inner` so it can be controlled. Up to
+    /// [DEFAULT_COMMAND_CAPACITY] commands can be queued.
     pub fn new(inner: S) -> (Self, Controller<S>) {
-        let (command_sender, command_receiver) = mpsc::channel::<Command<S>>();
+        Self::with_command_capacity(inner, DEFAULT_COMMAND_CAPACITY)
+    }
+
+    /// Wrap `inner` so it can be controlled. Up to `capacity` commands can be
+    /// queued between batches.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is 0.
+    pub fn with_command_capacity(inner: S, capacity: usize) -> (Self, Controller<S>) {
+        Self::with_commands(inner, capacity, |_, command| match command {})
+    }
+}
+
+impl<S, C> Controllable<S, C>
+where
+    S: Sound,
+{
+    /// Wrap `inner` so it can be controlled and receive commands of type `C`
+    /// which are run with `run_command`. Up to `capacity` commands can be
+    /// queued between batches.
+    pub(crate) fn with_commands(
+        inner: S,
+        capacity: usize,
+        run_command: fn(&mut S, C),
+    ) -> (Self, Controller<S, C>) {
+        let shared = Arc::new(Shared {
+            commands: ArrayQueue::new(capacity),
+            run_command,
+            controller_count: AtomicUsize::new(1),
+            dropped_commands: AtomicU64::new(0),
+            position_micros: AtomicU64::new(to_micros(inner.position())),
+            duration_micros: AtomicU64::new(to_micros(inner.duration())),
+        });
         let controllable = Controllable {
             inner,
-            command_receiver,
+            shared: shared.clone(),
             finished: false,
         };
-        let controller = Controller { command_sender };
+        let controller = Controller { shared };
 
         (controllable, controller)
     }
+
+    /// Return Finished as soon as the inner sound finishes instead of only
+    /// after all [Controller]s have dropped.
+    pub(crate) fn finish_with_inner(mut self) -> Self {
+        self.finished = true;
+        self
+    }
 }
 
-impl<S> Sound for Controllable<S>
+impl<S, C> Sound for Controllable<S, C>
 where
     S: Sound,
+    C: Send,
 {
     fn channel_count(&self) -> u16 {
         self.inner.channel_count()
@@ -72,24 +166,68 @@ where
         }
     }
 
-    fn on_start_of_batch(&mut self) {
-        loop {
-            match self.command_receiver.try_recv() {
-                Ok(command) => command(&mut self.inner),
-                Err(mpsc::TryRecvError::Empty) => break,
-                Err(mpsc::TryRecvError::Disconnected) => {
-                    self.finished = true;
-                    break;
-                }
+    fn next_samples(
+        &mut self,
+        buffer: &mut [i16],
+    ) -> Result<(usize, crate::NextSample), crate::Error> {
+        let (num_read, next) = self.inner.next_samples(buffer)?;
+        match next {
+            crate::NextSample::Finished if !self.finished => {
+                Ok((num_read, crate::NextSample::Paused))
+            }
+            _ => Ok((num_read, next)),
+        }
+    }
+
+    fn next_samples_f32(
+        &mut self,
+        buffer: &mut [f32],
+    ) -> Result<(usize, crate::NextSample), crate::Error> {
+        let (num_read, next) = self.inner.next_samples_f32(buffer)?;
+        match next {
+            crate::NextSample::Finished if !self.finished => {
+                Ok((num_read, crate::NextSample::Paused))
             }
+            _ => Ok((num_read, next)),
+        }
+    }
+
+    fn on_start_of_batch(&mut self) {
+        // Checked before draining so commands sent by the last Controller
+        // before it dropped are still run.
+        let disconnected = self.shared.controller_count.load(Ordering::Acquire) == 0;
+        while let Some(command) = self.shared.commands.pop() {
+            command.run(&mut self.inner, self.shared.run_command);
+        }
+        if disconnected {
+            self.finished = true;
         }
         self.inner.on_start_of_batch();
+        self.shared
+            .position_micros
+            .store(to_micros(self.inner.position()), Ordering::Relaxed);
+        self.shared
+            .duration_micros
+            .store(to_micros(self.inner.duration()), Ordering::Relaxed);
+    }
+
+    fn as_seekable(&mut self) -> Option<&mut dyn Seekable> {
+        self.inner.as_seekable()
+    }
+
+    fn position(&self) -> Option<Duration> {
+        self.inner.position()
+    }
+
+    fn duration(&self) -> Option<Duration> {
+        self.inner.duration()
     }
 }
 
-impl<S> Wrapper for Controllable<S>
+impl<S, C> Wrapper for Controllable<S, C>
 where
     S: Sound,
+    C: Send,
 {
     type Inner = S;
 
@@ -106,26 +244,109 @@ where
     }
 }
 
-// TODO consider using Small Box for perf
-type Command<S> = Box<dyn FnOnce(&mut S) + Send>;
+/// A command run on the inner sound of a [Controllable] at the start of a
+/// batch.
+enum Command<S, C> {
+    /// A function and its arguments. Used by the built-in commands so that
+    /// sending them does not allocate.
+    Inline(fn(&mut S, CommandArgs), CommandArgs),
+    /// A command specific to the inner sound.
+    Typed(C),
+    /// A custom command from [Controller::send_command].
+    Boxed(Box<dyn FnOnce(&mut S) + Send>),
+}
+
+/// The arguments of an inline [Command]. Each command uses the fields it
+/// needs.
+#[derive(Default)]
+struct CommandArgs {
+    value: f32,
+    duration: Duration,
+    flag: bool,
+    sound: Option<Box<dyn Sound>>,
+}
+
+impl CommandArgs {
+    fn sound(sound: Box<dyn Sound>) -> Self {
+        CommandArgs {
+            sound: Some(sound),
+            ..Default::default()
+        }
+    }
+
+    fn value(value: f32) -> Self {
+        CommandArgs {
+            value,
+            ..Default::default()
+        }
+    }
+
+    fn duration(duration: Duration) -> Self {
+        CommandArgs {
+            duration,
+            ..Default::default()
+        }
+    }
+
+    fn value_over(value: f32, duration: Duration) -> Self {
+        CommandArgs {
+            value,
+            duration,
+            ..Default::default()
+        }
+    }
+}
+
+impl<S, C> Command<S, C> {
+    fn run(self, sound: &mut S, run_command: fn(&mut S, C)) {
+        match self {
+            Command::Inline(apply, args) => apply(sound, args),
+            Command::Typed(command) => run_command(sound, command),
+            Command::Boxed(command) => command(sound),
+        }
+    }
+}
+
+/// The error returned by [Controller::try_send_command] when the command queue
+/// of the [Controllable] is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommandQueueFull;
+
+impl std::fmt::Display for CommandQueueFull {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "the command queue of the controllable sound is full")
+    }
+}
+
+impl std::error::Error for CommandQueueFull {}
 
 /// The remote Controller for a Sound wrapped in a Controllable.
-pub struct Controller<S: Sound> {
-    command_sender: mpsc::Sender<Command<S>>,
+pub struct Controller<S: Sound, C = Infallible> {
+    shared: Arc<Shared<S, C>>,
 }
 
-impl<S> Clone for Controller<S>
+impl<S, C> Clone for Controller<S, C>
 where
     S: Sound,
 {
     fn clone(&self) -> Self {
+        self.shared.controller_count.fetch_add(1, Ordering::Relaxed);
         Self {
-            command_sender: self.command_sender.clone(),
+            shared: self.shared.clone(),
         }
     }
 }
 
-impl<S> Controller<S>
+impl<S, C> Drop for Controller<S, C>
+where
+    S: Sound,
+{
+    fn drop(&mut self) {
+        self.shared.controller_count.fetch_sub(1, Ordering::Release);
+    }
+}
+
+impl<S, C> Controller<S, C>
 where
     S: Sound,
 {
@@ -135,60 +356,234 @@ where
     /// sound of the controllable. Many but not all actions are available as
     /// trait functions on `Controller` but sending a command allows for full
     /// control.
-    pub fn send_command(&mut self, command: Command<S>) {
-        // Ignore the error since it only happens if the receiver
-        // has been dropped which is not expected after it has been
-        // sent to the manager.
-        let _ = self.command_sender.send(command);
+    ///
+    /// If the command queue is full the command is dropped and counted in
+    /// [dropped_commands][Controller::dropped_commands]. The same applies to
+    /// all other functions that send a command. Use
+    /// [try_send_command][Controller::try_send_command] to handle a full
+    /// queue.
+    pub fn send_command(&mut self, command: Box<dyn FnOnce(&mut S) + Send>) {
+        self.send(Command::Boxed(command));
+    }
+
+    /// Same as [send_command][Controller::send_command] but returns an error
+    /// instead of dropping the command if the command queue is full.
+    ///
+    /// The queue is emptied at the start of each batch so it is normally only
+    /// full if many commands are sent at once or the sound is not being
+    /// played.
+    pub fn try_send_command(
+        &mut self,
+        command: Box<dyn FnOnce(&mut S) + Send>,
+    ) -> Result<(), CommandQueueFull> {
+        self.shared
+            .commands
+            .push(Command::Boxed(command))
+            .map_err(|_| CommandQueueFull)
+    }
+
+    /// The number of commands dropped because the command queue was full.
+    pub fn dropped_commands(&self) -> u64 {
+        self.shared.dropped_commands.load(Ordering::Relaxed)
+    }
+
+    /// The [position][Sound::position] of the controllable sound at the start
+    /// of the most recent batch.
+    ///
+    /// This does not block the audio thread but can lag behind by up to one
+    /// batch of samples.
+    pub fn position(&self) -> Option<Duration> {
+        from_micros(self.shared.position_micros.load(Ordering::Relaxed))
+    }
+
+    /// The [duration][Sound::duration] of the controllable sound at the start
+    /// of the most recent batch.
+    pub fn duration(&self) -> Option<Duration> {
+        from_micros(self.shared.duration_micros.load(Ordering::Relaxed))
+    }
+
+    fn send(&mut self, command: Command<S, C>) {
+        if self.shared.commands.push(command).is_err() {
+            self.shared.dropped_commands.fetch_add(1, Ordering::Relaxed);
+            log::warn!("dropping command sent to controllable sound with a full command queue");
+        }
+    }
+
+    /// Send a command that runs `apply` with `args` without allocating.
+    fn send_inline(&mut self, apply: fn(&mut S, CommandArgs), args: CommandArgs) {
+        self.send(Command::Inline(apply, args));
+    }
+
+    /// Send a command specific to the inner sound without allocating.
+    pub(crate) fn send_typed(&mut self, command: C) {
+        self.send(Command::Typed(command));
+    }
+
+    /// Same as [send_typed][Controller::send_typed] but returns an error
+    /// instead of dropping the command if the command queue is full.
+    pub(crate) fn try_send_typed(&mut self, command: C) -> Result<(), CommandQueueFull> {
+        self.shared
+            .commands
+            .push(Command::Typed(command))
+            .map_err(|_| CommandQueueFull)
     }
 }
 
-impl<S> Controller<S>
+impl<S, C> Controller<S, C>
 where
     S: Sound + AddSound,
 {
     /// Add `sound` to the sound container.
     pub fn add(&mut self, sound: Box<dyn Sound>) {
-        self.send_command(Box::new(|s: &mut S| s.add(sound)));
+        self.send_inline(
+            |s, args| {
+                if let Some(sound) = args.sound {
+                    s.add(sound)
+                }
+            },
+            CommandArgs::sound(sound),
+        );
     }
 }
 
-impl<S> Controller<S>
+impl<S, C> Controller<S, C>
 where
     S: Sound + ClearSounds,
 {
     /// Clear all sounds currently playing or scheduled to play.
     pub fn clear(&mut self) {
-        self.send_command(Box::new(|s: &mut S| s.clear()));
+        self.send_inline(|s, _| s.clear(), CommandArgs::default());
     }
 }
 
-impl<S> Controller<S>
+impl<S, C> Controller<S, C>
 where
     S: Sound + SetPaused,
 {
     /// Pause or unpause the controllable sound.
     pub fn set_paused(&mut self, paused: bool) {
-        self.send_command(Box::new(move |s: &mut S| s.set_paused(paused)));
+        let args = CommandArgs {
+            flag: paused,
+            ..Default::default()
+        };
+        self.send_inline(|s, args| s.set_paused(args.flag), args);
     }
 }
 
-impl<S> Controller<S>
+impl<S, C> Controller<S, C>
 where
     S: Sound + SetSpeed,
 {
     /// Set the playback speed of the controllable sound.
     pub fn set_speed(&mut self, speed: f32) {
-        self.send_command(Box::new(move |s: &mut S| s.set_speed(speed)));
+        self.send_inline(|s, args| s.set_speed(args.value), CommandArgs::value(speed));
+    }
+
+    /// Change the playback speed of the controllable sound gradually over
+    /// `duration`.
+    pub fn set_speed_over(&mut self, speed: f32, duration: Duration) {
+        self.send_inline(
+            |s, args| s.set_speed_over(args.value, args.duration),
+            CommandArgs::value_over(speed, duration),
+        );
     }
 }
 
-impl<S> Controller<S>
+impl<S, C> Controller<S, C>
+where
+    S: Sound + FadeOut,
+{
+    /// Fade the controllable sound out over `duration` and then finish it.
+    pub fn fade_out(&mut self, duration: Duration) {
+        self.send_inline(
+            |s, args| s.fade_out(args.duration),
+            CommandArgs::duration(duration),
+        );
+    }
+}
+
+impl<S, C> Controller<S, C>
+where
+    S: Sound + SetPan,
+{
+    /// Set the stereo position of the controllable sound. -1.0 is fully left
+    /// and 1.0 is fully right.
+    pub fn set_pan(&mut self, pan: f32) {
+        self.send_inline(|s, args| s.set_pan(args.value), CommandArgs::value(pan));
+    }
+}
+
+impl<S, C> Controller<S, C>
+where
+    S: Sound + SetPitch,
+{
+    /// Set the pitch of the controllable sound without changing its speed.
+    pub fn set_pitch(&mut self, pitch: f32) {
+        self.send_inline(|s, args| s.set_pitch(args.value), CommandArgs::value(pitch));
+    }
+
+    /// Set the pitch of the controllable sound in semitones without changing
+    /// its speed.
+    pub fn set_pitch_semitones(&mut self, semitones: f32) {
+        self.send_inline(
+            |s, args| s.set_pitch_semitones(args.value),
+            CommandArgs::value(semitones),
+        );
+    }
+}
+
+impl<S, C> Controller<S, C>
+where
+    S: Sound + SetTempo,
+{
+    /// Set the tempo of the controllable sound without changing its pitch.
+    pub fn set_tempo(&mut self, tempo: f32) {
+        self.send_inline(|s, args| s.set_tempo(args.value), CommandArgs::value(tempo));
+    }
+}
+
+impl<S, C> Controller<S, C>
 where
     S: Sound + SetVolume,
 {
     /// Set the volume of the controllable sound.
     pub fn set_volume(&mut self, volume: f32) {
-        self.send_command(Box::new(move |s: &mut S| s.set_volume(volume)));
+        self.send_inline(
+            |s, args| s.set_volume(args.value),
+            CommandArgs::value(volume),
+        );
+    }
+
+    /// Change the volume of the controllable sound gradually over `duration`
+    /// to avoid clicks.
+    pub fn set_volume_over(&mut self, volume: f32, duration: Duration) {
+        self.send_inline(
+            |s, args| s.set_volume_over(args.value, args.duration),
+            CommandArgs::value_over(volume, duration),
+        );
     }
 }
+
+impl<S, C> Controller<S, C>
+where
+    S: Sound + Seekable,
+{
+    /// Jump to `position` in the controllable sound.
+    ///
+    /// Since the seek happens later on the audio thread, an error while
+    /// seeking is logged instead of being returned.
+    pub fn seek(&mut self, position: Duration) {
+        self.send_inline(
+            |s, args| {
+                if let Err(e) = s.seek(args.duration) {
+                    log::error!("error seeking controllable sound: {}", e);
+                }
+            },
+            CommandArgs::duration(position),
+        );
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/controllable.rs"]
+mod tests;
@@ -0,0 +1,317 @@
+use std::time::Duration;
+
+use crate::{utils, NextSample, Sound};
+
+use super::{Seekable, SetPan, SetPaused, SetPitch, SetSpeed, SetTempo, SetVolume};
+
+/// A sound that can be faded out and then finished.
+pub trait FadeOut {
+    /// Fade the volume to silence over `duration` then finish the sound.
+    ///
+    /// A zero `duration` finishes the sound at the end of the current frame.
+    fn fade_out(&mut self, duration: Duration);
+}
+
+/// A wrapper that applies a gain envelope to the inner sound to avoid clicks
+/// when starting or stopping.
+///
+/// Usually created with [Sound::fade_in], [Sound::fade_out_after] or
+/// [Sound::fadable]. Gain changes linearly once per frame. Any time the inner
+/// sound is paused does not count against the durations.
+pub struct Fade<S: Sound> {
+    inner: S,
+    gain: f32,
+    /// Gain change per frame while ramping.
+    gain_step: f32,
+    /// The gain once ramping completes.
+    target_gain: f32,
+    ramp_frames_remaining: u64,
+    /// Frames until the scheduled fade out starts.
+    fade_out_in_frames: Option<u64>,
+    fade_out_frames: u64,
+    /// Finish once the gain reaches the target (silence).
+    finish_after_ramp: bool,
+    finished: bool,
+    next_channel_idx: u16,
+    current_sample_rate: u32,
+}
+
+impl<S> Fade<S>
+where
+    S: Sound,
+{
+    /// Wrap `inner` without an envelope. Use [FadeOut::fade_out] to fade out
+    /// later.
+    pub fn new(inner: S) -> Self {
+        let current_sample_rate = inner.sample_rate();
+        Fade {
+            inner,
+            gain: 1.0,
+            gain_step: 0.0,
+            target_gain: 1.0,
+            ramp_frames_remaining: 0,
+            fade_out_in_frames: None,
+            fade_out_frames: 0,
+            finish_after_ramp: false,
+            finished: false,
+            next_channel_idx: 0,
+            current_sample_rate,
+        }
+    }
+
+    /// Wrap `inner` and fade in from silence over `duration`.
+    pub fn new_fade_in(inner: S, duration: Duration) -> Self {
+        let mut fade = Fade::new(inner);
+        fade.gain = 0.0;
+        fade.ramp_to(1.0, duration);
+        fade
+    }
+
+    /// Wrap `inner` and, once `after` has been played, fade out over
+    /// `duration` and then finish.
+    pub fn new_fade_out_after(inner: S, after: Duration, duration: Duration) -> Self {
+        let mut fade = Fade::new(inner);
+        fade.schedule_fade_out(after, duration);
+        fade
+    }
+
+    /// Start fading out over `duration` once `after` has been played from
+    /// now, then finish. Replaces any previously scheduled fade out.
+    pub fn schedule_fade_out(&mut self, after: Duration, duration: Duration) {
+        self.fade_out_in_frames = Some(self.num_frames(after));
+        self.fade_out_frames = self.num_frames(duration);
+    }
+
+    /// The current gain between 0.0 and 1.0.
+    pub fn gain(&self) -> f32 {
+        self.gain
+    }
+
+    /// Get a reference to the wrapped inner Sound.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the wrapped inner Sound.
+    pub fn inner_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    /// Unwrap and return the previously wrapped Sound.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    fn num_frames(&self, duration: Duration) -> u64 {
+        utils::duration_to_num_samples(duration, 1, self.current_sample_rate)
+    }
+
+    fn ramp_to(&mut self, target_gain: f32, duration: Duration) {
+        let num_frames = self.num_frames(duration);
+        self.target_gain = target_gain;
+        self.ramp_frames_remaining = num_frames;
+        if num_frames == 0 {
+            self.gain = target_gain;
+            self.gain_step = 0.0;
+        } else {
+            self.gain_step = (target_gain - self.gain) / num_frames as f32;
+        }
+    }
+
+    fn start_fade_out(&mut self, num_frames: u64) {
+        self.fade_out_in_frames = None;
+        self.finish_after_ramp = true;
+        self.target_gain = 0.0;
+        self.ramp_frames_remaining = num_frames;
+        self.gain_step = -self.gain / num_frames.max(1) as f32;
+        if num_frames == 0 {
+            self.finished = true;
+        }
+    }
+
+    /// Advance the envelope by one frame.
+    fn on_end_of_frame(&mut self) {
+        if self.ramp_frames_remaining > 0 {
+            self.ramp_frames_remaining -= 1;
+            self.gain += self.gain_step;
+            if self.ramp_frames_remaining == 0 {
+                self.gain = self.target_gain;
+                if self.finish_after_ramp {
+                    self.finished = true;
+                }
+            }
+        }
+        if let Some(frames) = &mut self.fade_out_in_frames {
+            if *frames > 0 {
+                *frames -= 1;
+            }
+            if *frames == 0 {
+                self.start_fade_out(self.fade_out_frames);
+            }
+        }
+    }
+
+    /// Convert the frame counts to a new sample rate.
+    fn on_sample_rate_changed(&mut self) {
+        let old_rate = self.current_sample_rate;
+        let new_rate = self.inner.sample_rate();
+        if old_rate == new_rate {
+            return;
+        }
+        let convert = |frames: u64| utils::convert_num_samples(frames, 1, old_rate, 1, new_rate);
+        if self.ramp_frames_remaining > 0 {
+            let frames = convert(self.ramp_frames_remaining).max(1);
+            self.gain_step = (self.target_gain - self.gain) / frames as f32;
+            self.ramp_frames_remaining = frames;
+        }
+        self.fade_out_in_frames = self.fade_out_in_frames.map(convert);
+        self.fade_out_frames = convert(self.fade_out_frames);
+        self.current_sample_rate = new_rate;
+    }
+}
+
+impl<S> Sound for Fade<S>
+where
+    S: Sound,
+{
+    fn channel_count(&self) -> u16 {
+        self.inner.channel_count()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        if self.finished && self.next_channel_idx == 0 {
+            return Ok(NextSample::Finished);
+        }
+        let next = self.inner.next_sample()?;
+        match next {
+            NextSample::Sample(s) => {
+                self.next_channel_idx += 1;
+                if self.next_channel_idx >= self.inner.channel_count() {
+                    self.next_channel_idx = 0;
+                }
+                let gain = self.gain;
+                if self.next_channel_idx == 0 {
+                    self.on_end_of_frame();
+                }
+                if gain == 1.0 {
+                    return Ok(NextSample::Sample(s));
+                }
+                Ok(NextSample::Sample((s as f32 * gain).round() as i16))
+            }
+            NextSample::MetadataChanged => {
+                self.next_channel_idx = 0;
+                self.on_sample_rate_changed();
+                Ok(next)
+            }
+            NextSample::Paused | NextSample::Finished => {
+                self.next_channel_idx = 0;
+                Ok(next)
+            }
+        }
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.inner.on_start_of_batch()
+    }
+
+    fn as_seekable(&mut self) -> Option<&mut dyn Seekable> {
+        self.inner.as_seekable()
+    }
+
+    fn position(&self) -> Option<Duration> {
+        self.inner.position()
+    }
+
+    fn duration(&self) -> Option<Duration> {
+        self.inner.duration()
+    }
+}
+
+impl<S> FadeOut for Fade<S>
+where
+    S: Sound,
+{
+    fn fade_out(&mut self, duration: Duration) {
+        let num_frames = self.num_frames(duration);
+        self.start_fade_out(num_frames);
+    }
+}
+
+impl<S> SetPaused for Fade<S>
+where
+    S: Sound + SetPaused,
+{
+    fn set_paused(&mut self, paused: bool) {
+        self.inner.set_paused(paused)
+    }
+}
+
+impl<S> SetVolume for Fade<S>
+where
+    S: Sound + SetVolume,
+{
+    fn set_volume(&mut self, multiplier: f32) {
+        self.inner.set_volume(multiplier)
+    }
+
+    fn set_volume_over(&mut self, multiplier: f32, duration: Duration) {
+        self.inner.set_volume_over(multiplier, duration)
+    }
+}
+
+impl<S> SetSpeed for Fade<S>
+where
+    S: Sound + SetSpeed,
+{
+    fn set_speed(&mut self, multiplier: f32) {
+        self.inner.set_speed(multiplier)
+    }
+
+    fn set_speed_over(&mut self, multiplier: f32, duration: Duration) {
+        self.inner.set_speed_over(multiplier, duration)
+    }
+}
+
+impl<S> SetTempo for Fade<S>
+where
+    S: Sound + SetTempo,
+{
+    fn set_tempo(&mut self, multiplier: f32) {
+        self.inner.set_tempo(multiplier)
+    }
+}
+
+impl<S> SetPitch for Fade<S>
+where
+    S: Sound + SetPitch,
+{
+    fn set_pitch(&mut self, multiplier: f32) {
+        self.inner.set_pitch(multiplier)
+    }
+}
+
+impl<S> SetPan for Fade<S>
+where
+    S: Sound + SetPan,
+{
+    fn set_pan(&mut self, pan: f32) {
+        self.inner.set_pan(pan)
+    }
+}
+
+impl<S> Seekable for Fade<S>
+where
+    S: Sound + Seekable,
+{
+    fn seek(&mut self, position: std::time::Duration) -> Result<(), crate::Error> {
+        self.inner.seek(position)
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/fade.rs"]
+mod tests;
@@ -2,7 +2,10 @@ use std::time::Duration;
 
 use crate::Sound;
 
-use super::Wrapper;
+use super::{
+    AddSound, ClearSounds, FadeOut, Seekable, SetPan, SetPaused, SetPitch, SetSpeed, SetTempo,
+    SetVolume,
+};
 
 /// Play the  first part of an inner Sound measured in seconds then stop even
 /// if the inner sound still has samples remaining. Finishes early if the inner
@@ -100,6 +103,33 @@ where
     fn on_start_of_batch(&mut self) {
         self.inner.on_start_of_batch()
     }
+
+    fn as_seekable(&mut self) -> Option<&mut dyn Seekable> {
+        // Seeking through self keeps the remaining samples in sync.
+        self.inner.as_seekable()?;
+        Some(self)
+    }
+
+    /// Returns the position of the inner sound if known, otherwise how much
+    /// of `duration` has been played.
+    fn position(&self) -> Option<Duration> {
+        self.inner.position().or_else(|| {
+            let remaining = crate::utils::num_samples_to_duration(
+                self.samples_remaining,
+                self.current_channel_count,
+                self.current_sample_rate,
+            );
+            Some(self.total_duration.saturating_sub(remaining))
+        })
+    }
+
+    /// Returns the shorter of `duration` and the inner sound's duration.
+    fn duration(&self) -> Option<Duration> {
+        Some(match self.inner.duration() {
+            Some(inner_duration) => inner_duration.min(self.total_duration),
+            None => self.total_duration,
+        })
+    }
 }
 
 pub fn num_samples(duration: Duration, num_channels: u16, num_samples: u32) -> u64 {
@@ -108,19 +138,114 @@ pub fn num_samples(duration: Duration, num_channels: u16, num_samples: u32) -> u
     micros * num_channels as u64 * num_samples as u64 / MICROS_PER_SEC
 }
 
-impl<S: Sound> Wrapper for FinishAfter<S> {
-    type Inner = S;
+impl<S> Seekable for FinishAfter<S>
+where
+    S: Sound,
+{
+    /// Seek the inner sound if it supports seeking (see
+    /// [Sound::as_seekable]) and still finish `duration` after the start of
+    /// the inner sound. Otherwise an IoError of
+    /// [ErrorKind::Unsupported][std::io::ErrorKind::Unsupported] is returned.
+    fn seek(&mut self, position: Duration) -> Result<(), crate::Error> {
+        match self.inner.as_seekable() {
+            Some(seekable) => seekable.seek(position)?,
+            None => return Err(std::io::Error::from(std::io::ErrorKind::Unsupported).into()),
+        }
+        self.samples_remaining = num_samples(
+            self.total_duration.saturating_sub(position),
+            self.current_channel_count,
+            self.current_sample_rate,
+        );
+        Ok(())
+    }
+}
 
-    fn inner(&self) -> &Self::Inner {
-        &self.inner
+impl<S> SetPaused for FinishAfter<S>
+where
+    S: Sound + SetPaused,
+{
+    fn set_paused(&mut self, paused: bool) {
+        self.inner.set_paused(paused)
     }
+}
 
-    fn inner_mut(&mut self) -> &mut Self::Inner {
-        &mut self.inner
+impl<S> SetVolume for FinishAfter<S>
+where
+    S: Sound + SetVolume,
+{
+    fn set_volume(&mut self, multiplier: f32) {
+        self.inner.set_volume(multiplier)
     }
 
-    fn into_inner(self) -> Self::Inner {
-        self.inner
+    fn set_volume_over(&mut self, multiplier: f32, duration: Duration) {
+        self.inner.set_volume_over(multiplier, duration)
+    }
+}
+
+impl<S> SetSpeed for FinishAfter<S>
+where
+    S: Sound + SetSpeed,
+{
+    fn set_speed(&mut self, multiplier: f32) {
+        self.inner.set_speed(multiplier)
+    }
+
+    fn set_speed_over(&mut self, multiplier: f32, duration: Duration) {
+        self.inner.set_speed_over(multiplier, duration)
+    }
+}
+
+impl<S> SetTempo for FinishAfter<S>
+where
+    S: Sound + SetTempo,
+{
+    fn set_tempo(&mut self, multiplier: f32) {
+        self.inner.set_tempo(multiplier)
+    }
+}
+
+impl<S> SetPitch for FinishAfter<S>
+where
+    S: Sound + SetPitch,
+{
+    fn set_pitch(&mut self, multiplier: f32) {
+        self.inner.set_pitch(multiplier)
+    }
+}
+
+impl<S> FadeOut for FinishAfter<S>
+where
+    S: Sound + FadeOut,
+{
+    fn fade_out(&mut self, duration: Duration) {
+        self.inner.fade_out(duration)
+    }
+}
+
+impl<S> SetPan for FinishAfter<S>
+where
+    S: Sound + SetPan,
+{
+    fn set_pan(&mut self, pan: f32) {
+        self.inner.set_pan(pan)
+    }
+}
+
+impl<S> AddSound for FinishAfter<S>
+where
+    S: Sound + AddSound,
+{
+    fn add(&mut self, sound: Box<dyn Sound>) {
+        self.inner.add(sound)
+    }
+}
+
+impl<S> ClearSounds for FinishAfter<S>
+where
+    S: Sound + ClearSounds,
+{
+    fn clear(&mut self) {
+        self.inner.clear()
     }
 }
 
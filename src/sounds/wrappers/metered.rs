@@ -0,0 +1,244 @@
+use std::sync::atomic::{AtomicU16, AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::manager::BackendSource;
+use crate::{NextSample, Sound};
+
+use super::Seekable;
+use super::Wrapper;
+
+/// The number of channels that are metered. Later channels are ignored.
+pub const MAX_METERED_CHANNELS: usize = 8;
+
+/// A wrapper that measures the peak and RMS level of each channel of the
+/// inner sound over each batch so they can be read from a [MeterHandle] on
+/// another thread, e.g. to draw a VU meter.
+///
+/// Levels are published at the start of each batch for the samples of the
+/// previous batch. Levels are linear where 1.0 is full scale. f32 samples
+/// above full scale are measured as they are so levels can exceed 1.0.
+///
+/// Since it is also a [BackendSource] when the inner sound is, a
+/// [Renderer][crate::manager::Renderer] can be wrapped to meter the master
+/// output.
+pub struct Metered<S: Sound> {
+    inner: S,
+    published: Arc<PublishedLevels>,
+    next_channel_idx: usize,
+    peaks: [f32; MAX_METERED_CHANNELS],
+    sums_of_squares: [f32; MAX_METERED_CHANNELS],
+    num_frames: u32,
+}
+
+/// Levels written by [Metered] and read by [MeterHandle]. f32 values are
+/// stored as their bits.
+struct PublishedLevels {
+    channel_count: AtomicU16,
+    peaks: [AtomicU32; MAX_METERED_CHANNELS],
+    rms: [AtomicU32; MAX_METERED_CHANNELS],
+}
+
+impl<S> Metered<S>
+where
+    S: Sound,
+{
+    /// Wrap `inner` and return a handle to read its levels.
+    pub fn new(inner: S) -> (Self, MeterHandle) {
+        let published = Arc::new(PublishedLevels {
+            channel_count: AtomicU16::new(inner.channel_count()),
+            peaks: Default::default(),
+            rms: Default::default(),
+        });
+        let metered = Metered {
+            inner,
+            published: published.clone(),
+            next_channel_idx: 0,
+            peaks: [0.0; MAX_METERED_CHANNELS],
+            sums_of_squares: [0.0; MAX_METERED_CHANNELS],
+            num_frames: 0,
+        };
+        (metered, MeterHandle { published })
+    }
+
+    /// Measure `sample` where 1.0 is full scale.
+    fn measure(&mut self, sample: f32) {
+        let channel_idx = self.next_channel_idx;
+        if let Some(peak) = self.peaks.get_mut(channel_idx) {
+            *peak = peak.max(sample.abs());
+            self.sums_of_squares[channel_idx] += sample * sample;
+        }
+        self.next_channel_idx += 1;
+        if self.next_channel_idx >= self.inner.channel_count() as usize {
+            self.next_channel_idx = 0;
+            self.num_frames += 1;
+        }
+    }
+
+    fn on_next(&mut self, next: NextSample) {
+        match next {
+            NextSample::Sample(s) => self.measure(s as f32 / i16::MAX as f32),
+            NextSample::MetadataChanged | NextSample::Paused | NextSample::Finished => {
+                self.next_channel_idx = 0
+            }
+        }
+    }
+
+    /// Publish the levels measured since the last call and start measuring
+    /// again.
+    fn publish(&mut self) {
+        let published = &self.published;
+        published
+            .channel_count
+            .store(self.inner.channel_count(), Ordering::Relaxed);
+        let num_frames = self.num_frames.max(1) as f32;
+        for idx in 0..MAX_METERED_CHANNELS {
+            let rms = (self.sums_of_squares[idx] / num_frames).sqrt();
+            published.peaks[idx].store(self.peaks[idx].to_bits(), Ordering::Relaxed);
+            published.rms[idx].store(rms.to_bits(), Ordering::Relaxed);
+        }
+        self.peaks = [0.0; MAX_METERED_CHANNELS];
+        self.sums_of_squares = [0.0; MAX_METERED_CHANNELS];
+        self.num_frames = 0;
+    }
+}
+
+impl<S> Sound for Metered<S>
+where
+    S: Sound,
+{
+    fn channel_count(&self) -> u16 {
+        self.inner.channel_count()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        let next = self.inner.next_sample()?;
+        self.on_next(next);
+        Ok(next)
+    }
+
+    fn next_samples(&mut self, buffer: &mut [i16]) -> Result<(usize, NextSample), crate::Error> {
+        let (num_read, next) = self.inner.next_samples(buffer)?;
+        for sample in &buffer[..num_read] {
+            self.measure(*sample as f32 / i16::MAX as f32);
+        }
+        if !matches!(next, NextSample::Sample(_)) {
+            self.on_next(next);
+        }
+        Ok((num_read, next))
+    }
+
+    fn next_samples_f32(
+        &mut self,
+        buffer: &mut [f32],
+    ) -> Result<(usize, NextSample), crate::Error> {
+        let (num_read, next) = self.inner.next_samples_f32(buffer)?;
+        for sample in &buffer[..num_read] {
+            self.measure(*sample);
+        }
+        if !matches!(next, NextSample::Sample(_)) {
+            self.on_next(next);
+        }
+        Ok((num_read, next))
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.publish();
+        self.inner.on_start_of_batch()
+    }
+
+    fn as_seekable(&mut self) -> Option<&mut dyn Seekable> {
+        self.inner.as_seekable()
+    }
+
+    fn position(&self) -> Option<Duration> {
+        self.inner.position()
+    }
+
+    fn duration(&self) -> Option<Duration> {
+        self.inner.duration()
+    }
+}
+
+impl<S> BackendSource for Metered<S>
+where
+    S: BackendSource,
+{
+    fn set_output_channel_count_and_sample_rate(
+        &mut self,
+        output_channel_count: u16,
+        output_sample_rate: u32,
+    ) {
+        self.inner
+            .set_output_channel_count_and_sample_rate(output_channel_count, output_sample_rate);
+    }
+}
+
+impl<S> Wrapper for Metered<S>
+where
+    S: Sound,
+{
+    type Inner = S;
+
+    fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    fn inner_mut(&mut self) -> &mut Self::Inner {
+        &mut self.inner
+    }
+
+    fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+/// Reads the levels measured by a [Metered] sound without blocking the audio
+/// thread.
+#[derive(Clone)]
+pub struct MeterHandle {
+    published: Arc<PublishedLevels>,
+}
+
+impl MeterHandle {
+    /// The channel count of the metered sound at the start of the most recent
+    /// batch.
+    pub fn channel_count(&self) -> u16 {
+        self.published.channel_count.load(Ordering::Relaxed)
+    }
+
+    /// The largest absolute sample of `channel_idx` in the most recent batch.
+    /// 0.0 if the channel does not exist.
+    pub fn peak(&self, channel_idx: usize) -> f32 {
+        self.load(&self.published.peaks, channel_idx)
+    }
+
+    /// The root mean square of the samples of `channel_idx` in the most
+    /// recent batch. 0.0 if the channel does not exist.
+    pub fn rms(&self, channel_idx: usize) -> f32 {
+        self.load(&self.published.rms, channel_idx)
+    }
+
+    fn load(&self, levels: &[AtomicU32; MAX_METERED_CHANNELS], channel_idx: usize) -> f32 {
+        if channel_idx >= self.channel_count() as usize {
+            return 0.0;
+        }
+        levels
+            .get(channel_idx)
+            .map_or(0.0, |level| f32::from_bits(level.load(Ordering::Relaxed)))
+    }
+}
+
+impl std::fmt::Debug for MeterHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MeterHandle").finish()
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/metered.rs"]
+mod tests;
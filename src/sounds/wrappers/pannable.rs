@@ -0,0 +1,253 @@
+use std::time::Duration;
+
+use crate::{NextSample, Sound};
+
+use super::{FadeOut, Seekable, SetPaused, SetPitch, SetSpeed, SetTempo, SetVolume};
+
+/// A sound that can be placed between the left and right speakers.
+pub trait SetPan {
+    /// Change the stereo position.
+    ///
+    /// -1.0 is fully left, 0.0 is the center and 1.0 is fully right. Values
+    /// outside of that range are clamped.
+    fn set_pan(&mut self, pan: f32);
+}
+
+/// A wrapper that places the inner sound between the left and right speakers.
+///
+/// Mono sounds are upmixed to stereo using a constant-power pan law so the
+/// loudness stays the same as the sound moves (each side is 3dB quieter
+/// than the mono sound when centered). Stereo sounds are balanced with the
+/// same law scaled so that the center leaves the sound unchanged. For sounds
+/// with more than two channels only the first two (front left and right) are
+/// balanced.
+///
+/// Pan changes take effect at the start of the next frame.
+pub struct Pannable<S: Sound> {
+    inner: S,
+    pan: f32,
+    /// The gains for the left and right channels of the current frame.
+    gains: [f32; 2],
+    pan_changed: bool,
+    next_channel_idx: u16,
+    /// The last mono sample which is returned again for the right channel.
+    mono_sample: i16,
+}
+
+impl<S> Pannable<S>
+where
+    S: Sound,
+{
+    /// Wrap `inner` such that it can be panned. Starts centered.
+    pub fn new(inner: S) -> Self {
+        Self::new_with_pan(inner, 0.0)
+    }
+
+    /// Wrap `inner` such that it can be panned and set an initial pan.
+    pub fn new_with_pan(inner: S, pan: f32) -> Self {
+        let mut pannable = Pannable {
+            inner,
+            pan: 0.0,
+            gains: [1.0; 2],
+            pan_changed: false,
+            next_channel_idx: 0,
+            mono_sample: 0,
+        };
+        pannable.set_pan(pan);
+        pannable.update_gains();
+        pannable
+    }
+
+    /// Return the current pan. 0.0 is the center.
+    pub fn pan(&self) -> f32 {
+        self.pan
+    }
+
+    /// Get a reference to the wrapped inner Sound.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the wrapped inner Sound.
+    pub fn inner_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    /// Unwrap and return the previously wrapped Sound.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    fn update_gains(&mut self) {
+        self.pan_changed = false;
+        let angle = (self.pan + 1.0) * std::f32::consts::FRAC_PI_4;
+        let (right, left) = angle.sin_cos();
+        self.gains = if self.inner.channel_count() == 1 {
+            [left, right]
+        } else {
+            [
+                (left * std::f32::consts::SQRT_2).min(1.0),
+                (right * std::f32::consts::SQRT_2).min(1.0),
+            ]
+        };
+    }
+
+    fn apply_gain(&self, sample: i16, channel_idx: u16) -> i16 {
+        match self.gains.get(channel_idx as usize) {
+            // `as` saturates at the bounds of i16.
+            Some(gain) => (sample as f32 * gain).round() as i16,
+            None => sample,
+        }
+    }
+}
+
+impl<S> Sound for Pannable<S>
+where
+    S: Sound,
+{
+    fn channel_count(&self) -> u16 {
+        self.inner.channel_count().max(2)
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        let channel_idx = self.next_channel_idx;
+        if channel_idx == 0 && self.pan_changed {
+            self.update_gains();
+        }
+        let inner_channel_count = self.inner.channel_count();
+        let sample = if inner_channel_count == 1 && channel_idx == 1 {
+            self.mono_sample
+        } else {
+            match self.inner.next_sample()? {
+                NextSample::Sample(s) => s,
+                NextSample::MetadataChanged => {
+                    self.next_channel_idx = 0;
+                    // The channel count may have changed.
+                    self.update_gains();
+                    return Ok(NextSample::MetadataChanged);
+                }
+                next @ (NextSample::Paused | NextSample::Finished) => {
+                    self.next_channel_idx = 0;
+                    return Ok(next);
+                }
+            }
+        };
+        self.mono_sample = sample;
+        self.next_channel_idx += 1;
+        if self.next_channel_idx >= inner_channel_count.max(2) {
+            self.next_channel_idx = 0;
+        }
+        Ok(NextSample::Sample(self.apply_gain(sample, channel_idx)))
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.inner.on_start_of_batch()
+    }
+
+    fn as_seekable(&mut self) -> Option<&mut dyn Seekable> {
+        self.inner.as_seekable()
+    }
+
+    fn position(&self) -> Option<Duration> {
+        self.inner.position()
+    }
+
+    fn duration(&self) -> Option<Duration> {
+        self.inner.duration()
+    }
+}
+
+impl<S> SetPan for Pannable<S>
+where
+    S: Sound,
+{
+    fn set_pan(&mut self, pan: f32) {
+        // NaN becomes the center.
+        self.pan = if pan.is_nan() {
+            0.0
+        } else {
+            pan.clamp(-1.0, 1.0)
+        };
+        self.pan_changed = true;
+    }
+}
+
+impl<S> SetPaused for Pannable<S>
+where
+    S: Sound + SetPaused,
+{
+    fn set_paused(&mut self, paused: bool) {
+        self.inner.set_paused(paused)
+    }
+}
+
+impl<S> SetVolume for Pannable<S>
+where
+    S: Sound + SetVolume,
+{
+    fn set_volume(&mut self, multiplier: f32) {
+        self.inner.set_volume(multiplier)
+    }
+
+    fn set_volume_over(&mut self, multiplier: f32, duration: Duration) {
+        self.inner.set_volume_over(multiplier, duration)
+    }
+}
+
+impl<S> SetSpeed for Pannable<S>
+where
+    S: Sound + SetSpeed,
+{
+    fn set_speed(&mut self, multiplier: f32) {
+        self.inner.set_speed(multiplier)
+    }
+
+    fn set_speed_over(&mut self, multiplier: f32, duration: Duration) {
+        self.inner.set_speed_over(multiplier, duration)
+    }
+}
+
+impl<S> SetTempo for Pannable<S>
+where
+    S: Sound + SetTempo,
+{
+    fn set_tempo(&mut self, multiplier: f32) {
+        self.inner.set_tempo(multiplier)
+    }
+}
+
+impl<S> SetPitch for Pannable<S>
+where
+    S: Sound + SetPitch,
+{
+    fn set_pitch(&mut self, multiplier: f32) {
+        self.inner.set_pitch(multiplier)
+    }
+}
+
+impl<S> FadeOut for Pannable<S>
+where
+    S: Sound + FadeOut,
+{
+    fn fade_out(&mut self, duration: std::time::Duration) {
+        self.inner.fade_out(duration)
+    }
+}
+
+impl<S> Seekable for Pannable<S>
+where
+    S: Sound + Seekable,
+{
+    fn seek(&mut self, position: std::time::Duration) -> Result<(), crate::Error> {
+        self.next_channel_idx = 0;
+        self.inner.seek(position)
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/pannable.rs"]
+mod tests;
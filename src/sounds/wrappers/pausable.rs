@@ -1,6 +1,8 @@
+use std::time::Duration;
+
 use crate::Sound;
 
-use super::{SetSpeed, SetVolume};
+use super::{FadeOut, Seekable, SetPan, SetPitch, SetSpeed, SetTempo, SetVolume};
 
 /// A Sound which can be paused.
 pub trait SetPaused {
@@ -62,9 +64,41 @@ where
         self.inner.next_sample()
     }
 
+    fn next_samples(
+        &mut self,
+        buffer: &mut [i16],
+    ) -> Result<(usize, crate::NextSample), crate::Error> {
+        if self.paused {
+            return Ok((0, crate::NextSample::Paused));
+        }
+        self.inner.next_samples(buffer)
+    }
+
+    fn next_samples_f32(
+        &mut self,
+        buffer: &mut [f32],
+    ) -> Result<(usize, crate::NextSample), crate::Error> {
+        if self.paused {
+            return Ok((0, crate::NextSample::Paused));
+        }
+        self.inner.next_samples_f32(buffer)
+    }
+
     fn on_start_of_batch(&mut self) {
         self.inner.on_start_of_batch()
     }
+
+    fn as_seekable(&mut self) -> Option<&mut dyn Seekable> {
+        self.inner.as_seekable()
+    }
+
+    fn position(&self) -> Option<Duration> {
+        self.inner.position()
+    }
+
+    fn duration(&self) -> Option<Duration> {
+        self.inner.duration()
+    }
 }
 
 impl<S> Pausable<S>
@@ -93,6 +127,10 @@ where
     fn set_volume(&mut self, multiplier: f32) {
         self.inner.set_volume(multiplier)
     }
+
+    fn set_volume_over(&mut self, multiplier: f32, duration: Duration) {
+        self.inner.set_volume_over(multiplier, duration)
+    }
 }
 
 impl<S> SetSpeed for Pausable<S>
@@ -102,6 +140,55 @@ where
     fn set_speed(&mut self, multiplier: f32) {
         self.inner.set_speed(multiplier)
     }
+
+    fn set_speed_over(&mut self, multiplier: f32, duration: Duration) {
+        self.inner.set_speed_over(multiplier, duration)
+    }
+}
+
+impl<S> SetTempo for Pausable<S>
+where
+    S: Sound + SetTempo,
+{
+    fn set_tempo(&mut self, multiplier: f32) {
+        self.inner.set_tempo(multiplier)
+    }
+}
+
+impl<S> SetPitch for Pausable<S>
+where
+    S: Sound + SetPitch,
+{
+    fn set_pitch(&mut self, multiplier: f32) {
+        self.inner.set_pitch(multiplier)
+    }
+}
+
+impl<S> FadeOut for Pausable<S>
+where
+    S: Sound + FadeOut,
+{
+    fn fade_out(&mut self, duration: std::time::Duration) {
+        self.inner.fade_out(duration)
+    }
+}
+
+impl<S> SetPan for Pausable<S>
+where
+    S: Sound + SetPan,
+{
+    fn set_pan(&mut self, pan: f32) {
+        self.inner.set_pan(pan)
+    }
+}
+
+impl<S> Seekable for Pausable<S>
+where
+    S: Sound + Seekable,
+{
+    fn seek(&mut self, position: std::time::Duration) -> Result<(), crate::Error> {
+        self.inner.seek(position)
+    }
 }
 
 #[cfg(test)]
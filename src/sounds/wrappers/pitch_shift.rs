@@ -0,0 +1,267 @@
+use std::time::Duration;
+
+use crate::{NextSample, Sound};
+
+use super::{
+    channel_count_converter::MAX_STANDARD_CHANNEL_COUNT,
+    sample_rate_converter::{scaled_rates, Interpolator},
+    AdjustableTempo, FadeOut, ResampleQuality, Seekable, SetPan, SetPaused, SetSpeed, SetTempo,
+    SetVolume,
+};
+
+/// A sound that can have the pitch adjusted without changing the speed.
+pub trait SetPitch {
+    /// Change the pitch.
+    ///
+    /// 1.0 is the normal pitch. 2.0 is one octave higher, 0.5 is one octave
+    /// lower. The duration is unchanged.
+    fn set_pitch(&mut self, multiplier: f32);
+
+    /// Change the pitch by a number of semitones. 0.0 is the normal pitch, 12.0
+    /// is one octave higher.
+    fn set_pitch_semitones(&mut self, semitones: f32) {
+        self.set_pitch(2_f32.powf(semitones / 12.0))
+    }
+}
+
+/// A wrapper that adjusts the pitch of the inner sound while keeping the
+/// duration.
+///
+/// The tempo of the inner sound is adjusted with the same algorithm as
+/// [AdjustableTempo] and then resampled to restore the duration using the
+/// same algorithms as [SampleRateConverter][super::SampleRateConverter] (see
+/// [with_quality][PitchShift::with_quality]). While the pitch is 1.0 and has
+/// never been changed samples are passed through unchanged.
+///
+/// Unlike [AdjustableSpeed][super::AdjustableSpeed] the sample rate does not
+/// change.
+pub struct PitchShift<S: Sound> {
+    inner: AdjustableTempo<S>,
+    pitch: f32,
+    quality: ResampleQuality,
+    /// Index of the channel of the next sample while passing through. Used to
+    /// only start resampling at the start of a frame.
+    next_channel_idx: u16,
+    /// Inactive while passing through.
+    resampler: Interpolator,
+}
+
+impl<S> PitchShift<S>
+where
+    S: Sound,
+{
+    /// Wrap `inner` such that its pitch can be adjusted.
+    pub fn new(inner: S) -> Self {
+        Self::new_with_pitch(inner, 1.0)
+    }
+
+    /// Wrap `inner` such that its pitch can be adjusted and set an initial
+    /// pitch.
+    pub fn new_with_pitch(inner: S, pitch: f32) -> Self {
+        Self::with_quality(inner, pitch, ResampleQuality::default())
+    }
+
+    /// Wrap `inner` such that its pitch can be adjusted, set an initial pitch
+    /// and resample using the algorithm of `quality`.
+    pub fn with_quality(inner: S, pitch: f32, quality: ResampleQuality) -> Self {
+        // Allocate for the highest channel count the inner sound is likely to
+        // change to so the change does not allocate on the audio thread.
+        let max_count = (MAX_STANDARD_CHANNEL_COUNT as u16).max(inner.channel_count());
+        let mut resampler = Interpolator::new(quality, max_count, 1, 1);
+        resampler.set_channel_count(inner.channel_count());
+        let mut shifted = PitchShift {
+            inner: AdjustableTempo::new(inner),
+            pitch: 1.0,
+            quality,
+            next_channel_idx: 0,
+            resampler,
+        };
+        shifted.set_pitch(pitch);
+        shifted
+    }
+
+    /// Get a reference to the wrapped inner Sound.
+    pub fn inner(&self) -> &S {
+        self.inner.inner()
+    }
+
+    /// Get a mutable reference to the wrapped inner Sound.
+    pub fn inner_mut(&mut self) -> &mut S {
+        self.inner.inner_mut()
+    }
+
+    /// Unwrap and return the previously wrapped Sound.
+    ///
+    /// Any buffered samples are lost.
+    pub fn into_inner(self) -> S {
+        self.inner.into_inner()
+    }
+
+    /// Return the current pitch multiplier. 1.0 is the default pitch.
+    pub fn pitch(&self) -> f32 {
+        self.pitch
+    }
+
+    /// The algorithm used to resample.
+    pub fn quality(&self) -> ResampleQuality {
+        self.quality
+    }
+
+    /// Set the rates of the resampler to read `pitch` frames of the stretched
+    /// inner sound for each output frame.
+    fn update_rates(&mut self) {
+        let channel_count = self.inner.channel_count();
+        if self.resampler.channel_count() != channel_count {
+            self.resampler.set_channel_count(channel_count);
+        }
+        let to_rate = self.inner.sample_rate();
+        let from_rate = ((to_rate as f32 * self.pitch).round() as u32).max(1);
+        let (from_rate_scaled, to_rate_scaled) = scaled_rates(from_rate, to_rate);
+        self.resampler.set_rates(from_rate_scaled, to_rate_scaled);
+    }
+
+    fn next_passed_through_sample(&mut self) -> Result<NextSample, crate::Error> {
+        let next = self.inner.next_sample()?;
+        match next {
+            NextSample::Sample(_) => {
+                self.next_channel_idx += 1;
+                if self.next_channel_idx >= self.inner.channel_count() {
+                    self.next_channel_idx = 0;
+                }
+            }
+            NextSample::MetadataChanged | NextSample::Paused | NextSample::Finished => {
+                self.next_channel_idx = 0;
+            }
+        }
+        Ok(next)
+    }
+}
+
+impl<S> Sound for PitchShift<S>
+where
+    S: Sound,
+{
+    fn channel_count(&self) -> u16 {
+        self.inner.channel_count()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        if !self.resampler.is_active() && (self.pitch == 1.0 || self.next_channel_idx != 0) {
+            return self.next_passed_through_sample();
+        }
+        let next = self.resampler.next_sample(&mut self.inner)?;
+        if let NextSample::MetadataChanged = next {
+            // The frames read so far are kept unless the channel count
+            // changed.
+            self.update_rates();
+        }
+        Ok(next)
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.inner.on_start_of_batch()
+    }
+
+    fn as_seekable(&mut self) -> Option<&mut dyn Seekable> {
+        // Seeking through self drops the buffered frames.
+        self.inner.as_seekable()?;
+        Some(self)
+    }
+
+    fn position(&self) -> Option<Duration> {
+        self.inner.position()
+    }
+
+    fn duration(&self) -> Option<Duration> {
+        self.inner.duration()
+    }
+}
+
+impl<S> SetPitch for PitchShift<S>
+where
+    S: Sound,
+{
+    fn set_pitch(&mut self, multiplier: f32) {
+        self.pitch = multiplier;
+        // Stretch by the pitch so that resampling restores the duration.
+        self.inner.set_tempo(1.0 / multiplier);
+        self.update_rates();
+    }
+}
+
+impl<S> SetPaused for PitchShift<S>
+where
+    S: Sound + SetPaused,
+{
+    fn set_paused(&mut self, paused: bool) {
+        self.inner.set_paused(paused)
+    }
+}
+
+impl<S> SetVolume for PitchShift<S>
+where
+    S: Sound + SetVolume,
+{
+    fn set_volume(&mut self, multiplier: f32) {
+        self.inner.set_volume(multiplier)
+    }
+
+    fn set_volume_over(&mut self, multiplier: f32, duration: Duration) {
+        self.inner.set_volume_over(multiplier, duration)
+    }
+}
+
+impl<S> SetSpeed for PitchShift<S>
+where
+    S: Sound + SetSpeed,
+{
+    fn set_speed(&mut self, multiplier: f32) {
+        self.inner.set_speed(multiplier)
+    }
+
+    fn set_speed_over(&mut self, multiplier: f32, duration: Duration) {
+        self.inner.set_speed_over(multiplier, duration)
+    }
+}
+
+impl<S> FadeOut for PitchShift<S>
+where
+    S: Sound + FadeOut,
+{
+    fn fade_out(&mut self, duration: std::time::Duration) {
+        self.inner.fade_out(duration)
+    }
+}
+
+impl<S> SetPan for PitchShift<S>
+where
+    S: Sound + SetPan,
+{
+    fn set_pan(&mut self, pan: f32) {
+        self.inner.set_pan(pan)
+    }
+}
+
+impl<S> Seekable for PitchShift<S>
+where
+    S: Sound,
+{
+    /// Seek the inner sound if it supports seeking (see
+    /// [Sound::as_seekable]) and drop the buffered frames. Otherwise an
+    /// IoError of [ErrorKind::Unsupported][std::io::ErrorKind::Unsupported]
+    /// is returned.
+    fn seek(&mut self, position: std::time::Duration) -> Result<(), crate::Error> {
+        self.inner.seek(position)?;
+        self.resampler.reset();
+        self.next_channel_idx = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/pitch_shift.rs"]
+mod tests;
@@ -1,11 +1,20 @@
-use crate::{NextSample, Sound};
+use std::time::Duration;
 
-use super::Wrapper;
+use crate::{
+    sound::{next_samples_f32_from_i16, next_samples_one_by_one},
+    NextSample, Sound,
+};
+
+use super::{Seekable, Wrapper};
 
 // Forked from https://github.com/RustAudio/rodio/blob/d5b9ae3467dab4316ee77b260a5b7432f74866b0/src/conversions/sample_rate.rs
 
 /// Convert a Sound from one sample rate (number of samples per second) to
 /// another.
+///
+/// The [ResampleQuality] used defaults to [ResampleQuality::Linear] which is
+/// cheap but aliases audibly, especially when converting to a higher sample
+/// rate. See [with_quality][SampleRateConverter::with_quality].
 pub struct SampleRateConverter<S: Sound> {
     /// The from Sound we are pulling samples from.
     inner: S,
@@ -41,6 +50,33 @@ pub struct SampleRateConverter<S: Sound> {
     channel_count_changed: bool,
     /// Whether the inner channel last returned Paused or Finished
     inner_paused: bool,
+    quality: ResampleQuality,
+    /// Used instead of the linear interpolation above for other qualities when
+    /// the sample rates differ. Kept while the rates match so that it can be
+    /// reused.
+    interpolator: Option<Interpolator>,
+}
+
+/// The algorithm used by a [SampleRateConverter] to compute the output
+/// samples.
+///
+/// Higher qualities need more computation per sample and delay when the inner
+/// sound is paused or finishes slightly more.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResampleQuality {
+    /// Linear interpolation between the two nearest input samples. The
+    /// cheapest option but it has poor high frequency response and aliases.
+    #[default]
+    Linear,
+    /// Cubic (Catmull-Rom) interpolation over the four nearest input samples.
+    /// Smoother than linear for little extra cost but still aliases when
+    /// converting to a lower sample rate.
+    Cubic,
+    /// Windowed sinc interpolation using precomputed tables of the sinc
+    /// function and window. Low pass filters to avoid aliasing when converting to a lower sample
+    /// rate and avoids imaging when converting to a higher sample rate. The
+    /// most expensive option.
+    Sinc,
 }
 
 impl<S> SampleRateConverter<S>
@@ -50,6 +86,16 @@ where
     /// Create a new SampleRateConverter with an output sample rate of
     /// `to_rate`.
     pub fn new(inner: S, to_rate: u32) -> SampleRateConverter<S> {
+        Self::with_quality(inner, to_rate, ResampleQuality::default())
+    }
+
+    /// Create a new SampleRateConverter with an output sample rate of
+    /// `to_rate` using the algorithm of `quality`.
+    pub fn with_quality(
+        inner: S,
+        to_rate: u32,
+        quality: ResampleQuality,
+    ) -> SampleRateConverter<S> {
         let channel_count = inner.channel_count();
         let mut new = SampleRateConverter {
             inner,
@@ -64,6 +110,8 @@ where
             channel_count,
             channel_count_changed: false,
             inner_paused: false,
+            quality,
+            interpolator: None,
         };
         new.init();
         new
@@ -79,66 +127,100 @@ where
         assert!(from_rate >= 1);
         assert!(self.to_rate >= 1);
 
-        // finding greatest common divisor
-        let gcd = {
-            #[inline]
-            fn gcd(a: u32, b: u32) -> u32 {
-                if b == 0 {
-                    a
-                } else {
-                    gcd(b, a % b)
+        // These will get filled on the first or next call to next_sample.
+        // The Vecs are reused so a frame can be read without allocating.
+        self.current_frame.clear();
+        self.next_frame.clear();
+        self.output_frame.clear();
+        for frame in [
+            &mut self.current_frame,
+            &mut self.next_frame,
+            &mut self.output_frame,
+        ] {
+            frame.reserve(channel_count as usize);
+        }
+
+        (self.from_rate_scaled, self.to_rate_scaled) = scaled_rates(from_rate, self.to_rate);
+        self.current_frame_pos_in_chunk = 0;
+        self.next_output_frame_pos_in_chunk = 0;
+        match &mut self.interpolator {
+            // The frames in the window are kept so the output continues
+            // smoothly.
+            Some(interpolator) if interpolator.channel_count() == channel_count => {
+                interpolator.set_rates(self.from_rate_scaled, self.to_rate_scaled)
+            }
+            _ => {
+                self.interpolator = match self.quality {
+                    ResampleQuality::Linear => None,
+                    // Created even while passing through so that changing
+                    // the rate later does not allocate.
+                    ResampleQuality::Cubic | ResampleQuality::Sinc => Some(Interpolator::new(
+                        self.quality,
+                        channel_count,
+                        self.from_rate_scaled,
+                        self.to_rate_scaled,
+                    )),
                 }
             }
+        }
+    }
 
-            gcd(from_rate, self.to_rate)
-        };
+    /// The algorithm used to convert the sample rate.
+    pub fn quality(&self) -> ResampleQuality {
+        self.quality
+    }
 
-        // These will get filled on the first or next call to next_sample
-        self.current_frame = Vec::new();
-        self.next_frame = Vec::new();
+    /// Whether inner is at the output rate and the [Interpolator] (if any)
+    /// has no frames left from converting an earlier rate.
+    fn passing_through(&self) -> bool {
+        self.from_rate_scaled == self.to_rate_scaled
+            && !self
+                .interpolator
+                .as_ref()
+                .is_some_and(Interpolator::is_active)
+    }
 
-        self.to_rate_scaled = self.to_rate / gcd;
-        self.from_rate_scaled = from_rate / gcd;
-        self.current_frame_pos_in_chunk = 0;
-        self.next_output_frame_pos_in_chunk = 0;
-        self.output_frame = Vec::with_capacity(channel_count as usize - 1);
+    /// next_sample when using an [Interpolator].
+    fn next_interpolated_sample(&mut self) -> Result<NextSample, crate::Error> {
+        let interpolator = self
+            .interpolator
+            .as_mut()
+            .expect("only called with an interpolator");
+        match interpolator.next_sample(&mut self.inner)? {
+            NextSample::MetadataChanged => {
+                self.init();
+                self.next_sample()
+            }
+            next => Ok(next),
+        }
     }
 
     fn fill_frames(&mut self) -> Result<bool, crate::Error> {
-        let from_rate = self.inner.sample_rate();
-        let (first_samples, next_samples) = if from_rate == self.to_rate {
-            (Vec::new(), Vec::new())
-        } else {
-            let mut collect_frame = || match self.inner.next_frame() {
-                Ok(f) => Ok(f),
-                Err(special) => match special {
-                    Ok(NextSample::Sample(_)) => unreachable!(),
-                    Ok(NextSample::MetadataChanged) => Err(None),
-                    Err(e) => Err(Some(e)),
-                    Ok(NextSample::Paused) => {
-                        self.inner_paused = true;
-                        Ok(Vec::new())
-                    }
-                    Ok(NextSample::Finished) => {
-                        self.inner_paused = false;
-                        Ok(Vec::new())
-                    }
-                },
-            };
-            let first = match collect_frame() {
-                Ok(o) => o,
-                Err(Some(e)) => return Err(e),
-                Err(None) => return Ok(false),
-            };
-            let next = match collect_frame() {
-                Ok(o) => o,
-                Err(Some(e)) => return Err(e),
-                Err(None) => return Ok(false),
-            };
-            (first, next)
-        };
-        self.current_frame = first_samples;
-        self.next_frame = next_samples;
+        self.current_frame.clear();
+        self.next_frame.clear();
+        // Compare the rates this was set up for rather than the current rate
+        // of inner which may have changed before inner returns
+        // MetadataChanged.
+        if self.from_rate_scaled == self.to_rate_scaled {
+            return Ok(true);
+        }
+        // Frames are read into the existing Vecs to avoid allocating.
+        for frame in [&mut self.current_frame, &mut self.next_frame] {
+            match self.inner.append_next_frame_to(frame) {
+                Ok(()) => {}
+                Err(Ok(NextSample::Sample(_))) => unreachable!(),
+                Err(Ok(NextSample::MetadataChanged)) => return Ok(false),
+                Err(Err(e)) => return Err(e),
+                Err(Ok(NextSample::Paused)) => {
+                    self.inner_paused = true;
+                    frame.clear();
+                }
+                Err(Ok(NextSample::Finished)) => {
+                    self.inner_paused = false;
+                    frame.clear();
+                }
+            }
+        }
         Ok(true)
     }
 
@@ -191,7 +273,7 @@ where
 
         // the algorithm below doesn't work if `self.from_rate_scaled ==
         // self.to_rate_scaled`
-        if self.from_rate_scaled == self.to_rate_scaled {
+        if self.passing_through() {
             debug_assert_eq!(self.from_rate_scaled, 1);
             let next = self.inner.next_sample()?;
             match next {
@@ -207,6 +289,10 @@ where
             }
         }
 
+        if self.interpolator.is_some() {
+            return self.next_interpolated_sample();
+        }
+
         // Short circuit if there are some samples waiting in the already processed
         // frame
         if let Some(sample) = self.output_frame.pop() {
@@ -314,9 +400,53 @@ where
         }
     }
 
+    fn next_samples(&mut self, buffer: &mut [i16]) -> Result<(usize, NextSample), crate::Error> {
+        // Only the pass through case is handled in blocks.
+        if self.channel_count_changed || !self.passing_through() {
+            return next_samples_one_by_one(self, buffer);
+        }
+        let (num_read, next) = self.inner.next_samples(buffer)?;
+        if let NextSample::MetadataChanged = next {
+            if self.inner.sample_rate() != self.to_rate {
+                self.init();
+            }
+        }
+        Ok((num_read, next))
+    }
+
+    fn next_samples_f32(
+        &mut self,
+        buffer: &mut [f32],
+    ) -> Result<(usize, NextSample), crate::Error> {
+        // Converting is done in i16 so only the pass through case keeps the
+        // f32 samples.
+        if self.channel_count_changed || !self.passing_through() {
+            return next_samples_f32_from_i16(self, buffer);
+        }
+        let (num_read, next) = self.inner.next_samples_f32(buffer)?;
+        if let NextSample::MetadataChanged = next {
+            if self.inner.sample_rate() != self.to_rate {
+                self.init();
+            }
+        }
+        Ok((num_read, next))
+    }
+
     fn on_start_of_batch(&mut self) {
         self.inner.on_start_of_batch()
     }
+
+    fn as_seekable(&mut self) -> Option<&mut dyn Seekable> {
+        self.inner.as_seekable()
+    }
+
+    fn position(&self) -> Option<Duration> {
+        self.inner.position()
+    }
+
+    fn duration(&self) -> Option<Duration> {
+        self.inner.duration()
+    }
 }
 
 impl<S: Sound> Wrapper for SampleRateConverter<S> {
@@ -335,10 +465,398 @@ impl<S: Sound> Wrapper for SampleRateConverter<S> {
     }
 }
 
+/// Divide both rates by their greatest common divisor.
+pub(super) fn scaled_rates(from_rate: u32, to_rate: u32) -> (u32, u32) {
+    #[inline]
+    fn gcd(a: u32, b: u32) -> u32 {
+        if b == 0 {
+            a
+        } else {
+            gcd(b, a % b)
+        }
+    }
+
+    let gcd = gcd(from_rate, to_rate);
+    (from_rate / gcd, to_rate / gcd)
+}
+
 fn linear_interpolation(first: i16, second: i16, numerator: u32, denominator: u32) -> i16 {
     (first as i64 + (second as i64 - first as i64) * numerator as i64 / denominator as i64) as i16
 }
 
+/// Number of zero crossings of the sinc function on each side of the center
+/// when not low pass filtering below the input rate.
+const SINC_ZERO_CROSSINGS: usize = 16;
+/// The maximum number of input frames used for each output frame by the sinc
+/// filter. Limits the cost of large down sampling ratios.
+const SINC_MAX_TAPS: usize = 256;
+/// Number of entries per zero crossing in the table of the sinc function.
+/// Values in between are linearly interpolated.
+const SINC_TABLE_RESOLUTION: usize = 512;
+/// Number of entries in the table of the window applied to the sinc function.
+const SINC_WINDOW_RESOLUTION: usize = 4096;
+/// Cutoff frequency of the sinc filter relative to the lower of the input and
+/// output Nyquist frequencies. Leaves room for the transition band.
+const SINC_CUTOFF: f64 = 0.95;
+
+/// Resamples using a window of input frames around the output position.
+///
+/// Output frame `n` is at input position `n * from_rate_scaled /
+/// to_rate_scaled` which is tracked as the index of the center frame in
+/// `window` plus `frac / to_rate_scaled`.
+///
+/// The rates can be changed with [set_rates][Interpolator::set_rates] without
+/// losing the frames in the window or allocating so changing the rate (e.g.
+/// while ramping the speed) does not interrupt the output.
+///
+/// Also used by [PitchShift][super::PitchShift] to resample the stretched
+/// sound.
+pub(super) struct Interpolator {
+    kernel: Kernel,
+    channel_count: usize,
+    /// Number of input frames used on each side of the output position for
+    /// the current rates.
+    half_taps: usize,
+    from_rate_scaled: u32,
+    to_rate_scaled: u32,
+    /// Input frames oldest first. Holds twice the most frames ever needed so
+    /// frames only need to be moved to the start once it is full.
+    window: Vec<f32>,
+    /// Index of the frame after the newest frame in `window`.
+    window_end: usize,
+    /// Number of frames in `window` after the center frame. Negative when the
+    /// center frame has not been pushed yet.
+    frames_ahead: isize,
+    /// Numerator of the fractional output position past the center frame.
+    frac: u32,
+    /// Set once inner returns Finished. Silent frames are pushed afterward so
+    /// the last input frames are output.
+    inner_finished: bool,
+    /// Number of silent frames pushed after inner finished.
+    silent_frames_pushed: usize,
+    /// Whether any frames were pushed since the last reset.
+    active: bool,
+    /// Scratch space for reading a frame from inner.
+    input_frame: Vec<i16>,
+    /// The rest of the last computed output frame stored in reverse order.
+    output_frame: Vec<i16>,
+    /// Weights of each tap for the current output frame.
+    weights: Vec<f32>,
+}
+
+enum Kernel {
+    Linear,
+    Cubic,
+    /// Blackman windowed sinc with the cutoff relative to the input Nyquist
+    /// frequency.
+    Sinc {
+        cutoff: f32,
+    },
+}
+
+/// Tables of the sinc function and the window applied to it. They do not
+/// depend on the rates so are computed once and shared by all sinc
+/// [Interpolator]s.
+struct SincTables {
+    /// `sin(PI * x) / (PI * x)` for `x` from 0 to `SINC_ZERO_CROSSINGS + 1`.
+    sinc: Vec<f32>,
+    /// Blackman window from the center (0) to the edge (1).
+    window: Vec<f32>,
+}
+
+impl SincTables {
+    fn get() -> &'static SincTables {
+        static TABLES: std::sync::OnceLock<SincTables> = std::sync::OnceLock::new();
+        TABLES.get_or_init(|| {
+            // One extra entry so lookups can always interpolate with the next.
+            let sinc = (0..=(SINC_ZERO_CROSSINGS + 1) * SINC_TABLE_RESOLUTION + 1)
+                .map(|i| {
+                    let x = i as f64 / SINC_TABLE_RESOLUTION as f64;
+                    if i == 0 {
+                        1.0
+                    } else {
+                        let a = std::f64::consts::PI * x;
+                        (a.sin() / a) as f32
+                    }
+                })
+                .collect();
+            let window = (0..=SINC_WINDOW_RESOLUTION + 1)
+                .map(|i| {
+                    let u = (i as f64 / SINC_WINDOW_RESOLUTION as f64).min(1.0);
+                    (0.42
+                        + 0.5 * (std::f64::consts::PI * u).cos()
+                        + 0.08 * (2.0 * std::f64::consts::PI * u).cos()) as f32
+                })
+                .collect();
+            SincTables { sinc, window }
+        })
+    }
+
+    /// Linearly interpolate `table` at `position` in entries.
+    fn lookup(table: &[f32], position: f32) -> f32 {
+        let idx = position as usize;
+        if idx + 1 >= table.len() {
+            return 0.0;
+        }
+        let frac = position - idx as f32;
+        table[idx] + (table[idx + 1] - table[idx]) * frac
+    }
+
+    /// Weight of an input frame `x` frames away from the output position.
+    fn weight(&self, x: f32, cutoff: f32, half_taps: usize) -> f32 {
+        let x = x.abs();
+        let u = x / half_taps as f32;
+        if u >= 1.0 {
+            return 0.0;
+        }
+        Self::lookup(&self.sinc, x * cutoff * SINC_TABLE_RESOLUTION as f32)
+            * Self::lookup(&self.window, u * SINC_WINDOW_RESOLUTION as f32)
+    }
+}
+
+impl Interpolator {
+    pub(super) fn new(
+        quality: ResampleQuality,
+        channel_count: u16,
+        from_rate_scaled: u32,
+        to_rate_scaled: u32,
+    ) -> Interpolator {
+        let (kernel, max_half_taps) = match quality {
+            ResampleQuality::Linear => (Kernel::Linear, 1),
+            ResampleQuality::Cubic => (Kernel::Cubic, 2),
+            ResampleQuality::Sinc => {
+                // Compute the tables now rather than on the first rate change.
+                SincTables::get();
+                (Kernel::Sinc { cutoff: 0.0 }, SINC_MAX_TAPS / 2)
+            }
+        };
+        let channel_count = channel_count as usize;
+        let mut interpolator = Interpolator {
+            kernel,
+            channel_count,
+            half_taps: max_half_taps,
+            from_rate_scaled: 1,
+            to_rate_scaled: 1,
+            window: vec![0.0; 4 * max_half_taps * channel_count],
+            window_end: 0,
+            frames_ahead: 0,
+            frac: 0,
+            inner_finished: false,
+            silent_frames_pushed: 0,
+            active: false,
+            input_frame: Vec::with_capacity(channel_count),
+            output_frame: Vec::with_capacity(channel_count),
+            weights: vec![0.0; 2 * max_half_taps],
+        };
+        interpolator.set_rates(from_rate_scaled, to_rate_scaled);
+        interpolator.reset();
+        interpolator
+    }
+
+    pub(super) fn channel_count(&self) -> u16 {
+        self.channel_count as u16
+    }
+
+    /// Change the channel count dropping any frames pushed so far.
+    ///
+    /// The buffers are reused so this only allocates if the channel count is
+    /// higher than it has been before.
+    pub(super) fn set_channel_count(&mut self, channel_count: u16) {
+        let channel_count = channel_count as usize;
+        let window_frames = self.window.len() / self.channel_count;
+        self.channel_count = channel_count;
+        self.window.clear();
+        self.window.resize(window_frames * channel_count, 0.0);
+        self.input_frame.clear();
+        self.input_frame.reserve(channel_count);
+        self.output_frame.reserve(channel_count);
+        self.reset();
+    }
+
+    /// Whether there are input frames that have not been completely output
+    /// yet.
+    pub(super) fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Change the rates keeping the frames in the window and the position
+    /// within the current input frame.
+    pub(super) fn set_rates(&mut self, from_rate_scaled: u32, to_rate_scaled: u32) {
+        if from_rate_scaled == self.from_rate_scaled && to_rate_scaled == self.to_rate_scaled {
+            return;
+        }
+        self.frac = (self.frac as u64 * to_rate_scaled as u64 / self.to_rate_scaled as u64) as u32;
+        self.from_rate_scaled = from_rate_scaled;
+        self.to_rate_scaled = to_rate_scaled;
+        if let Kernel::Sinc { cutoff } = &mut self.kernel {
+            // When down sampling the filter must cut off at the output
+            // Nyquist frequency which stretches the sinc over more input
+            // frames.
+            let ratio = (to_rate_scaled as f64 / from_rate_scaled as f64).min(1.0);
+            *cutoff = (SINC_CUTOFF * ratio) as f32;
+            self.half_taps =
+                ((SINC_ZERO_CROSSINGS as f64 / ratio).ceil() as usize).min(SINC_MAX_TAPS / 2);
+        }
+    }
+
+    /// Go back to the state before any input frames were pushed.
+    pub(super) fn reset(&mut self) {
+        // Start in the second half so the first half can hold the frames
+        // before the output position.
+        self.window_end = self.window.len() / self.channel_count / 2;
+        // The next frame pushed is the center.
+        self.frames_ahead = -1;
+        self.frac = 0;
+        self.inner_finished = false;
+        self.silent_frames_pushed = 0;
+        self.active = false;
+        self.output_frame.clear();
+    }
+
+    /// Get the next output sample reading from `inner` as needed.
+    ///
+    /// MetadataChanged and Paused from inner are returned as is. The frames
+    /// read so far are kept so we continue where we left off.
+    pub(super) fn next_sample<S: Sound>(
+        &mut self,
+        inner: &mut S,
+    ) -> Result<NextSample, crate::Error> {
+        if let Some(sample) = self.output_frame.pop() {
+            return Ok(NextSample::Sample(sample));
+        }
+        loop {
+            if self.inner_finished && self.frames_ahead < self.silent_frames_pushed as isize {
+                // The last input frame has passed the output position. Reset
+                // so we start over if inner has more samples later.
+                self.reset();
+                return Ok(NextSample::Finished);
+            }
+            if self.frames_ahead >= self.half_taps as isize {
+                break;
+            }
+            if self.inner_finished {
+                self.push_silent_frame();
+                continue;
+            }
+            self.input_frame.clear();
+            match inner.append_next_frame_to(&mut self.input_frame) {
+                Ok(()) => self.push_input_frame(),
+                Err(Ok(NextSample::Sample(_))) => unreachable!(),
+                Err(Ok(next @ (NextSample::MetadataChanged | NextSample::Paused))) => {
+                    return Ok(next)
+                }
+                Err(Ok(NextSample::Finished)) => self.inner_finished = true,
+                Err(Err(e)) => return Err(e),
+            }
+        }
+        Ok(NextSample::Sample(self.compute_output_frame()))
+    }
+
+    /// Push `input_frame` as the newest frame.
+    fn push_input_frame(&mut self) {
+        let start = self.next_frame_start();
+        for (slot, sample) in self.window[start..].iter_mut().zip(&self.input_frame) {
+            *slot = *sample as f32;
+        }
+        self.frame_pushed(start);
+    }
+
+    /// Push a silent frame after the inner sound finished.
+    fn push_silent_frame(&mut self) {
+        let start = self.next_frame_start();
+        self.window[start..start + self.channel_count].fill(0.0);
+        self.silent_frames_pushed += 1;
+        self.frame_pushed(start);
+    }
+
+    /// Index in `window` of the first sample of the next frame to push.
+    fn next_frame_start(&mut self) -> usize {
+        if self.window_end * self.channel_count == self.window.len() {
+            // Keep the newest half which holds at least all the frames the
+            // output position needs.
+            let half_len = self.window.len() / 2;
+            self.window.copy_within(half_len.., 0);
+            self.window_end /= 2;
+        }
+        self.window_end * self.channel_count
+    }
+
+    fn frame_pushed(&mut self, start: usize) {
+        if !self.active {
+            // Repeat the first frame before it rather than ramping from
+            // silence, e.g. when converting starts after passing through.
+            self.active = true;
+            let (before, first) = self.window.split_at_mut(start);
+            for frame in before.chunks_exact_mut(self.channel_count) {
+                frame.copy_from_slice(&first[..self.channel_count]);
+            }
+        }
+        self.window_end += 1;
+        self.frames_ahead += 1;
+    }
+
+    /// Compute the output frame at the current position, store all but the
+    /// first sample in output_frame, advance the position and return the first
+    /// sample.
+    fn compute_output_frame(&mut self) -> i16 {
+        let taps = 2 * self.half_taps;
+        let t = self.frac as f32 / self.to_rate_scaled as f32;
+        let weights = &mut self.weights[..taps];
+        match self.kernel {
+            Kernel::Linear => {
+                weights[0] = 1.0 - t;
+                weights[1] = t;
+            }
+            Kernel::Cubic => {
+                let t2 = t * t;
+                let t3 = t2 * t;
+                weights[0] = 0.5 * (-t3 + 2.0 * t2 - t);
+                weights[1] = 0.5 * (3.0 * t3 - 5.0 * t2 + 2.0);
+                weights[2] = 0.5 * (-3.0 * t3 + 4.0 * t2 + t);
+                weights[3] = 0.5 * (t3 - t2);
+            }
+            Kernel::Sinc { cutoff } => {
+                let tables = SincTables::get();
+                for (tap, weight) in weights.iter_mut().enumerate() {
+                    // Distance in input frames from the output position.
+                    let x = tap as f32 - (self.half_taps - 1) as f32 - t;
+                    *weight = tables.weight(x, cutoff, self.half_taps);
+                }
+                // Normalize so a constant signal keeps its level.
+                let sum: f32 = weights.iter().sum();
+                for weight in weights.iter_mut() {
+                    *weight /= sum;
+                }
+            }
+        }
+
+        // The first tap is `half_taps - 1` frames before the center frame.
+        let first_tap = self.window_end - 1 - self.frames_ahead as usize + 1 - self.half_taps;
+        let taps_window =
+            &self.window[first_tap * self.channel_count..][..taps * self.channel_count];
+        self.output_frame.clear();
+        for channel in (0..self.channel_count).rev() {
+            let sum: f32 = weights
+                .iter()
+                .zip(taps_window.chunks_exact(self.channel_count))
+                .map(|(weight, frame)| weight * frame[channel])
+                .sum();
+            // `as` saturates at the bounds of i16.
+            self.output_frame.push(sum.round() as i16);
+        }
+
+        self.frac += self.from_rate_scaled;
+        while self.frac >= self.to_rate_scaled {
+            self.frac -= self.to_rate_scaled;
+            self.frames_ahead -= 1;
+        }
+
+        self.output_frame
+            .pop()
+            .expect("channel_count is at least 1")
+    }
+}
+
 #[cfg(test)]
 #[path = "./tests/sample_rate_converter.rs"]
 mod tests;
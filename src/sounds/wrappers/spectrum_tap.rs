@@ -0,0 +1,377 @@
+use std::f32::consts::PI;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::manager::BackendSource;
+use crate::{NextSample, Sound};
+
+use super::Seekable;
+use super::Wrapper;
+
+/// The window applied to the samples before the FFT of a [SpectrumTap].
+///
+/// Windows other than [Window::Rectangular] reduce the leakage of a
+/// frequency into neighbouring bins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Window {
+    /// No window. The narrowest peaks but the most leakage.
+    Rectangular,
+    /// A good default for visualizations.
+    #[default]
+    Hann,
+    /// Similar to Hann with less leakage into the nearest bins but more into
+    /// distant bins.
+    Hamming,
+    /// Wider peaks but the least leakage.
+    Blackman,
+}
+
+impl Window {
+    fn coefficient(self, idx: usize, size: usize) -> f32 {
+        let phase = 2.0 * PI * idx as f32 / size as f32;
+        match self {
+            Window::Rectangular => 1.0,
+            Window::Hann => 0.5 - 0.5 * phase.cos(),
+            Window::Hamming => 0.54 - 0.46 * phase.cos(),
+            Window::Blackman => 0.42 - 0.5 * phase.cos() + 0.08 * (2.0 * phase).cos(),
+        }
+    }
+}
+
+/// A pass-through wrapper that computes the frequency spectrum of the inner
+/// sound so it can be read from a [SpectrumHandle] on another thread, e.g. to
+/// draw a visualizer.
+///
+/// The channels are mixed to mono and the most recent `size` frames are
+/// windowed and transformed at the start of each batch in which new samples
+/// were played. All buffers are allocated up front so the audio thread does
+/// not allocate.
+///
+/// Since it is also a [BackendSource] when the inner sound is, a
+/// [Renderer][crate::manager::Renderer] can be wrapped to analyze everything
+/// playing.
+pub struct SpectrumTap<S: Sound> {
+    inner: S,
+    published: Arc<PublishedSpectrum>,
+    fft: Fft,
+    window: Vec<f32>,
+    /// Scale that makes a full scale sine at the center of a bin have a
+    /// magnitude of 1.0.
+    magnitude_scale: f32,
+    /// The most recent mono frames. The oldest is at `history_idx`.
+    history: Vec<f32>,
+    history_idx: usize,
+    next_channel_idx: u16,
+    frame_sum: f32,
+    new_frames: bool,
+    real: Vec<f32>,
+    imag: Vec<f32>,
+}
+
+/// The spectrum written by [SpectrumTap] and read by [SpectrumHandle]. f32
+/// values are stored as their bits.
+struct PublishedSpectrum {
+    sample_rate: AtomicU32,
+    magnitudes: Box<[AtomicU32]>,
+}
+
+impl<S> SpectrumTap<S>
+where
+    S: Sound,
+{
+    /// Wrap `inner` to analyze the most recent `size` frames with `window`
+    /// applied and return a handle to read the spectrum.
+    ///
+    /// `size` / 2 + 1 bins are published. Larger sizes resolve frequencies
+    /// more finely but react slower.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is not a power of two or is less than 2.
+    pub fn new(inner: S, size: usize, window: Window) -> (Self, SpectrumHandle) {
+        assert!(
+            size >= 2 && size.is_power_of_two(),
+            "size must be a power of two of at least 2"
+        );
+        let window: Vec<f32> = (0..size).map(|i| window.coefficient(i, size)).collect();
+        let window_sum: f32 = window.iter().sum();
+        let published = Arc::new(PublishedSpectrum {
+            sample_rate: AtomicU32::new(inner.sample_rate()),
+            magnitudes: (0..size / 2 + 1).map(|_| AtomicU32::new(0)).collect(),
+        });
+        let tap = SpectrumTap {
+            inner,
+            published: published.clone(),
+            fft: Fft::new(size),
+            window,
+            magnitude_scale: 2.0 / window_sum,
+            history: vec![0.0; size],
+            history_idx: 0,
+            next_channel_idx: 0,
+            frame_sum: 0.0,
+            new_frames: false,
+            real: vec![0.0; size],
+            imag: vec![0.0; size],
+        };
+        (tap, SpectrumHandle { published })
+    }
+
+    /// Add `sample` where 1.0 is full scale to the mono frame being mixed.
+    fn push(&mut self, sample: f32) {
+        self.frame_sum += sample;
+        self.next_channel_idx += 1;
+        let channel_count = self.inner.channel_count();
+        if self.next_channel_idx >= channel_count {
+            self.history[self.history_idx] = self.frame_sum / channel_count as f32;
+            self.history_idx = (self.history_idx + 1) % self.history.len();
+            self.next_channel_idx = 0;
+            self.frame_sum = 0.0;
+            self.new_frames = true;
+        }
+    }
+
+    fn on_next(&mut self, next: NextSample) {
+        match next {
+            NextSample::Sample(s) => self.push(s as f32 / i16::MAX as f32),
+            NextSample::MetadataChanged | NextSample::Paused | NextSample::Finished => {
+                self.next_channel_idx = 0;
+                self.frame_sum = 0.0;
+            }
+        }
+    }
+
+    /// Transform the most recent frames and publish the magnitudes.
+    fn publish(&mut self) {
+        self.published
+            .sample_rate
+            .store(self.inner.sample_rate(), Ordering::Relaxed);
+        if !self.new_frames {
+            return;
+        }
+        self.new_frames = false;
+        let size = self.history.len();
+        for i in 0..size {
+            let sample = self.history[(self.history_idx + i) % size];
+            self.real[i] = sample * self.window[i];
+            self.imag[i] = 0.0;
+        }
+        self.fft.process(&mut self.real, &mut self.imag);
+        for (i, magnitude) in self.published.magnitudes.iter().enumerate() {
+            let value = self.real[i].hypot(self.imag[i]) * self.magnitude_scale;
+            // The DC and Nyquist bins have no mirrored negative frequency.
+            let value = if i == 0 || i == size / 2 {
+                value / 2.0
+            } else {
+                value
+            };
+            magnitude.store(value.to_bits(), Ordering::Relaxed);
+        }
+    }
+}
+
+impl<S> Sound for SpectrumTap<S>
+where
+    S: Sound,
+{
+    fn channel_count(&self) -> u16 {
+        self.inner.channel_count()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        let next = self.inner.next_sample()?;
+        self.on_next(next);
+        Ok(next)
+    }
+
+    fn next_samples(&mut self, buffer: &mut [i16]) -> Result<(usize, NextSample), crate::Error> {
+        let (num_read, next) = self.inner.next_samples(buffer)?;
+        for sample in &buffer[..num_read] {
+            self.push(*sample as f32 / i16::MAX as f32);
+        }
+        if !matches!(next, NextSample::Sample(_)) {
+            self.on_next(next);
+        }
+        Ok((num_read, next))
+    }
+
+    fn next_samples_f32(
+        &mut self,
+        buffer: &mut [f32],
+    ) -> Result<(usize, NextSample), crate::Error> {
+        let (num_read, next) = self.inner.next_samples_f32(buffer)?;
+        for sample in &buffer[..num_read] {
+            self.push(*sample);
+        }
+        if !matches!(next, NextSample::Sample(_)) {
+            self.on_next(next);
+        }
+        Ok((num_read, next))
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.publish();
+        self.inner.on_start_of_batch()
+    }
+
+    fn as_seekable(&mut self) -> Option<&mut dyn Seekable> {
+        self.inner.as_seekable()
+    }
+
+    fn position(&self) -> Option<Duration> {
+        self.inner.position()
+    }
+
+    fn duration(&self) -> Option<Duration> {
+        self.inner.duration()
+    }
+}
+
+impl<S> BackendSource for SpectrumTap<S>
+where
+    S: BackendSource,
+{
+    fn set_output_channel_count_and_sample_rate(
+        &mut self,
+        output_channel_count: u16,
+        output_sample_rate: u32,
+    ) {
+        self.inner
+            .set_output_channel_count_and_sample_rate(output_channel_count, output_sample_rate);
+    }
+}
+
+impl<S> Wrapper for SpectrumTap<S>
+where
+    S: Sound,
+{
+    type Inner = S;
+
+    fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    fn inner_mut(&mut self) -> &mut Self::Inner {
+        &mut self.inner
+    }
+
+    fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+/// Reads the spectrum computed by a [SpectrumTap] without blocking the audio
+/// thread.
+#[derive(Clone)]
+pub struct SpectrumHandle {
+    published: Arc<PublishedSpectrum>,
+}
+
+impl SpectrumHandle {
+    /// The number of magnitude bins which is the size of the tap / 2 + 1.
+    pub fn bin_count(&self) -> usize {
+        self.published.magnitudes.len()
+    }
+
+    /// The center frequency in Hz of bin `idx`.
+    pub fn bin_frequency(&self, idx: usize) -> f32 {
+        let size = (self.bin_count() - 1) * 2;
+        idx as f32 * self.published.sample_rate.load(Ordering::Relaxed) as f32 / size as f32
+    }
+
+    /// Copy the most recent magnitudes into `magnitudes` which is resized to
+    /// [bin_count][SpectrumHandle::bin_count]. A full scale sine has a
+    /// magnitude of about 1.0 in its bin.
+    ///
+    /// Bins are updated individually so a read while the audio thread is
+    /// publishing can contain bins from two consecutive batches.
+    pub fn read_magnitudes(&self, magnitudes: &mut Vec<f32>) {
+        magnitudes.clear();
+        magnitudes.extend(
+            self.published
+                .magnitudes
+                .iter()
+                .map(|m| f32::from_bits(m.load(Ordering::Relaxed))),
+        );
+    }
+
+    /// Same as [read_magnitudes][SpectrumHandle::read_magnitudes] but returns
+    /// a new Vec.
+    pub fn magnitudes(&self) -> Vec<f32> {
+        let mut magnitudes = Vec::with_capacity(self.bin_count());
+        self.read_magnitudes(&mut magnitudes);
+        magnitudes
+    }
+}
+
+impl std::fmt::Debug for SpectrumHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SpectrumHandle")
+            .field("bin_count", &self.bin_count())
+            .finish()
+    }
+}
+
+/// An in place radix-2 complex FFT with precomputed tables.
+struct Fft {
+    /// The index each index is swapped with before the butterflies.
+    bit_reversed: Vec<usize>,
+    /// cos and sin of -2πk/size for k < size / 2.
+    twiddles: Vec<(f32, f32)>,
+}
+
+impl Fft {
+    fn new(size: usize) -> Self {
+        // size is at least 2 so the shift is less than usize::BITS.
+        let bits = size.trailing_zeros();
+        let bit_reversed = (0..size)
+            .map(|i| i.reverse_bits() >> (usize::BITS - bits))
+            .collect();
+        let twiddles = (0..size / 2)
+            .map(|k| {
+                let angle = -2.0 * PI * k as f32 / size as f32;
+                (angle.cos(), angle.sin())
+            })
+            .collect();
+        Fft {
+            bit_reversed,
+            twiddles,
+        }
+    }
+
+    fn process(&self, real: &mut [f32], imag: &mut [f32]) {
+        let size = real.len();
+        for (i, &j) in self.bit_reversed.iter().enumerate() {
+            if i < j {
+                real.swap(i, j);
+                imag.swap(i, j);
+            }
+        }
+        let mut len = 2;
+        while len <= size {
+            let half = len / 2;
+            let twiddle_step = size / len;
+            for start in (0..size).step_by(len) {
+                for k in 0..half {
+                    let (cos, sin) = self.twiddles[k * twiddle_step];
+                    let (a, b) = (start + k, start + k + half);
+                    let re = real[b] * cos - imag[b] * sin;
+                    let im = real[b] * sin + imag[b] * cos;
+                    real[b] = real[a] - re;
+                    imag[b] = imag[a] - im;
+                    real[a] += re;
+                    imag[a] += im;
+                }
+            }
+            len *= 2;
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/spectrum_tap.rs"]
+mod tests;
@@ -0,0 +1,321 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::manager::BackendSource;
+use crate::{utils, NextSample, Sound};
+
+use super::Seekable;
+use super::Wrapper;
+
+/// Set on entries of the ring buffer that are a format instead of a sample.
+const FORMAT_FLAG: u64 = 1 << 63;
+
+/// A pass-through wrapper that copies the samples of the inner sound into a
+/// bounded ring buffer to be read by a [TeeReader] on another thread, e.g.
+/// to record the output to disk.
+///
+/// Writing never blocks or allocates. If the reader falls behind, whole
+/// frames that do not fit are dropped and counted in
+/// [TeeReader::dropped_samples]. Each `MetadataChanged` of the inner sound is
+/// passed to the reader along with the new channel count and sample rate.
+///
+/// f32 samples are converted to i16 (clamping at full scale) before being
+/// copied.
+///
+/// Since it is also a [BackendSource] when the inner sound is, a
+/// [Renderer][crate::manager::Renderer] can be wrapped to capture the whole
+/// mix.
+pub struct Tee<S: Sound> {
+    inner: S,
+    ring: Arc<Ring>,
+    /// The number of entries written which is never more than `capacity` ahead
+    /// of the reader.
+    written: u64,
+    next_channel_idx: u16,
+    /// Set when the current frame did not fit and its samples are dropped.
+    dropping_frame: bool,
+    /// Set when a format did not fit and must be written before any samples.
+    format_pending: bool,
+}
+
+/// A single producer single consumer ring buffer of entries.
+struct Ring {
+    entries: Box<[AtomicU64]>,
+    /// The number of entries written by the [Tee].
+    written: AtomicU64,
+    /// The number of entries read by the [TeeReader].
+    read: AtomicU64,
+    dropped_samples: AtomicU64,
+}
+
+fn encode_format(channel_count: u16, sample_rate: u32) -> u64 {
+    FORMAT_FLAG | ((channel_count as u64) << 32) | sample_rate as u64
+}
+
+impl<S> Tee<S>
+where
+    S: Sound,
+{
+    /// Wrap `inner` and return the reader of its samples. The ring buffer
+    /// holds `capacity` samples.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is 0.
+    pub fn new(inner: S, capacity: usize) -> (Self, TeeReader) {
+        assert!(capacity > 0, "capacity must not be 0");
+        let ring = Arc::new(Ring {
+            entries: (0..capacity).map(|_| AtomicU64::new(0)).collect(),
+            written: AtomicU64::new(0),
+            read: AtomicU64::new(0),
+            dropped_samples: AtomicU64::new(0),
+        });
+        let reader = TeeReader {
+            ring: ring.clone(),
+            read: 0,
+            channel_count: inner.channel_count(),
+            sample_rate: inner.sample_rate(),
+        };
+        let tee = Tee {
+            inner,
+            ring,
+            written: 0,
+            next_channel_idx: 0,
+            dropping_frame: false,
+            format_pending: false,
+        };
+        (tee, reader)
+    }
+
+    fn free_entries(&self) -> u64 {
+        let read = self.ring.read.load(Ordering::Acquire);
+        self.ring.entries.len() as u64 - (self.written - read)
+    }
+
+    /// Write entries the caller checked there is space for.
+    fn write(&mut self, entry: u64) {
+        let idx = (self.written % self.ring.entries.len() as u64) as usize;
+        self.ring.entries[idx].store(entry, Ordering::Relaxed);
+        self.written += 1;
+        self.ring.written.store(self.written, Ordering::Release);
+    }
+
+    fn push(&mut self, sample: i16) {
+        let channel_count = self.inner.channel_count();
+        if self.next_channel_idx == 0 {
+            let format_entries = self.format_pending as u64;
+            self.dropping_frame = self.free_entries() < channel_count as u64 + format_entries;
+            if !self.dropping_frame && self.format_pending {
+                self.format_pending = false;
+                self.write(encode_format(channel_count, self.inner.sample_rate()));
+            }
+        }
+        if self.dropping_frame {
+            self.ring.dropped_samples.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.write(sample as u16 as u64);
+        }
+        self.next_channel_idx += 1;
+        if self.next_channel_idx >= channel_count {
+            self.next_channel_idx = 0;
+        }
+    }
+
+    fn on_next(&mut self, next: NextSample) {
+        match next {
+            NextSample::Sample(s) => self.push(s),
+            NextSample::MetadataChanged => {
+                self.next_channel_idx = 0;
+                if self.free_entries() > 0 {
+                    self.format_pending = false;
+                    self.write(encode_format(
+                        self.inner.channel_count(),
+                        self.inner.sample_rate(),
+                    ));
+                } else {
+                    self.format_pending = true;
+                }
+            }
+            NextSample::Paused | NextSample::Finished => self.next_channel_idx = 0,
+        }
+    }
+}
+
+impl<S> Sound for Tee<S>
+where
+    S: Sound,
+{
+    fn channel_count(&self) -> u16 {
+        self.inner.channel_count()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        let next = self.inner.next_sample()?;
+        self.on_next(next);
+        Ok(next)
+    }
+
+    fn next_samples(&mut self, buffer: &mut [i16]) -> Result<(usize, NextSample), crate::Error> {
+        let (num_read, next) = self.inner.next_samples(buffer)?;
+        for sample in &buffer[..num_read] {
+            self.push(*sample);
+        }
+        if !matches!(next, NextSample::Sample(_)) {
+            self.on_next(next);
+        }
+        Ok((num_read, next))
+    }
+
+    fn next_samples_f32(
+        &mut self,
+        buffer: &mut [f32],
+    ) -> Result<(usize, NextSample), crate::Error> {
+        let (num_read, next) = self.inner.next_samples_f32(buffer)?;
+        for sample in &buffer[..num_read] {
+            self.push(utils::f32_to_i16(*sample));
+        }
+        if !matches!(next, NextSample::Sample(_)) {
+            self.on_next(next);
+        }
+        Ok((num_read, next))
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.inner.on_start_of_batch()
+    }
+
+    fn as_seekable(&mut self) -> Option<&mut dyn Seekable> {
+        self.inner.as_seekable()
+    }
+
+    fn position(&self) -> Option<Duration> {
+        self.inner.position()
+    }
+
+    fn duration(&self) -> Option<Duration> {
+        self.inner.duration()
+    }
+}
+
+impl<S> BackendSource for Tee<S>
+where
+    S: BackendSource,
+{
+    fn set_output_channel_count_and_sample_rate(
+        &mut self,
+        output_channel_count: u16,
+        output_sample_rate: u32,
+    ) {
+        self.inner
+            .set_output_channel_count_and_sample_rate(output_channel_count, output_sample_rate);
+    }
+}
+
+impl<S> Wrapper for Tee<S>
+where
+    S: Sound,
+{
+    type Inner = S;
+
+    fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    fn inner_mut(&mut self) -> &mut Self::Inner {
+        &mut self.inner
+    }
+
+    fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+/// The result of [TeeReader::read].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TeeRead {
+    /// This many samples in the current format were read into the buffer.
+    Samples(usize),
+    /// The following samples are in a new format. See
+    /// [TeeReader::channel_count] and [TeeReader::sample_rate].
+    MetadataChanged,
+    /// No samples are available yet.
+    Empty,
+}
+
+/// Reads the samples copied by a [Tee] on another thread.
+pub struct TeeReader {
+    ring: Arc<Ring>,
+    /// The number of entries read.
+    read: u64,
+    channel_count: u16,
+    sample_rate: u32,
+}
+
+impl TeeReader {
+    /// Read available samples into `buffer` until it is full, no more samples
+    /// are available or the format changes.
+    ///
+    /// Never blocks. Samples start on the first channel of a frame after
+    /// [TeeRead::MetadataChanged] and after frames were dropped.
+    pub fn read(&mut self, buffer: &mut [i16]) -> TeeRead {
+        let written = self.ring.written.load(Ordering::Acquire);
+        let capacity = self.ring.entries.len() as u64;
+        let mut num_read = 0;
+        while num_read < buffer.len() && self.read < written {
+            let entry = self.ring.entries[(self.read % capacity) as usize].load(Ordering::Relaxed);
+            if entry & FORMAT_FLAG != 0 {
+                if num_read > 0 {
+                    // Return the samples in the old format first.
+                    break;
+                }
+                self.read += 1;
+                self.ring.read.store(self.read, Ordering::Release);
+                self.channel_count = (entry >> 32) as u16;
+                self.sample_rate = entry as u32;
+                return TeeRead::MetadataChanged;
+            }
+            buffer[num_read] = entry as u16 as i16;
+            num_read += 1;
+            self.read += 1;
+        }
+        self.ring.read.store(self.read, Ordering::Release);
+        if num_read == 0 {
+            TeeRead::Empty
+        } else {
+            TeeRead::Samples(num_read)
+        }
+    }
+
+    /// The channel count of the samples being read.
+    pub fn channel_count(&self) -> u16 {
+        self.channel_count
+    }
+
+    /// The sample rate of the samples being read.
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// The total number of samples dropped because the buffer was full.
+    pub fn dropped_samples(&self) -> u64 {
+        self.ring.dropped_samples.load(Ordering::Relaxed)
+    }
+}
+
+impl std::fmt::Debug for TeeReader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TeeReader")
+            .field("channel_count", &self.channel_count)
+            .field("sample_rate", &self.sample_rate)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/tee.rs"]
+mod tests;
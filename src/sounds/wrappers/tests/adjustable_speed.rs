@@ -66,6 +66,11 @@ fn metadata_changed_notification() {
     );
     first.set_speed(0.50);
     assert_eq!(first.sample_rate(), 22050);
+    // The rest of the frame is returned before the change is reported.
+    assert_eq!(
+        first.next_sample().unwrap(),
+        crate::NextSample::Sample(1000)
+    );
     assert_eq!(
         first.next_sample().unwrap(),
         crate::NextSample::MetadataChanged
@@ -75,3 +80,109 @@ fn metadata_changed_notification() {
         crate::NextSample::Sample(1000)
     );
 }
+
+#[test]
+fn set_speed_over_ramps_in_steps() {
+    let mut sound = ConstantValueSound::new(1000);
+    sound.sample_rate = 10_000;
+    let mut sound = sound.with_adjustable_speed();
+    sound.set_speed_over(2.0, std::time::Duration::from_millis(200));
+    assert_eq!(sound.speed(), 2.0);
+    let mut rates = Vec::new();
+    let mut num_samples = 0;
+    while rates.len() < 4 {
+        match sound.next_sample().unwrap() {
+            crate::NextSample::MetadataChanged => rates.push((num_samples, sound.sample_rate())),
+            crate::NextSample::Sample(_) => num_samples += 1,
+            crate::NextSample::Paused | crate::NextSample::Finished => unreachable!(),
+        }
+    }
+    // Each step lasts 50ms of output at the new rate.
+    assert_eq!(
+        rates,
+        [(0, 12_500), (1250, 15_000), (2750, 17_500), (4500, 20_000)]
+    );
+    for _ in 0..1000 {
+        assert_eq!(
+            sound.next_sample().unwrap(),
+            crate::NextSample::Sample(1000)
+        );
+    }
+}
+
+#[test]
+fn buffers_stop_at_ramp_steps() {
+    let mut sound = ConstantValueSound::new(1000);
+    sound.sample_rate = 10_000;
+    let mut sound = sound.with_adjustable_speed_of(2.0);
+    sound.set_smoothing(std::time::Duration::from_millis(100));
+    sound.set_speed(1.0);
+    let mut buffer = [0; 2000];
+    assert_eq!(
+        sound.next_samples(&mut buffer).unwrap(),
+        (0, crate::NextSample::MetadataChanged)
+    );
+    assert_eq!(sound.sample_rate(), 15_000);
+    assert_eq!(sound.next_samples(&mut buffer).unwrap().0, 1500);
+    assert_eq!(
+        sound.next_samples(&mut buffer).unwrap(),
+        (0, crate::NextSample::MetadataChanged)
+    );
+    assert_eq!(sound.sample_rate(), 10_000);
+    assert_eq!(sound.next_samples(&mut buffer).unwrap().0, 2000);
+}
+
+#[test]
+fn buffers_stop_at_end_of_frame_before_change() {
+    let mut sound = ConstantValueSound::new(1000).with_adjustable_speed();
+    let mut buffer = [0; 3];
+    assert_eq!(sound.next_samples(&mut buffer).unwrap().0, 3);
+    sound.set_speed_over(2.0, std::time::Duration::from_millis(100));
+    assert_eq!(
+        sound.next_samples(&mut buffer).unwrap(),
+        (1, crate::NextSample::Sample(1000))
+    );
+    assert_eq!(
+        sound.next_samples(&mut buffer).unwrap(),
+        (0, crate::NextSample::MetadataChanged)
+    );
+}
+
+#[test]
+fn sinc_ramp_has_no_dropout() {
+    // Different values per channel to catch the channels getting out of order.
+    let samples: Vec<i16> = (0..DEFAULT_SAMPLE_RATE * 2)
+        .flat_map(|_| [10000, -10000])
+        .collect();
+    let sound = crate::sounds::MemorySound::from_samples(
+        std::sync::Arc::new(samples),
+        2,
+        DEFAULT_SAMPLE_RATE,
+    )
+    .with_adjustable_speed();
+    // Passes through until the speed changes.
+    let mut converted = crate::sounds::wrappers::SampleRateConverter::with_quality(
+        sound,
+        DEFAULT_SAMPLE_RATE,
+        crate::sounds::wrappers::ResampleQuality::Sinc,
+    );
+    let mut num_samples = 0;
+    while num_samples < 48000 {
+        // Change the speed mid-frame.
+        if num_samples == 1001 {
+            converted.set_speed_over(2.0, std::time::Duration::from_millis(200));
+        }
+        if num_samples == 24001 {
+            converted.set_speed_over(1.0, std::time::Duration::from_millis(100));
+        }
+        match converted.next_sample().unwrap() {
+            crate::NextSample::Sample(s) => {
+                let expected = if num_samples % 2 == 0 { 10000 } else { -10000 };
+                assert!((s - expected).abs() <= 2, "{s} at {num_samples}");
+                num_samples += 1;
+            }
+            crate::NextSample::MetadataChanged if num_samples % 2 == 0 => {}
+            next => panic!("{next:?} at {num_samples}"),
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use std::sync::Arc;
+
+use crate::{
+    sounds::{wrappers::SetSpeed, MemorySound},
+    NextSample, Sound,
+};
+
+use super::*;
+
+const SAMPLE_RATE: u32 = 8000;
+
+fn sine(freq: f64, num_frames: usize, channel_count: u16) -> Vec<i16> {
+    (0..num_frames)
+        .flat_map(|n| {
+            let t = n as f64 / SAMPLE_RATE as f64;
+            let sample = ((2.0 * std::f64::consts::PI * freq * t).sin() * 10000.0) as i16;
+            std::iter::repeat_n(sample, channel_count as usize)
+        })
+        .collect()
+}
+
+fn read_all<S: Sound>(sound: &mut S) -> Vec<i16> {
+    let mut output = Vec::new();
+    loop {
+        match sound.next_sample().unwrap() {
+            NextSample::Sample(s) => output.push(s),
+            NextSample::MetadataChanged => {}
+            NextSample::Paused | NextSample::Finished => return output,
+        }
+    }
+}
+
+/// Estimate the frequency of a mono signal by counting rising zero crossings.
+fn frequency(samples: &[i16]) -> f64 {
+    let crossings = samples.windows(2).filter(|w| w[0] < 0 && w[1] >= 0).count();
+    crossings as f64 * SAMPLE_RATE as f64 / samples.len() as f64
+}
+
+fn stretch(samples: Vec<i16>, tempo: f32) -> Vec<i16> {
+    let sound = MemorySound::from_samples(Arc::new(samples), 1, SAMPLE_RATE);
+    read_all(&mut sound.with_adjustable_tempo_of(tempo))
+}
+
+#[test]
+fn default_tempo_passes_through() {
+    let samples = sine(440.0, 1000, 2);
+    let sound = MemorySound::from_samples(Arc::new(samples.clone()), 2, SAMPLE_RATE);
+    let mut stretched = sound.with_adjustable_tempo();
+    assert_eq!(stretched.tempo(), 1.0);
+    assert_eq!(read_all(&mut stretched), samples);
+}
+
+#[test]
+fn faster_tempo_keeps_pitch() {
+    let samples = sine(440.0, 16000, 1);
+    let output = stretch(samples.clone(), 2.0);
+    let expected_len = samples.len() / 2;
+    assert!(
+        output.len().abs_diff(expected_len) < 200,
+        "{}",
+        output.len()
+    );
+    assert!((frequency(&output) - 440.0).abs() < 10.0);
+}
+
+#[test]
+fn slower_tempo_keeps_pitch() {
+    let samples = sine(440.0, 8000, 1);
+    let output = stretch(samples.clone(), 0.5);
+    let expected_len = samples.len() * 2;
+    assert!(
+        output.len().abs_diff(expected_len) < 200,
+        "{}",
+        output.len()
+    );
+    assert!((frequency(&output) - 440.0).abs() < 10.0);
+}
+
+#[test]
+fn stretching_keeps_level() {
+    let output = stretch(sine(440.0, 16000, 1), 1.5);
+    let peak = output[400..output.len() - 400]
+        .iter()
+        .map(|s| s.unsigned_abs())
+        .max()
+        .unwrap();
+    assert!((9000..=10500).contains(&peak), "{peak}");
+}
+
+#[test]
+fn returning_to_normal_tempo_is_continuous() {
+    let samples = sine(440.0, 4000, 1);
+    let sound = MemorySound::from_samples(Arc::new(samples.clone()), 1, SAMPLE_RATE);
+    let mut stretched = sound.with_adjustable_tempo_of(1.25);
+    let mut output = Vec::new();
+    for _ in 0..1000 {
+        let NextSample::Sample(s) = stretched.next_sample().unwrap() else {
+            panic!("expected sample");
+        };
+        output.push(s);
+    }
+    stretched.set_tempo(1.0);
+    output.extend(read_all(&mut stretched));
+    // No jumps larger than a sine of this frequency can make between samples.
+    let max_step = output
+        .windows(2)
+        .map(|w| (w[1] as i32 - w[0] as i32).abs())
+        .max()
+        .unwrap();
+    assert!(max_step < 4000, "{max_step}");
+}
+
+#[test]
+fn stereo_channels_stay_in_order() {
+    let samples: Vec<i16> = (0..4000).flat_map(|_| [1000, -1000]).collect();
+    let sound = MemorySound::from_samples(Arc::new(samples), 2, SAMPLE_RATE);
+    let output = read_all(&mut sound.with_adjustable_tempo_of(1.5));
+    assert!(output.len().is_multiple_of(2));
+    for frame in output[200..output.len() - 200].chunks(2) {
+        assert!((985..=1015).contains(&frame[0]), "{frame:?}");
+        assert!((-1015..=-985).contains(&frame[1]), "{frame:?}");
+    }
+}
+
+#[test]
+fn combines_with_speed() {
+    let samples = sine(440.0, 16000, 1);
+    let sound = MemorySound::from_samples(Arc::new(samples), 1, SAMPLE_RATE);
+    let mut sound = sound.with_adjustable_speed().with_adjustable_tempo();
+    sound.set_speed(2.0);
+    sound.set_tempo(0.5);
+    assert_eq!(sound.sample_rate(), SAMPLE_RATE * 2);
+    let output = read_all(&mut sound);
+    // Speed doubles the sample rate and the tempo doubles the length so the
+    // duration is unchanged.
+    assert!(output.len().abs_diff(32000) < 400, "{}", output.len());
+}
+
+fn read_samples<S: Sound + ?Sized>(sound: &mut S, num_samples: usize) -> Vec<i16> {
+    let mut output = Vec::with_capacity(num_samples);
+    while output.len() < num_samples {
+        match sound.next_sample().unwrap() {
+            NextSample::Sample(s) => output.push(s),
+            NextSample::MetadataChanged => {}
+            next => panic!("unexpected {next:?}"),
+        }
+    }
+    output
+}
+
+#[test]
+fn seeking_drops_buffered_samples() {
+    let new_sound = || {
+        MemorySound::from_samples(Arc::new(sine(440.0, 8000, 1)), 1, SAMPLE_RATE)
+            .with_adjustable_tempo_of(1.5)
+    };
+    let mut sound: Box<dyn Sound> = Box::new(new_sound());
+    let mut expected = new_sound();
+    assert_eq!(
+        read_samples(&mut sound, 500),
+        read_samples(&mut expected, 500)
+    );
+    // Only seeking has an effect.
+    assert!(sound.as_seekable().is_some());
+    assert_eq!(
+        read_samples(&mut sound, 500),
+        read_samples(&mut expected, 500)
+    );
+
+    sound.seek(std::time::Duration::ZERO).unwrap();
+    assert_eq!(
+        read_samples(&mut sound, 1000),
+        read_samples(&mut new_sound(), 1000)
+    );
+}
@@ -27,3 +27,82 @@ fn test_saturation() {
         crate::NextSample::Sample(i16::MAX)
     );
 }
+
+#[test]
+fn f32_samples_keep_precision() {
+    let mut first = ConstantValueSound::new(1).with_adjustable_volume();
+    first.set_volume(0.25);
+    let mut buffer = [0.0; 2];
+    first.next_samples_f32(&mut buffer).unwrap();
+    assert_eq!(buffer, [0.25 / i16::MAX as f32; 2]);
+}
+
+fn next_frame<S: Sound>(sound: &mut S) -> [i16; 2] {
+    let mut frame = [0; 2];
+    for sample in &mut frame {
+        let crate::NextSample::Sample(s) = sound.next_sample().unwrap() else {
+            panic!("expected sample");
+        };
+        *sample = s;
+    }
+    frame
+}
+
+/// 10 frames at 10kHz.
+const TEN_FRAMES: Duration = Duration::from_millis(1);
+
+fn constant(value: i16) -> ConstantValueSound {
+    let mut sound = ConstantValueSound::new(value);
+    sound.sample_rate = 10_000;
+    sound
+}
+
+#[test]
+fn set_volume_over_ramps_per_frame() {
+    let mut sound = constant(1000).with_adjustable_volume();
+    sound.set_volume_over(0.0, TEN_FRAMES);
+    assert_eq!(sound.volume(), 0.0);
+    let frames: Vec<_> = (0..12).map(|_| next_frame(&mut sound)).collect();
+    assert_eq!(frames[0], [1000, 1000]);
+    assert!((495..=505).contains(&frames[5][0]), "{:?}", frames[5]);
+    for frame in &frames {
+        assert_eq!(frame[0], frame[1]);
+    }
+    for pair in frames.windows(2) {
+        assert!(pair[0][0] >= pair[1][0]);
+    }
+    assert_eq!(frames[10], [0, 0]);
+    assert_eq!(frames[11], [0, 0]);
+}
+
+#[test]
+fn smoothing_ramps_set_volume() {
+    let mut one_by_one = constant(1000).with_adjustable_volume();
+    one_by_one.set_smoothing(TEN_FRAMES);
+    one_by_one.set_volume(2.0);
+    let expected: Vec<i16> = (0..12).flat_map(|_| next_frame(&mut one_by_one)).collect();
+    assert_eq!(expected[0], 1000);
+    assert_eq!(expected[23], 2000);
+
+    // Buffers that split frames give the same result.
+    let mut buffered = constant(1000).with_adjustable_volume();
+    buffered.set_smoothing(TEN_FRAMES);
+    buffered.set_volume(2.0);
+    let mut samples = [0; 24];
+    for chunk in samples.chunks_mut(5) {
+        buffered.next_samples(chunk).unwrap();
+    }
+    assert_eq!(samples.as_slice(), expected.as_slice());
+}
+
+#[test]
+fn controller_set_volume_over() {
+    let (mut sound, mut controller) = constant(1000).with_adjustable_volume().controllable();
+    controller.set_volume_over(0.5, TEN_FRAMES);
+    sound.on_start_of_batch();
+    assert_eq!(next_frame(&mut sound), [1000, 1000]);
+    for _ in 0..9 {
+        next_frame(&mut sound);
+    }
+    assert_eq!(next_frame(&mut sound), [500, 500]);
+}
@@ -0,0 +1,119 @@
+use std::sync::Arc;
+
+use super::*;
+use crate::sounds::MemorySound;
+
+fn read_one_by_one(mut sound: impl Sound) -> Vec<i16> {
+    let mut samples = Vec::new();
+    while let NextSample::Sample(s) = sound.next_sample().unwrap() {
+        samples.push(s);
+    }
+    samples
+}
+
+fn read_in_blocks(mut sound: impl Sound, block_len: usize) -> Vec<i16> {
+    let mut samples = Vec::new();
+    let mut buffer = vec![0; block_len];
+    loop {
+        let (num_read, next) = sound.next_samples(&mut buffer).unwrap();
+        samples.extend_from_slice(&buffer[..num_read]);
+        if !matches!(next, NextSample::Sample(_)) {
+            return samples;
+        }
+    }
+}
+
+#[test]
+fn mono_to_stereo_blocks_match_one_by_one() {
+    let samples = Arc::new(vec![1, 2, 3, 4, 5]);
+    let expected = read_one_by_one(ChannelCountConverter::new(
+        MemorySound::from_samples(samples.clone(), 1, 1000),
+        2,
+    ));
+    assert_eq!(expected, vec![1, 1, 2, 2, 3, 3, 4, 4, 5, 5]);
+    for block_len in 1..12 {
+        let converter =
+            ChannelCountConverter::new(MemorySound::from_samples(samples.clone(), 1, 1000), 2);
+        assert_eq!(read_in_blocks(converter, block_len), expected);
+    }
+}
+
+#[test]
+fn stereo_to_mono_blocks_match_one_by_one() {
+    let samples = Arc::new(vec![1, 3, 10, 20, -4, -6, 7, 9]);
+    let expected = read_one_by_one(ChannelCountConverter::new(
+        MemorySound::from_samples(samples.clone(), 2, 1000),
+        1,
+    ));
+    assert_eq!(expected, vec![2, 15, -5, 8]);
+    for block_len in 1..6 {
+        let converter =
+            ChannelCountConverter::new(MemorySound::from_samples(samples.clone(), 2, 1000), 1);
+        assert_eq!(read_in_blocks(converter, block_len), expected);
+    }
+}
+
+#[test]
+fn five_one_to_stereo() {
+    let samples = Arc::new(vec![100, 200, 1000, 5000, 300, 400]);
+    let converter = ChannelCountConverter::new(MemorySound::from_samples(samples, 6, 1000), 2);
+    assert_eq!(
+        read_one_by_one(converter),
+        vec![100 + 707 + 212, 200 + 707 + 283]
+    );
+}
+
+#[test]
+fn seven_one_to_stereo() {
+    let samples = Arc::new(vec![100, 200, 0, 5000, 1000, 0, 1000, 0]);
+    let converter = ChannelCountConverter::new(MemorySound::from_samples(samples, 8, 1000), 2);
+    // The back and side channels combine into the 5.1 surround left channel.
+    assert_eq!(read_one_by_one(converter), vec![100 + 1000, 200]);
+}
+
+#[test]
+fn mono_to_five_one_uses_center() {
+    let samples = Arc::new(vec![5, 6]);
+    let converter = ChannelCountConverter::new(MemorySound::from_samples(samples, 1, 1000), 6);
+    assert_eq!(
+        read_one_by_one(converter),
+        vec![0, 0, 5, 0, 0, 0, 0, 0, 6, 0, 0, 0]
+    );
+}
+
+#[test]
+fn unsupported_counts_are_discrete() {
+    let samples = Arc::new(vec![1, 2, 3, 4, 5, 6]);
+    let converter =
+        ChannelCountConverter::new(MemorySound::from_samples(samples.clone(), 3, 1000), 2);
+    assert_eq!(read_one_by_one(converter), vec![1, 2, 4, 5]);
+    let converter = ChannelCountConverter::new(MemorySound::from_samples(samples, 3, 1000), 5);
+    assert_eq!(
+        read_one_by_one(converter),
+        vec![1, 2, 3, 0, 0, 4, 5, 6, 0, 0]
+    );
+}
+
+#[test]
+fn custom_matrix() {
+    let swap = ChannelMatrix::new(2, 2, vec![0.0, 1.0, 1.0, 0.0]).unwrap();
+    let samples = Arc::new(vec![1, 2, 3, 4]);
+    let converter =
+        ChannelCountConverter::with_matrix(MemorySound::from_samples(samples, 2, 1000), swap);
+    assert_eq!(read_in_blocks(converter, 3), vec![2, 1, 4, 3]);
+}
+
+#[test]
+fn custom_matrix_falls_back_to_standard() {
+    let swap = ChannelMatrix::new(2, 2, vec![0.0, 1.0, 1.0, 0.0]).unwrap();
+    let samples = Arc::new(vec![1, 2]);
+    let converter =
+        ChannelCountConverter::with_matrix(MemorySound::from_samples(samples, 1, 1000), swap);
+    assert_eq!(read_one_by_one(converter), vec![1, 1, 2, 2]);
+}
+
+#[test]
+fn invalid_matrix() {
+    assert!(ChannelMatrix::new(2, 2, vec![1.0; 3]).is_err());
+    assert!(ChannelMatrix::new(0, 2, vec![]).is_err());
+}
@@ -0,0 +1,97 @@
+use std::sync::Arc;
+
+use super::*;
+use crate::sounds::MemorySound;
+
+#[test]
+fn position_and_duration_published_each_batch() {
+    let sound = MemorySound::from_samples(Arc::new(vec![1, 2, 3, 4, 5, 6]), 1, 1000);
+    let (mut controllable, controller) = sound.controllable();
+    assert_eq!(controller.duration(), Some(Duration::from_millis(6)));
+    assert_eq!(controller.position(), Some(Duration::ZERO));
+    controllable.next_sample().unwrap();
+    controllable.next_sample().unwrap();
+    // Not updated until the next batch
+    assert_eq!(controller.position(), Some(Duration::ZERO));
+    controllable.on_start_of_batch();
+    assert_eq!(controller.position(), Some(Duration::from_millis(2)));
+}
+
+#[test]
+fn seek_is_applied_on_next_batch() {
+    let sound = MemorySound::from_samples(Arc::new(vec![1, 2, 3, 4, 5, 6]), 1, 1000);
+    let (mut controllable, mut controller) = sound.controllable();
+    controller.seek(Duration::from_millis(4));
+    assert_eq!(
+        controllable.next_sample().unwrap(),
+        crate::NextSample::Sample(1)
+    );
+    controllable.on_start_of_batch();
+    assert_eq!(
+        controllable.next_sample().unwrap(),
+        crate::NextSample::MetadataChanged
+    );
+    assert_eq!(
+        controllable.next_sample().unwrap(),
+        crate::NextSample::Sample(5)
+    );
+    assert_eq!(controller.position(), Some(Duration::from_millis(4)));
+}
+
+#[test]
+fn full_queue_is_reported() {
+    let sound = crate::tests::ConstantValueSound::new(1000).with_adjustable_volume();
+    let (mut controllable, mut controller) = Controllable::with_command_capacity(sound, 2);
+    controller.set_volume(0.5);
+    assert_eq!(
+        controller.try_send_command(Box::new(|s| s.set_volume(0.25))),
+        Ok(())
+    );
+    assert_eq!(
+        controller.try_send_command(Box::new(|s| s.set_volume(0.0))),
+        Err(CommandQueueFull)
+    );
+    controller.set_volume(0.0);
+    assert_eq!(controller.dropped_commands(), 1);
+    controllable.on_start_of_batch();
+    assert_eq!(
+        controllable.next_sample().unwrap(),
+        crate::NextSample::Sample(250)
+    );
+    // There is space again.
+    controller.set_volume(1.0);
+    controllable.on_start_of_batch();
+    assert_eq!(
+        controllable.next_sample().unwrap(),
+        crate::NextSample::Sample(1000)
+    );
+}
+
+#[test]
+fn finished_after_last_controller_dropped_and_commands_run() {
+    let sound = MemorySound::from_samples(Arc::new(vec![1, 2]), 1, 1000).pausable();
+    let (mut controllable, mut controller) = sound.controllable();
+    let mut other = controller.clone();
+    controller.set_paused(true);
+    drop(controller);
+    controllable.on_start_of_batch();
+    assert_eq!(
+        controllable.next_sample().unwrap(),
+        crate::NextSample::Paused
+    );
+    other.set_paused(false);
+    drop(other);
+    controllable.on_start_of_batch();
+    assert_eq!(
+        controllable.next_sample().unwrap(),
+        crate::NextSample::Sample(1)
+    );
+    assert_eq!(
+        controllable.next_sample().unwrap(),
+        crate::NextSample::Sample(2)
+    );
+    assert_eq!(
+        controllable.next_sample().unwrap(),
+        crate::NextSample::Finished
+    );
+}
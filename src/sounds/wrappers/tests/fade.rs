@@ -0,0 +1,116 @@
+use super::*;
+use crate::{sounds::wrappers::SetPaused, tests::ConstantValueSound};
+
+/// Frames are a whole number of microseconds at this rate.
+const SAMPLE_RATE: u32 = 10_000;
+
+fn constant(value: i16) -> ConstantValueSound {
+    let mut sound = ConstantValueSound::new(value);
+    sound.sample_rate = SAMPLE_RATE;
+    sound
+}
+
+fn next_frame<S: Sound>(sound: &mut S) -> [i16; 2] {
+    let mut frame = [0; 2];
+    for sample in &mut frame {
+        let NextSample::Sample(s) = sound.next_sample().unwrap() else {
+            panic!("expected sample");
+        };
+        *sample = s;
+    }
+    frame
+}
+
+/// 10 frames at SAMPLE_RATE.
+fn ten_frames() -> Duration {
+    Duration::from_millis(1)
+}
+
+#[test]
+fn fade_in_ramps_from_silence() {
+    let mut sound = constant(1000).fade_in(ten_frames());
+    let frames: Vec<_> = (0..12).map(|_| next_frame(&mut sound)).collect();
+    assert_eq!(frames[0], [0, 0]);
+    assert_eq!(frames[5], [500, 500]);
+    for pair in frames.windows(2) {
+        assert!(pair[0][0] <= pair[1][0]);
+    }
+    assert_eq!(frames[10], [1000, 1000]);
+    assert_eq!(frames[11], [1000, 1000]);
+    assert_eq!(sound.gain(), 1.0);
+}
+
+#[test]
+fn fade_out_after_finishes() {
+    let mut sound = constant(1000).fade_out_after(ten_frames(), ten_frames());
+    for _ in 0..10 {
+        assert_eq!(next_frame(&mut sound), [1000, 1000]);
+    }
+    // Like fading in, the gain starts at the current value and steps once per
+    // frame.
+    let mut previous = 1100;
+    for _ in 0..10 {
+        let frame = next_frame(&mut sound);
+        assert_eq!(frame[0], frame[1]);
+        assert_eq!(frame[0], previous - 100);
+        previous = frame[0];
+    }
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Finished);
+}
+
+#[test]
+fn fade_out_from_current_gain() {
+    let mut sound = constant(1000).fade_in(ten_frames());
+    for _ in 0..5 {
+        next_frame(&mut sound);
+    }
+    sound.fade_out(ten_frames());
+    assert_eq!(next_frame(&mut sound), [500, 500]);
+    assert_eq!(next_frame(&mut sound), [450, 450]);
+    for _ in 0..8 {
+        next_frame(&mut sound);
+    }
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Finished);
+}
+
+#[test]
+fn pausing_does_not_count() {
+    let mut sound = constant(1000)
+        .pausable()
+        .fade_out_after(ten_frames(), Duration::ZERO);
+    for _ in 0..10 {
+        sound.set_paused(true);
+        assert_eq!(sound.next_sample().unwrap(), NextSample::Paused);
+        sound.set_paused(false);
+        assert_eq!(next_frame(&mut sound), [1000, 1000]);
+    }
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Finished);
+}
+
+#[test]
+fn controller_fades_out_then_finishes() {
+    let (mut sound, mut controller) = constant(1000).fadable().controllable();
+    sound.on_start_of_batch();
+    assert_eq!(next_frame(&mut sound), [1000, 1000]);
+    controller.fade_out(ten_frames());
+    drop(controller);
+    sound.on_start_of_batch();
+    for expected in (1..=10).rev() {
+        assert_eq!(next_frame(&mut sound), [expected * 100; 2]);
+    }
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Finished);
+}
+
+#[test]
+fn sample_rate_change_keeps_remaining_time() {
+    let mut sound = constant(1000).fade_in(ten_frames());
+    for _ in 0..5 {
+        next_frame(&mut sound);
+    }
+    sound.inner_mut().set_sample_rate(SAMPLE_RATE * 2);
+    assert_eq!(sound.next_sample().unwrap(), NextSample::MetadataChanged);
+    for _ in 0..10 {
+        assert!(next_frame(&mut sound)[0] < 1000);
+    }
+    assert_eq!(next_frame(&mut sound), [1000, 1000]);
+}
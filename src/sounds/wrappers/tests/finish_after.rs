@@ -88,3 +88,57 @@ fn test_metadata_change_end() {
     sound.inner_mut().set_channel_count(1);
     assert_eq!(sound.next_sample().unwrap(), crate::NextSample::Finished);
 }
+
+#[test]
+fn test_position_and_duration() {
+    let mut sound = ConstantValueSound::new(1000).finish_after(Duration::from_millis(100));
+    assert_eq!(sound.duration(), Some(Duration::from_millis(100)));
+    assert_eq!(sound.position(), Some(Duration::ZERO));
+    for _ in 0..(44100 * 2 / 20) {
+        sound.next_sample().unwrap();
+    }
+    assert_eq!(sound.position(), Some(Duration::from_millis(50)));
+
+    let inner = crate::sounds::MemorySound::from_samples(std::sync::Arc::new(vec![1; 10]), 1, 1000);
+    let sound = inner.finish_after(Duration::from_millis(100));
+    assert_eq!(sound.duration(), Some(Duration::from_millis(10)));
+}
+
+#[test]
+fn seek_through_box_updates_remaining() {
+    let inner =
+        crate::sounds::MemorySound::from_samples(std::sync::Arc::new(vec![1; 1000]), 1, 1000);
+    let mut sound: Box<dyn Sound> = Box::new(inner.finish_after(Duration::from_millis(100)));
+    sound
+        .as_seekable()
+        .expect("seekable")
+        .seek(Duration::from_millis(60))
+        .unwrap();
+    assert_eq!(sound.position(), Some(Duration::from_millis(60)));
+    assert_eq!(
+        sound.next_sample().unwrap(),
+        crate::NextSample::MetadataChanged
+    );
+    for _ in 0..40 {
+        assert_eq!(sound.next_sample().unwrap(), crate::NextSample::Sample(1));
+    }
+    assert_eq!(sound.next_sample().unwrap(), crate::NextSample::Finished);
+
+    // Seeking back plays up to `duration` again.
+    sound.seek(Duration::from_millis(90)).unwrap();
+    assert_eq!(
+        sound.next_sample().unwrap(),
+        crate::NextSample::MetadataChanged
+    );
+    for _ in 0..10 {
+        assert_eq!(sound.next_sample().unwrap(), crate::NextSample::Sample(1));
+    }
+    assert_eq!(sound.next_sample().unwrap(), crate::NextSample::Finished);
+}
+
+#[test]
+fn not_seekable_if_inner_is_not() {
+    let mut sound = ConstantValueSound::new(1000).finish_after(Duration::from_millis(100));
+    assert!(sound.as_seekable().is_none());
+    assert!(sound.seek(Duration::from_millis(10)).is_err());
+}
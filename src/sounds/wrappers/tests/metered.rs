@@ -0,0 +1,59 @@
+use crate::manager::{BackendSource, Manager};
+use crate::sounds::MemorySound;
+use crate::tests::ConstantValueSound;
+use crate::{NextSample, Sound};
+use std::sync::Arc;
+
+use super::*;
+
+#[test]
+fn levels_are_published_per_channel_at_start_of_batch() {
+    let samples = vec![i16::MAX, 0, -i16::MAX / 2, 0, 0, 0, 0, 0];
+    let (mut sound, meter) = MemorySound::from_samples(Arc::new(samples), 2, 1000).metered();
+    sound.on_start_of_batch();
+    let mut buffer = [0; 8];
+    sound.next_samples(&mut buffer).unwrap();
+    assert_eq!(meter.peak(0), 0.0);
+    sound.on_start_of_batch();
+    assert_eq!(meter.channel_count(), 2);
+    assert_eq!(meter.peak(0), 1.0);
+    assert_eq!(meter.peak(1), 0.0);
+    // sqrt((1 + 0.25) / 4)
+    assert!((meter.rms(0) - 0.559).abs() < 0.001, "{}", meter.rms(0));
+    assert_eq!(meter.rms(1), 0.0);
+    assert_eq!(meter.peak(2), 0.0);
+}
+
+#[test]
+fn next_sample_and_f32_are_measured() {
+    let (mut sound, meter) = ConstantValueSound::new(i16::MAX / 2).metered();
+    sound.next_sample().unwrap();
+    sound.next_sample().unwrap();
+    sound.on_start_of_batch();
+    assert!((meter.peak(0) - 0.5).abs() < 0.001);
+    assert!((meter.rms(1) - 0.5).abs() < 0.001);
+
+    let mut buffer = [0.0; 4];
+    sound.next_samples_f32(&mut buffer).unwrap();
+    sound.on_start_of_batch();
+    assert!((meter.rms(0) - 0.5).abs() < 0.001);
+
+    // Nothing played in the last batch.
+    sound.on_start_of_batch();
+    assert_eq!(meter.peak(0), 0.0);
+    assert_eq!(meter.rms(0), 0.0);
+}
+
+#[test]
+fn meters_renderer_as_backend_source() {
+    let (mut manager, renderer) = Manager::new();
+    let (mut renderer, meter) = Metered::new(renderer);
+    renderer.set_output_channel_count_and_sample_rate(1, 1000);
+    manager.play(Box::new(ConstantValueSound::new(i16::MAX / 4)));
+    renderer.on_start_of_batch();
+    let mut buffer = [0; 4];
+    while let (0, NextSample::MetadataChanged) = renderer.next_samples(&mut buffer).unwrap() {}
+    renderer.on_start_of_batch();
+    assert_eq!(meter.channel_count(), 1);
+    assert!((meter.peak(0) - 0.25).abs() < 0.001);
+}
@@ -0,0 +1,77 @@
+use super::*;
+use crate::tests::ConstantValueSound;
+
+fn next_frame<S: Sound>(sound: &mut S) -> [i16; 2] {
+    let mut frame = [0; 2];
+    for sample in &mut frame {
+        let NextSample::Sample(s) = sound.next_sample().unwrap() else {
+            panic!("expected sample");
+        };
+        *sample = s;
+    }
+    frame
+}
+
+fn mono(value: i16) -> ConstantValueSound {
+    let mut sound = ConstantValueSound::new(value);
+    sound.channel_count = 1;
+    sound
+}
+
+#[test]
+fn mono_is_upmixed_with_constant_power() {
+    let mut sound = mono(10000).with_adjustable_pan();
+    assert_eq!(sound.channel_count(), 2);
+    assert_eq!(next_frame(&mut sound), [7071, 7071]);
+    for pan in [-1.0, -0.6, -0.2, 0.3, 0.7, 1.0] {
+        sound.set_pan(pan);
+        let [left, right] = next_frame(&mut sound);
+        let power = (left as f32 / 10000.0).powi(2) + (right as f32 / 10000.0).powi(2);
+        assert!((power - 1.0).abs() < 0.001, "{pan} {power}");
+        assert_eq!(left > right, pan < 0.0);
+    }
+    sound.set_pan(-1.0);
+    assert_eq!(next_frame(&mut sound), [10000, 0]);
+    sound.set_pan(1.0);
+    assert_eq!(next_frame(&mut sound), [0, 10000]);
+}
+
+#[test]
+fn stereo_center_is_unchanged() {
+    let mut sound = ConstantValueSound::new(1000).with_adjustable_pan();
+    assert_eq!(next_frame(&mut sound), [1000, 1000]);
+    sound.set_pan(-1.0);
+    assert_eq!(next_frame(&mut sound), [1000, 0]);
+    sound.set_pan(0.5);
+    let [left, right] = next_frame(&mut sound);
+    assert_eq!(right, 1000);
+    assert!(left > 0 && left < 1000, "{left}");
+}
+
+#[test]
+fn out_of_range_is_clamped() {
+    let mut sound = mono(1000).with_adjustable_pan_of(-5.0);
+    assert_eq!(sound.pan(), -1.0);
+    assert_eq!(next_frame(&mut sound), [1000, 0]);
+}
+
+#[test]
+fn pan_changes_at_frame_start() {
+    let mut sound = mono(1000).with_adjustable_pan();
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(707));
+    sound.set_pan(1.0);
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(707));
+    assert_eq!(next_frame(&mut sound), [0, 1000]);
+}
+
+#[test]
+fn controller_sets_pan_and_volume() {
+    let (mut sound, mut controller) = mono(1000)
+        .with_adjustable_volume()
+        .with_adjustable_pan()
+        .controllable();
+    controller.set_pan(1.0);
+    controller.set_volume(0.5);
+    sound.on_start_of_batch();
+    assert_eq!(next_frame(&mut sound), [0, 500]);
+}
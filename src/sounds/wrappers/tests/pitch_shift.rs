@@ -0,0 +1,190 @@
+use std::sync::Arc;
+
+use crate::{
+    sounds::{wrappers::SetPaused, MemorySound},
+    NextSample, Sound,
+};
+
+use super::*;
+
+const SAMPLE_RATE: u32 = 8000;
+
+fn sine(freq: f64, num_frames: usize, channel_count: u16) -> Vec<i16> {
+    (0..num_frames)
+        .flat_map(|n| {
+            let t = n as f64 / SAMPLE_RATE as f64;
+            let sample = ((2.0 * std::f64::consts::PI * freq * t).sin() * 10000.0) as i16;
+            std::iter::repeat_n(sample, channel_count as usize)
+        })
+        .collect()
+}
+
+fn read_all<S: Sound>(sound: &mut S) -> Vec<i16> {
+    let mut output = Vec::new();
+    loop {
+        match sound.next_sample().unwrap() {
+            NextSample::Sample(s) => output.push(s),
+            NextSample::MetadataChanged => {}
+            NextSample::Paused | NextSample::Finished => return output,
+        }
+    }
+}
+
+/// Estimate the frequency of a mono signal by counting rising zero crossings.
+fn frequency(samples: &[i16]) -> f64 {
+    let crossings = samples.windows(2).filter(|w| w[0] < 0 && w[1] >= 0).count();
+    crossings as f64 * SAMPLE_RATE as f64 / samples.len() as f64
+}
+
+#[test]
+fn default_pitch_passes_through() {
+    let samples = sine(440.0, 1000, 2);
+    let sound = MemorySound::from_samples(Arc::new(samples.clone()), 2, SAMPLE_RATE);
+    let mut shifted = sound.with_adjustable_pitch();
+    assert_eq!(shifted.pitch(), 1.0);
+    assert_eq!(read_all(&mut shifted), samples);
+}
+
+#[test]
+fn higher_pitch_keeps_length() {
+    let sound = MemorySound::from_samples(Arc::new(sine(300.0, 16000, 1)), 1, SAMPLE_RATE);
+    let mut shifted = sound.with_adjustable_pitch_of(1.5);
+    assert_eq!(shifted.sample_rate(), SAMPLE_RATE);
+    let output = read_all(&mut shifted);
+    assert!(output.len().abs_diff(16000) < 200, "{}", output.len());
+    let freq = frequency(&output);
+    assert!((freq - 450.0).abs() < 10.0, "{freq}");
+}
+
+#[test]
+fn semitones() {
+    let sound = MemorySound::from_samples(Arc::new(sine(440.0, 16000, 1)), 1, SAMPLE_RATE);
+    let mut shifted = sound.with_adjustable_pitch();
+    shifted.set_pitch_semitones(-12.0);
+    assert_eq!(shifted.pitch(), 0.5);
+    let output = read_all(&mut shifted);
+    assert!(output.len().abs_diff(16000) < 200, "{}", output.len());
+    let freq = frequency(&output);
+    assert!((freq - 220.0).abs() < 10.0, "{freq}");
+}
+
+#[test]
+fn stereo_channels_stay_in_order() {
+    let samples: Vec<i16> = (0..4000).flat_map(|_| [1000, -1000]).collect();
+    let sound = MemorySound::from_samples(Arc::new(samples), 2, SAMPLE_RATE);
+    let output = read_all(&mut sound.with_adjustable_pitch_of(1.3));
+    assert!(output.len().is_multiple_of(2));
+    for frame in output[400..output.len() - 400].chunks(2) {
+        assert!((985..=1015).contains(&frame[0]), "{frame:?}");
+        assert!((-1015..=-985).contains(&frame[1]), "{frame:?}");
+    }
+}
+
+#[test]
+fn resumes_after_pause() {
+    let samples = sine(440.0, 4000, 1);
+    let sound = MemorySound::from_samples(Arc::new(samples.clone()), 1, SAMPLE_RATE);
+    let mut shifted = sound.pausable().with_adjustable_pitch_of(1.2);
+    let mut output = Vec::new();
+    for _ in 0..1000 {
+        let NextSample::Sample(s) = shifted.next_sample().unwrap() else {
+            panic!("expected sample");
+        };
+        output.push(s);
+    }
+    shifted.set_paused(true);
+    assert_eq!(shifted.next_sample().unwrap(), NextSample::Paused);
+    shifted.set_paused(false);
+    output.extend(read_all(&mut shifted));
+    assert!(output.len().abs_diff(4000) < 200, "{}", output.len());
+    let max_step = output
+        .windows(2)
+        .map(|w| (w[1] as i32 - w[0] as i32).abs())
+        .max()
+        .unwrap();
+    assert!(max_step < 5000, "{max_step}");
+}
+
+#[test]
+fn qualities_keep_length_and_pitch() {
+    for quality in [
+        ResampleQuality::Linear,
+        ResampleQuality::Cubic,
+        ResampleQuality::Sinc,
+    ] {
+        let sound = MemorySound::from_samples(Arc::new(sine(300.0, 16000, 1)), 1, SAMPLE_RATE);
+        let mut shifted = PitchShift::with_quality(sound, 1.5, quality);
+        assert_eq!(shifted.quality(), quality);
+        let output = read_all(&mut shifted);
+        assert!(
+            output.len().abs_diff(16000) < 200,
+            "{quality:?}: {}",
+            output.len()
+        );
+        let freq = frequency(&output);
+        assert!((freq - 450.0).abs() < 10.0, "{quality:?}: {freq}");
+    }
+}
+
+/// Energy of the output relative to the input when shifting a tone above the
+/// Nyquist frequency. Ideally the output is silent.
+fn alias_level(quality: ResampleQuality) -> f64 {
+    let input = sine(3000.0, 16000, 1);
+    let energy = |samples: &[i16]| {
+        samples[2000..samples.len() - 2000]
+            .iter()
+            .map(|s| (*s as f64).powi(2))
+            .sum::<f64>()
+            / (samples.len() - 4000) as f64
+    };
+    let input_energy = energy(&input);
+    let sound = MemorySound::from_samples(Arc::new(input), 1, SAMPLE_RATE);
+    let output = read_all(&mut PitchShift::with_quality(sound, 1.5, quality));
+    energy(&output) / input_energy
+}
+
+#[test]
+fn sinc_quality_has_less_aliasing() {
+    let linear = alias_level(ResampleQuality::Linear);
+    let sinc = alias_level(ResampleQuality::Sinc);
+    assert!(linear > 0.1, "linear: {linear}");
+    assert!(sinc < 1e-3, "sinc: {sinc}");
+}
+
+fn read_samples<S: Sound + ?Sized>(sound: &mut S, num_samples: usize) -> Vec<i16> {
+    let mut output = Vec::with_capacity(num_samples);
+    while output.len() < num_samples {
+        match sound.next_sample().unwrap() {
+            NextSample::Sample(s) => output.push(s),
+            NextSample::MetadataChanged => {}
+            next => panic!("unexpected {next:?}"),
+        }
+    }
+    output
+}
+
+#[test]
+fn seeking_drops_buffered_frames() {
+    let new_sound = || {
+        MemorySound::from_samples(Arc::new(sine(440.0, 8000, 2)), 2, SAMPLE_RATE)
+            .with_adjustable_pitch_of(1.3)
+    };
+    let mut sound: Box<dyn Sound> = Box::new(new_sound());
+    let mut expected = new_sound();
+    assert_eq!(
+        read_samples(&mut sound, 500),
+        read_samples(&mut expected, 500)
+    );
+    // Only seeking has an effect.
+    assert!(sound.as_seekable().is_some());
+    assert_eq!(
+        read_samples(&mut sound, 500),
+        read_samples(&mut expected, 500)
+    );
+
+    sound.seek(std::time::Duration::ZERO).unwrap();
+    assert_eq!(
+        read_samples(&mut sound, 1000),
+        read_samples(&mut new_sound(), 1000)
+    );
+}
@@ -121,3 +121,203 @@ fn catch_metadata_changed_when_passing_through() {
     assert_eq!(converted.next_sample().unwrap(), NextSample::Sample(6));
     assert_eq!(converted.channel_count(), 1);
 }
+
+#[test]
+fn rate_changed_to_output_rate_before_first_sample() {
+    let sound = Sawtooth::new(1, 2000).with_adjustable_speed();
+    let mut converted = SampleRateConverter::new(sound, 1000);
+    // The inner rate already matches but MetadataChanged has not been read.
+    converted.inner_mut().set_speed(0.5);
+    assert_eq!(converted.next_sample().unwrap(), NextSample::Sample(0));
+    assert_eq!(converted.next_sample().unwrap(), NextSample::Sample(1));
+}
+
+/// A mono sine sweep from `start_freq` to `end_freq` Hz.
+fn sweep(sample_rate: u32, start_freq: f64, end_freq: f64, num_samples: usize) -> Vec<i16> {
+    let duration = num_samples as f64 / sample_rate as f64;
+    (0..num_samples)
+        .map(|n| {
+            let t = n as f64 / sample_rate as f64;
+            let phase = 2.0
+                * std::f64::consts::PI
+                * (start_freq * t + (end_freq - start_freq) * t * t / (2.0 * duration));
+            (phase.sin() * 16000.0) as i16
+        })
+        .collect()
+}
+
+fn resample(samples: Vec<i16>, from_rate: u32, to_rate: u32, quality: ResampleQuality) -> Vec<i16> {
+    let sound =
+        crate::sounds::MemorySound::from_samples(std::sync::Arc::new(samples), 1, from_rate);
+    let mut converted = SampleRateConverter::with_quality(sound, to_rate, quality);
+    let mut output = Vec::new();
+    while let NextSample::Sample(s) = converted.next_sample().unwrap() {
+        output.push(s);
+    }
+    output
+}
+
+/// Fraction of the energy of `samples` (Hann windowed) at frequencies between
+/// `low_freq` and `high_freq`.
+fn energy_fraction(samples: &[i16], sample_rate: u32, low_freq: f64, high_freq: f64) -> f64 {
+    let n = samples.len();
+    let windowed: Vec<f64> = samples
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            let w = 0.5 - 0.5 * (2.0 * std::f64::consts::PI * i as f64 / n as f64).cos();
+            *s as f64 * w
+        })
+        .collect();
+    let mut total = 0.0;
+    let mut in_band = 0.0;
+    for bin in 1..n / 2 {
+        let (mut re, mut im) = (0.0, 0.0);
+        for (i, x) in windowed.iter().enumerate() {
+            let angle = 2.0 * std::f64::consts::PI * (bin * i % n) as f64 / n as f64;
+            re += x * angle.cos();
+            im -= x * angle.sin();
+        }
+        let power = re * re + im * im;
+        total += power;
+        let freq = bin as f64 * sample_rate as f64 / n as f64;
+        if freq >= low_freq && freq <= high_freq {
+            in_band += power;
+        }
+    }
+    in_band / total
+}
+
+/// Measure the energy of images above the input Nyquist frequency when
+/// converting a 22.05 kHz sweep to 48 kHz.
+fn upsampling_image_fraction(quality: ResampleQuality) -> f64 {
+    let input = sweep(22050, 200.0, 10000.0, 1200);
+    let output = resample(input, 22050, 48000, quality);
+    // Skip the start so filters have settled.
+    energy_fraction(&output[256..256 + 2048], 48000, 11600.0, 24000.0)
+}
+
+#[test]
+fn sinc_upsampling_has_less_imaging() {
+    let linear = upsampling_image_fraction(ResampleQuality::Linear);
+    let cubic = upsampling_image_fraction(ResampleQuality::Cubic);
+    let sinc = upsampling_image_fraction(ResampleQuality::Sinc);
+    assert!(cubic < linear, "cubic: {cubic} linear: {linear}");
+    assert!(sinc < cubic, "sinc: {sinc} cubic: {cubic}");
+    assert!(sinc < 1e-4, "sinc: {sinc}");
+}
+
+/// Measure how much of a 48 kHz sweep that is entirely above the 22.05 kHz
+/// Nyquist frequency aliases into the output. Ideally the output is silent.
+fn downsampling_alias_level(quality: ResampleQuality) -> f64 {
+    let input = sweep(48000, 13000.0, 20000.0, 4800);
+    let input_energy: f64 = input.iter().map(|s| (*s as f64).powi(2)).sum::<f64>() / 48000.0;
+    let output = resample(input, 48000, 22050, quality);
+    let output_energy: f64 = output[64..output.len() - 64]
+        .iter()
+        .map(|s| (*s as f64).powi(2))
+        .sum::<f64>()
+        / 22050.0;
+    output_energy / input_energy
+}
+
+#[test]
+fn sinc_downsampling_has_less_aliasing() {
+    let linear = downsampling_alias_level(ResampleQuality::Linear);
+    let sinc = downsampling_alias_level(ResampleQuality::Sinc);
+    assert!(linear > 0.1, "linear: {linear}");
+    assert!(sinc < 1e-3, "sinc: {sinc}");
+}
+
+#[test]
+fn interpolated_qualities_keep_length_and_level() {
+    for quality in [ResampleQuality::Cubic, ResampleQuality::Sinc] {
+        let output = resample(vec![1000; 1000], 1000, 3000, quality);
+        assert_eq!(output.len(), 3000, "{quality:?}");
+        // Away from the edges a constant is unchanged.
+        assert!(output[100..2900].iter().all(|s| *s == 1000), "{quality:?}");
+
+        let output = resample(vec![1000; 3000], 3000, 1000, quality);
+        assert_eq!(output.len(), 1000, "{quality:?}");
+    }
+}
+
+#[test]
+fn interpolated_pause_resumes_without_losing_samples() {
+    let sound = Sawtooth::new(2, 1000).pausable();
+    let mut converted = SampleRateConverter::with_quality(sound, 2000, ResampleQuality::Cubic);
+    let mut expected =
+        SampleRateConverter::with_quality(Sawtooth::new(2, 1000), 2000, ResampleQuality::Cubic);
+    for _ in 0..10 {
+        assert_eq!(
+            converted.next_sample().unwrap(),
+            expected.next_sample().unwrap()
+        );
+    }
+    converted.inner_mut().set_paused(true);
+    let mut num_buffered = 0;
+    while let NextSample::Sample(s) = converted.next_sample().unwrap() {
+        assert_eq!(NextSample::Sample(s), expected.next_sample().unwrap());
+        num_buffered += 1;
+    }
+    assert!(num_buffered < 4);
+    converted.inner_mut().set_paused(false);
+    for _ in 0..10 {
+        assert_eq!(
+            converted.next_sample().unwrap(),
+            expected.next_sample().unwrap()
+        );
+    }
+}
+
+/// Read `num_samples` samples from `sound` changing the speed every 10
+/// samples.
+fn read_changing_speed<S: Sound + SetSpeed>(sound: &mut S, num_samples: usize) -> Vec<i16> {
+    let mut output = Vec::with_capacity(num_samples);
+    while output.len() < num_samples {
+        if output.len() % 10 == 0 {
+            let speed = 1.0 + (output.len() / 10 % 7) as f32 * 0.05;
+            sound.set_speed(speed);
+        }
+        match sound.next_sample().unwrap() {
+            NextSample::Sample(s) => output.push(s),
+            NextSample::MetadataChanged => {}
+            next => panic!("unexpected {next:?}"),
+        }
+    }
+    output
+}
+
+#[test]
+fn rate_changes_keep_interpolated_history() {
+    for quality in [ResampleQuality::Cubic, ResampleQuality::Sinc] {
+        let sound = crate::tests::ConstantValueSound::new(10000).with_adjustable_speed();
+        let mut converted = SampleRateConverter::with_quality(sound, 48000, quality);
+        let output = read_changing_speed(&mut converted, 4000);
+        // The first frames start from the first input frame rather than
+        // silence and nothing is lost at each rate change.
+        assert!(
+            output.iter().all(|s| (9998..=10002).contains(s)),
+            "{quality:?}: {:?}",
+            output.iter().min()
+        );
+    }
+}
+
+#[cfg(feature = "assert-no-alloc")]
+#[test]
+fn rate_changes_do_not_allocate() {
+    let sound = crate::tests::ConstantValueSound::new(10000).with_adjustable_speed();
+    let mut converted = SampleRateConverter::with_quality(sound, 48000, ResampleQuality::Sinc);
+    let mut output = Vec::with_capacity(4000);
+    assert_no_alloc::assert_no_alloc(|| {
+        while output.len() < 4000 {
+            if output.len() % 10 == 0 {
+                converted.set_speed(0.5 + (output.len() / 10 % 7) as f32 * 0.25);
+            }
+            if let NextSample::Sample(s) = converted.next_sample().unwrap() {
+                output.push(s);
+            }
+        }
+    });
+}
@@ -0,0 +1,107 @@
+use crate::manager::{BackendSource, Manager};
+use crate::sounds::MemorySound;
+use crate::{NextSample, Sound};
+use std::sync::Arc;
+
+use super::*;
+
+const SAMPLE_RATE: u32 = 8000;
+const SIZE: usize = 64;
+
+/// A full scale sine at the center of bin `bin`.
+fn sine(bin: usize, channel_count: u16, num_frames: usize) -> MemorySound {
+    let samples = (0..num_frames)
+        .flat_map(|i| {
+            let phase = 2.0 * PI * bin as f32 * i as f32 / SIZE as f32;
+            let sample = (phase.sin() * i16::MAX as f32) as i16;
+            std::iter::repeat_n(sample, channel_count as usize)
+        })
+        .collect();
+    MemorySound::from_samples(Arc::new(samples), channel_count, SAMPLE_RATE)
+}
+
+fn peak_bin(magnitudes: &[f32]) -> usize {
+    magnitudes
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.total_cmp(b.1))
+        .unwrap()
+        .0
+}
+
+#[test]
+fn fft_matches_dft() {
+    let size = 16;
+    let fft = Fft::new(size);
+    let input: Vec<f32> = (0..size).map(|i| ((i * 7) % 5) as f32 - 2.0).collect();
+    let mut real = input.clone();
+    let mut imag = vec![0.0; size];
+    fft.process(&mut real, &mut imag);
+    for k in 0..size {
+        let (mut re, mut im) = (0.0, 0.0);
+        for (n, x) in input.iter().enumerate() {
+            let angle = -2.0 * PI * (k * n) as f32 / size as f32;
+            re += x * angle.cos();
+            im += x * angle.sin();
+        }
+        assert!((real[k] - re).abs() < 1e-3, "{k}: {} {re}", real[k]);
+        assert!((imag[k] - im).abs() < 1e-3, "{k}: {} {im}", imag[k]);
+    }
+}
+
+#[test]
+fn sine_peaks_in_its_bin() {
+    for window in [
+        Window::Rectangular,
+        Window::Hann,
+        Window::Hamming,
+        Window::Blackman,
+    ] {
+        let (mut tap, handle) = sine(8, 2, SIZE).with_spectrum_tap(SIZE, window);
+        assert_eq!(handle.bin_count(), SIZE / 2 + 1);
+        let mut buffer = [0; SIZE * 2];
+        assert_eq!(tap.next_samples(&mut buffer).unwrap().0, SIZE * 2);
+        assert!(handle.magnitudes().iter().all(|m| *m == 0.0));
+        tap.on_start_of_batch();
+        let magnitudes = handle.magnitudes();
+        assert_eq!(peak_bin(&magnitudes), 8, "{window:?}");
+        assert!(
+            (magnitudes[8] - 1.0).abs() < 0.01,
+            "{window:?} {magnitudes:?}"
+        );
+        assert!(magnitudes[20] < 0.01, "{window:?} {magnitudes:?}");
+        assert_eq!(handle.bin_frequency(8), 1000.0);
+    }
+}
+
+#[test]
+fn only_the_most_recent_frames_are_analyzed() {
+    let mut list = crate::sounds::SoundList::new();
+    list.add(Box::new(sine(4, 1, SIZE * 2)));
+    list.add(Box::new(sine(12, 1, SIZE)));
+    let (mut tap, handle) = list.with_spectrum_tap(SIZE, Window::Hann);
+    tap.on_start_of_batch();
+    while tap.next_sample().unwrap() != NextSample::Finished {}
+    tap.on_start_of_batch();
+    assert_eq!(peak_bin(&handle.magnitudes()), 12);
+}
+
+#[test]
+fn analyzes_renderer_as_backend_source() {
+    let (mut manager, renderer) = Manager::new();
+    let (mut renderer, handle) = SpectrumTap::new(renderer, SIZE, Window::Hann);
+    renderer.set_output_channel_count_and_sample_rate(1, SAMPLE_RATE);
+    manager.play(Box::new(sine(16, 1, SIZE)));
+    renderer.on_start_of_batch();
+    let mut buffer = [0.0; SIZE];
+    let mut num_filled = 0;
+    while num_filled < SIZE {
+        num_filled += renderer
+            .next_samples_f32(&mut buffer[num_filled..])
+            .unwrap()
+            .0;
+    }
+    renderer.on_start_of_batch();
+    assert_eq!(peak_bin(&handle.magnitudes()), 16);
+    assert_eq!(handle.bin_frequency(16), 2000.0);
+}
@@ -0,0 +1,93 @@
+use crate::manager::{BackendSource, Manager};
+use crate::sounds::{MemorySound, SoundList};
+use crate::tests::ConstantValueSound;
+use crate::{NextSample, Sound};
+use std::sync::Arc;
+
+use super::*;
+
+fn memory_sound(samples: Vec<i16>, channel_count: u16, sample_rate: u32) -> MemorySound {
+    MemorySound::from_samples(Arc::new(samples), channel_count, sample_rate)
+}
+
+#[test]
+fn samples_pass_through_and_are_copied() {
+    let (mut tee, mut reader) = memory_sound(vec![1, 2, 3, 4, -5, 6], 2, 1000).tee(16);
+    let mut buffer = [0; 4];
+    assert_eq!(
+        tee.next_samples(&mut buffer).unwrap(),
+        (4, NextSample::Sample(4))
+    );
+    assert_eq!(buffer, [1, 2, 3, 4]);
+    assert_eq!(tee.next_sample().unwrap(), NextSample::Sample(-5));
+    assert_eq!(tee.next_sample().unwrap(), NextSample::Sample(6));
+
+    let mut read = [0; 8];
+    assert_eq!(reader.read(&mut read), TeeRead::Samples(6));
+    assert_eq!(read[..6], [1, 2, 3, 4, -5, 6]);
+    assert_eq!(reader.channel_count(), 2);
+    assert_eq!(reader.sample_rate(), 1000);
+    assert_eq!(reader.read(&mut read), TeeRead::Empty);
+    assert_eq!(reader.dropped_samples(), 0);
+}
+
+#[test]
+fn whole_frames_are_dropped_when_full() {
+    let (mut tee, mut reader) = ConstantValueSound::new(7).tee(5);
+    let mut buffer = [0; 8];
+    tee.next_samples(&mut buffer).unwrap();
+    assert_eq!(buffer, [7; 8]);
+    assert_eq!(reader.dropped_samples(), 4);
+    let mut read = [0; 3];
+    assert_eq!(reader.read(&mut read), TeeRead::Samples(3));
+    assert_eq!(reader.read(&mut read), TeeRead::Samples(1));
+    // There is space again.
+    tee.next_samples(&mut buffer[..2]).unwrap();
+    assert_eq!(reader.read(&mut read), TeeRead::Samples(2));
+    assert_eq!(reader.dropped_samples(), 4);
+}
+
+#[test]
+fn metadata_changes_are_passed_to_reader() {
+    let mut list = SoundList::new();
+    list.add(Box::new(memory_sound(vec![1, 2], 2, 1000)));
+    list.add(Box::new(memory_sound(vec![3], 1, 2000)));
+    let (mut tee, mut reader) = list.tee(16);
+    let mut samples = Vec::new();
+    loop {
+        match tee.next_sample().unwrap() {
+            NextSample::Finished => break,
+            NextSample::Sample(s) => samples.push(s),
+            NextSample::MetadataChanged | NextSample::Paused => {}
+        }
+    }
+    assert_eq!(samples, [1, 2, 3]);
+
+    let mut read = [0; 8];
+    assert_eq!(reader.read(&mut read), TeeRead::MetadataChanged);
+    assert_eq!(reader.channel_count(), 2);
+    assert_eq!(reader.read(&mut read), TeeRead::Samples(2));
+    assert_eq!(read[..2], [1, 2]);
+    assert_eq!(reader.read(&mut read), TeeRead::MetadataChanged);
+    assert_eq!((reader.channel_count(), reader.sample_rate()), (1, 2000));
+    assert_eq!(reader.read(&mut read), TeeRead::Samples(1));
+    assert_eq!(read[0], 3);
+    assert_eq!(reader.read(&mut read), TeeRead::Empty);
+}
+
+#[test]
+fn captures_renderer_as_backend_source() {
+    let (mut manager, renderer) = Manager::new();
+    let (mut renderer, mut reader) = Tee::new(renderer, 64);
+    renderer.set_output_channel_count_and_sample_rate(1, 1000);
+    manager.play(Box::new(ConstantValueSound::new(100)));
+    renderer.on_start_of_batch();
+    let mut buffer = [0.0; 4];
+    while let (0, NextSample::MetadataChanged) = renderer.next_samples_f32(&mut buffer).unwrap() {}
+
+    let mut read = [0; 8];
+    assert_eq!(reader.read(&mut read), TeeRead::MetadataChanged);
+    assert_eq!((reader.channel_count(), reader.sample_rate()), (1, 1000));
+    assert_eq!(reader.read(&mut read), TeeRead::Samples(4));
+    assert_eq!(read[..4], [100; 4]);
+}
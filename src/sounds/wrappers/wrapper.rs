@@ -1,6 +1,9 @@
 use crate::Sound;
 
-use super::{AddSound, ClearSounds, SetPaused, SetSpeed, SetVolume};
+use super::{
+    AddSound, ClearSounds, FadeOut, Seekable, SetPan, SetPaused, SetPitch, SetSpeed, SetTempo,
+    SetVolume,
+};
 
 /// Super trait that implements all traits that a wrapper Sound should
 /// transparently pass through if implemented by the inner sound. If you have
@@ -21,6 +24,16 @@ pub trait Wrapper {
     fn into_inner(self) -> Self::Inner;
 }
 
+impl<S> SetPan for S
+where
+    S: Wrapper,
+    <S as Wrapper>::Inner: SetPan,
+{
+    fn set_pan(&mut self, pan: f32) {
+        self.inner_mut().set_pan(pan)
+    }
+}
+
 impl<S> SetPaused for S
 where
     S: Wrapper,
@@ -39,6 +52,40 @@ where
     fn set_speed(&mut self, new: f32) {
         self.inner_mut().set_speed(new)
     }
+
+    fn set_speed_over(&mut self, new: f32, duration: std::time::Duration) {
+        self.inner_mut().set_speed_over(new, duration)
+    }
+}
+
+impl<S> FadeOut for S
+where
+    S: Wrapper,
+    <S as Wrapper>::Inner: FadeOut,
+{
+    fn fade_out(&mut self, duration: std::time::Duration) {
+        self.inner_mut().fade_out(duration)
+    }
+}
+
+impl<S> SetPitch for S
+where
+    S: Wrapper,
+    <S as Wrapper>::Inner: SetPitch,
+{
+    fn set_pitch(&mut self, new: f32) {
+        self.inner_mut().set_pitch(new)
+    }
+}
+
+impl<S> SetTempo for S
+where
+    S: Wrapper,
+    <S as Wrapper>::Inner: SetTempo,
+{
+    fn set_tempo(&mut self, new: f32) {
+        self.inner_mut().set_tempo(new)
+    }
 }
 
 impl<S> SetVolume for S
@@ -49,6 +96,10 @@ where
     fn set_volume(&mut self, new: f32) {
         self.inner_mut().set_volume(new)
     }
+
+    fn set_volume_over(&mut self, new: f32, duration: std::time::Duration) {
+        self.inner_mut().set_volume_over(new, duration)
+    }
 }
 
 impl<S> AddSound for S
@@ -70,3 +121,13 @@ where
         self.inner_mut().clear()
     }
 }
+
+impl<S> Seekable for S
+where
+    S: Wrapper,
+    <S as Wrapper>::Inner: Seekable,
+{
+    fn seek(&mut self, position: std::time::Duration) -> Result<(), crate::Error> {
+        self.inner_mut().seek(position)
+    }
+}
@@ -0,0 +1,231 @@
+use std::time::Duration;
+
+use super::*;
+use crate::manager::BackendSource;
+use crate::NextSample;
+
+fn ramp(len: i16) -> Box<crate::sounds::MemorySound> {
+    Box::new(crate::sounds::MemorySound::from_samples(
+        Arc::new((1..=len).collect()),
+        1,
+        10000,
+    ))
+}
+
+fn start() -> (Manager, Renderer) {
+    let (manager, mut renderer) = Manager::new();
+    renderer.set_output_channel_count_and_sample_rate(1, 10000);
+    (manager, renderer)
+}
+
+/// Render a batch of `len` samples.
+fn render(renderer: &mut Renderer, len: usize) -> Vec<i16> {
+    renderer.on_start_of_batch();
+    let mut buffer = vec![0; len];
+    let mut num_filled = 0;
+    while num_filled < len {
+        match renderer.next_samples(&mut buffer[num_filled..]).unwrap() {
+            (num_read, NextSample::Sample(_) | NextSample::MetadataChanged) => {
+                num_filled += num_read
+            }
+            (_, NextSample::Paused | NextSample::Finished) => break,
+        }
+    }
+    buffer
+}
+
+#[test]
+fn play_at_starts_within_batch() {
+    let (mut manager, mut renderer) = start();
+    manager.play(Box::new(crate::tests::ConstantValueSound::new(0)));
+    render(&mut renderer, 8);
+    assert_eq!(renderer.current_frame(), 8);
+    render(&mut renderer, 8);
+    assert_eq!(manager.current_frame(), 8);
+    // 3 frames at 10kHz.
+    manager.play_at(ramp(2), StartTime::After(Duration::from_micros(300)));
+    manager.play_at(ramp(1), StartTime::Frame(28));
+    manager.play_at(ramp(1), StartTime::Frame(0));
+    assert_eq!(render(&mut renderer, 8), [1, 0, 0, 1, 2, 0, 0, 0]);
+    assert_eq!(render(&mut renderer, 8), [0, 0, 0, 0, 1, 0, 0, 0]);
+}
+
+/// With the assert-no-alloc feature this aborts if starting a batch or mixing
+/// allocates, including while reporting events, playing in groups, using
+/// handles and clearing.
+#[test]
+fn next_sample_does_not_allocate() {
+    use crate::sounds::wrappers::SetPaused;
+    use crate::sounds::{Clipping, MasterStage};
+
+    let (mut manager, mut renderer) = start();
+    let _events = manager.events();
+    let mut paused = crate::tests::ConstantValueSound::new(1).pausable();
+    paused.set_paused(true);
+    manager.play(Box::new(paused));
+    manager.play(ramp(2));
+    manager.play(ramp(4));
+    manager.play_at(ramp(1), StartTime::Frame(2));
+    let mut group = manager.create_group();
+    group.play(ramp(1));
+    group.set_volume(1.0);
+    let mut handle = manager.play_with_handle(ramp(1));
+    handle.set_volume(1.0);
+    renderer.on_start_of_batch();
+    assert_eq!(renderer.next_sample().unwrap(), NextSample::MetadataChanged);
+    assert_eq!(next_samples(&mut renderer, 4), [4, 4, 4, 4]);
+    assert_eq!(renderer.next_sample().unwrap(), NextSample::Paused);
+
+    manager.set_master_stage(MasterStage {
+        gain: 1.0,
+        clipping: Clipping::Limiter {
+            look_ahead: Duration::from_micros(200),
+            release: Duration::from_millis(10),
+        },
+    });
+    manager.play(ramp(1));
+    renderer.on_start_of_batch();
+    // Delayed by 2 frames.
+    assert_eq!(next_samples(&mut renderer, 3), [0, 0, 1]);
+
+    manager.clear();
+    group.clear();
+    manager.play_at(ramp(1), StartTime::After(Duration::ZERO));
+    group.play(ramp(1));
+    renderer.on_start_of_batch();
+    assert_eq!(next_samples(&mut renderer, 3), [0, 0, 2]);
+}
+
+/// With the assert-no-alloc feature this aborts if mixing into buffers
+/// allocates, including buffers longer than the mixer mixes at once.
+#[test]
+fn next_samples_do_not_allocate() {
+    let (mut manager, mut renderer) = start();
+    let _events = manager.events();
+    manager.play(ramp(3000));
+    let mut group = manager.create_group();
+    group.play(ramp(2000));
+    manager.play_at(ramp(1), StartTime::Frame(1500));
+    let mut buffer = vec![0; 3000];
+    let mut buffer_f32 = vec![0.0; 3000];
+    renderer.on_start_of_batch();
+    assert_eq!(
+        renderer.next_samples(&mut buffer).unwrap(),
+        (0, NextSample::MetadataChanged)
+    );
+    assert_eq!(
+        renderer.next_samples(&mut buffer[..2000]).unwrap(),
+        (2000, NextSample::Sample(4000))
+    );
+    assert_eq!(buffer[1500], 1501 * 2 + 1);
+    renderer.on_start_of_batch();
+    let (num_read, _) = renderer.next_samples_f32(&mut buffer_f32).unwrap();
+    assert_eq!(num_read, 1000);
+    assert_eq!(buffer_f32[999], 3000.0 / i16::MAX as f32);
+}
+
+/// With the assert-no-alloc feature this aborts if changing the tempo
+/// allocates on the audio thread.
+#[test]
+fn changing_tempo_does_not_allocate() {
+    let (mut manager, mut renderer) = start();
+    let (tempo, mut controller) = ramp(20000).with_adjustable_tempo().controllable();
+    manager.play(Box::new(tempo));
+    assert_eq!(render(&mut renderer, 1000)[999], 1000);
+    for tempo in [0.5, 2.0, 4.0, 1.0] {
+        controller.set_tempo(tempo);
+        render(&mut renderer, 1000);
+    }
+}
+
+/// With the assert-no-alloc feature this aborts if a change of the channel
+/// count of a sound allocates on the audio thread.
+#[test]
+fn changing_channel_count_does_not_allocate() {
+    let (mut manager, mut renderer) = start();
+    manager.play(Box::new(ChannelCountCycle::new(&[2, 6, 8, 1, 4, 2], 10)));
+    for _ in 0..4 {
+        render(&mut renderer, 100);
+    }
+}
+
+/// With the assert-no-alloc feature this aborts if changing the pitch or the
+/// channel count of a pitch shifted sound allocates on the audio thread.
+#[test]
+fn changing_pitch_does_not_allocate() {
+    let (mut manager, mut renderer) = start();
+    // The tempo stretcher allocates when the channel count increases so start
+    // with the highest.
+    let (pitched, mut controller) = ChannelCountCycle::new(&[8, 2, 1, 6], 700)
+        .with_adjustable_pitch_of(0.5)
+        .controllable();
+    manager.play(Box::new(pitched));
+    render(&mut renderer, 1000);
+    for pitch in [2.0, 1.5, 1.0] {
+        controller.set_pitch(pitch);
+        render(&mut renderer, 1000);
+    }
+}
+
+/// Silence that changes its channel count every `frames_per_count` frames,
+/// cycling through `counts`.
+struct ChannelCountCycle {
+    counts: &'static [u16],
+    frames_per_count: usize,
+    count_idx: usize,
+    samples_left: usize,
+}
+
+impl ChannelCountCycle {
+    fn new(counts: &'static [u16], frames_per_count: usize) -> ChannelCountCycle {
+        ChannelCountCycle {
+            counts,
+            frames_per_count,
+            count_idx: 0,
+            samples_left: counts[0] as usize * frames_per_count,
+        }
+    }
+}
+
+impl Sound for ChannelCountCycle {
+    fn channel_count(&self) -> u16 {
+        self.counts[self.count_idx]
+    }
+
+    fn sample_rate(&self) -> u32 {
+        10000
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        if self.samples_left == 0 {
+            self.count_idx = (self.count_idx + 1) % self.counts.len();
+            self.samples_left = self.channel_count() as usize * self.frames_per_count;
+            return Ok(NextSample::MetadataChanged);
+        }
+        self.samples_left -= 1;
+        Ok(NextSample::Sample(0))
+    }
+
+    fn on_start_of_batch(&mut self) {}
+}
+
+/// Read `len` samples one at a time.
+fn next_samples(renderer: &mut Renderer, len: usize) -> Vec<i16> {
+    (0..len)
+        .map(|_| match renderer.next_sample().unwrap() {
+            NextSample::Sample(s) => s,
+            other => panic!("unexpected {:?}", other),
+        })
+        .collect()
+}
+
+#[test]
+fn try_play_fails_when_the_queue_is_full() {
+    let (mut manager, mut renderer) = start();
+    for _ in 0..COMMAND_CAPACITY {
+        manager.try_play(ramp(1)).unwrap();
+    }
+    assert_eq!(manager.try_play(ramp(1)), Err(CommandQueueFull));
+    renderer.on_start_of_batch();
+    assert!(manager.try_play(ramp(1)).is_ok());
+}
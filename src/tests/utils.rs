@@ -30,3 +30,29 @@ fn test_duration_to_num_samples() {
         264_600_000_000
     );
 }
+
+#[test]
+fn test_num_samples_to_duration() {
+    assert_eq!(
+        num_samples_to_duration(44100, 1, 44100),
+        Duration::from_millis(1_000)
+    );
+    assert_eq!(
+        num_samples_to_duration(441, 2, 44100),
+        Duration::from_micros(5_000)
+    );
+    assert_eq!(
+        num_samples_to_duration(264_600_000_000, 6, 44100),
+        Duration::from_secs(1_000_000)
+    );
+}
+
+#[test]
+fn test_sample_conversion() {
+    assert_eq!(i16_to_f32(i16::MAX), 1.0);
+    assert_eq!(i16_to_f32(0), 0.0);
+    assert_eq!(f32_to_i16(i16_to_f32(1234)), 1234);
+    assert_eq!(f32_to_i16(-0.5), -16383);
+    assert_eq!(f32_to_i16(2.0), i16::MAX);
+    assert_eq!(f32_to_i16(-2.0), -i16::MAX);
+}
@@ -37,6 +37,25 @@ pub fn duration_to_num_samples(duration: Duration, channel_count: u16, sample_ra
     )
 }
 
+/// Return the amount of time it takes to play `num_samples` (the inverse of
+/// [duration_to_num_samples]).
+pub fn num_samples_to_duration(num_samples: u64, channel_count: u16, sample_rate: u32) -> Duration {
+    let micros = convert_num_samples(num_samples, channel_count, sample_rate, 1, 1_000_000);
+    Duration::from_micros(micros)
+}
+
+/// Convert an i16 sample to an f32 sample in the range -1.0 to 1.0.
+pub fn i16_to_f32(sample: i16) -> f32 {
+    sample as f32 / i16::MAX as f32
+}
+
+/// Convert an f32 sample to an i16 sample (the inverse of [i16_to_f32]).
+///
+/// Values outside of -1.0 to 1.0 are clipped.
+pub fn f32_to_i16(sample: f32) -> i16 {
+    (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16
+}
+
 #[cfg(test)]
 #[path = "./tests/utils.rs"]
 mod tests;